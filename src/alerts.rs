@@ -0,0 +1,349 @@
+use anyhow::bail;
+use chrono::{DateTime, Local};
+use queue::Queue;
+use serde::Deserialize;
+
+/// Number of fired/resolved alerts kept in the history
+const MAX_HISTORY: usize = 200;
+
+/// Node metric that alert rules are evaluated on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// Seconds since the tip block last advanced
+    TipAge,
+    /// Number of connected peers
+    Peers,
+    /// CPU usage of the node, in percent
+    Cpu,
+    /// Rejected transactions per received transactions of the session, in percent
+    RejectionRate,
+    /// Number of blocks behind the best known block
+    SyncLag,
+    /// 1 if the node answered the last refresh, 0 if all of its RPCs failed
+    NodeUp,
+}
+
+impl AlertMetric {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertMetric::TipAge => "Tip age",
+            AlertMetric::Peers => "Peers",
+            AlertMetric::Cpu => "CPU",
+            AlertMetric::RejectionRate => "Rejection rate",
+            AlertMetric::SyncLag => "Sync lag",
+            AlertMetric::NodeUp => "Node up",
+        }
+    }
+
+    fn format_value(&self, value: f64) -> String {
+        match self {
+            AlertMetric::TipAge => format!("{:.0}s", value),
+            AlertMetric::Peers => format!("{:.0}", value),
+            AlertMetric::Cpu => format!("{:.1}%", value),
+            AlertMetric::RejectionRate => format!("{:.2}%", value),
+            AlertMetric::SyncLag => format!("{:.0} blocks", value),
+            AlertMetric::NodeUp if value > 0.0 => String::from("up"),
+            AlertMetric::NodeUp => String::from("down"),
+        }
+    }
+}
+
+/// A threshold rule, declared as `[[alert]]` in the config file
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    /// Fires when the metric is greater than this value
+    pub above: Option<f64>,
+    /// Fires when the metric is less than this value
+    pub below: Option<f64>,
+    /// Seconds the condition must hold before the alert fires
+    #[serde(default)]
+    pub for_secs: u64,
+}
+
+impl AlertRule {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.name.is_empty() {
+            bail!("Alert rules must have a non-empty name");
+        }
+        match (self.above, self.below) {
+            (Some(x), None) | (None, Some(x)) if x.is_finite() => Ok(()),
+            (Some(_), None) | (None, Some(_)) => {
+                bail!("Threshold of alert `{}` must be a finite number", self.name)
+            }
+            _ => bail!(
+                "Alert `{}` must set exactly one of `above` and `below`",
+                self.name
+            ),
+        }
+    }
+
+    fn is_breached(&self, value: f64) -> bool {
+        self.above.is_some_and(|x| value > x) || self.below.is_some_and(|x| value < x)
+    }
+
+    /// The condition in a readable form, e.g. `Peers < 3 for 60s`
+    pub fn describe(&self) -> String {
+        let condition = match (self.above, self.below) {
+            (Some(x), _) => format!("> {}", x),
+            (_, Some(x)) => format!("< {}", x),
+            _ => String::new(),
+        };
+        if self.for_secs > 0 {
+            format!(
+                "{} {} for {}s",
+                self.metric.label(),
+                condition,
+                self.for_secs
+            )
+        } else {
+            format!("{} {}", self.metric.label(), condition)
+        }
+    }
+}
+
+/// Latest values of the metrics, collected from the dashboards every refresh.
+/// `None` if the value is unavailable, e.g. the node doesn't support `get_overview`
+#[derive(Clone, Debug, Default)]
+pub struct AlertMetrics {
+    pub tip_block_number: Option<u64>,
+    pub peers: Option<usize>,
+    pub cpu: Option<f64>,
+    pub rejection_rate: Option<f64>,
+    pub sync_lag: Option<u64>,
+    pub node_up: Option<bool>,
+}
+
+impl AlertMetrics {
+    /// Records a tip seen by one of the sources, keeping the highest one
+    pub fn observe_tip(&mut self, block_number: u64) {
+        self.tip_block_number = Some(
+            self.tip_block_number
+                .map_or(block_number, |x| x.max(block_number)),
+        );
+    }
+}
+
+/// Implemented by dashboard states and data that provide inputs of alert rules
+pub trait AlertSource {
+    fn collect_alert_metrics(&self, metrics: &mut AlertMetrics);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertStatus {
+    Fired,
+    Resolved,
+}
+
+#[derive(Clone, Debug)]
+pub struct AlertRecord {
+    pub time: DateTime<Local>,
+    pub node: String,
+    pub rule: String,
+    pub status: AlertStatus,
+    pub message: String,
+}
+
+struct RuleState {
+    rule: AlertRule,
+    breached_since: Option<DateTime<Local>>,
+    firing: bool,
+}
+
+/// Evaluates the alert rules on the sampled metrics and keeps the history of fired alerts
+pub struct AlertEngine {
+    rules: Vec<RuleState>,
+    // Highest tip block number and the time it was first seen
+    tip: Option<(u64, DateTime<Local>)>,
+    history: Queue<AlertRecord>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| RuleState {
+                    rule,
+                    breached_since: None,
+                    firing: false,
+                })
+                .collect(),
+            tip: None,
+            history: Queue::new(),
+        }
+    }
+
+    /// Forgets the state of all rules, e.g. after switching to another node. The history is kept
+    pub fn reset(&mut self) {
+        self.tip = None;
+        for state in self.rules.iter_mut() {
+            state.breached_since = None;
+            state.firing = false;
+        }
+    }
+
+    /// Evaluates all rules, returning the alerts fired or resolved by this evaluation
+    pub fn evaluate(
+        &mut self,
+        node: &str,
+        metrics: &AlertMetrics,
+        now: DateTime<Local>,
+    ) -> Vec<AlertRecord> {
+        if let Some(tip) = metrics.tip_block_number
+            && self.tip.is_none_or(|(last_tip, _)| tip > last_tip)
+        {
+            self.tip = Some((tip, now));
+        }
+        let tip_age = self
+            .tip
+            .map(|(_, time)| (now - time).num_milliseconds() as f64 / 1e3);
+        let mut records = vec![];
+        for state in self.rules.iter_mut() {
+            let value = match state.rule.metric {
+                AlertMetric::TipAge => tip_age,
+                AlertMetric::Peers => metrics.peers.map(|x| x as f64),
+                AlertMetric::Cpu => metrics.cpu,
+                AlertMetric::RejectionRate => metrics.rejection_rate,
+                AlertMetric::SyncLag => metrics.sync_lag.map(|x| x as f64),
+                AlertMetric::NodeUp => metrics.node_up.map(|x| if x { 1.0 } else { 0.0 }),
+            };
+            // Unavailable metrics leave the rule as it is
+            let Some(value) = value else {
+                continue;
+            };
+            let message = format!(
+                "{} is {} ({})",
+                state.rule.metric.label(),
+                state.rule.metric.format_value(value),
+                state.rule.describe()
+            );
+            if state.rule.is_breached(value) {
+                let since = *state.breached_since.get_or_insert(now);
+                if !state.firing && (now - since).num_seconds() >= state.rule.for_secs as i64 {
+                    state.firing = true;
+                    records.push(AlertRecord {
+                        time: now,
+                        node: node.to_string(),
+                        rule: state.rule.name.clone(),
+                        status: AlertStatus::Fired,
+                        message,
+                    });
+                }
+            } else {
+                state.breached_since = None;
+                if state.firing {
+                    state.firing = false;
+                    records.push(AlertRecord {
+                        time: now,
+                        node: node.to_string(),
+                        rule: state.rule.name.clone(),
+                        status: AlertStatus::Resolved,
+                        message,
+                    });
+                }
+            }
+        }
+        for record in records.iter() {
+            log::warn!(
+                "Alert `{}` of node `{}` {:?}: {}",
+                record.rule,
+                record.node,
+                record.status,
+                record.message
+            );
+            self.history.queue(record.clone()).unwrap();
+            if self.history.len() > MAX_HISTORY {
+                self.history.dequeue();
+            }
+        }
+        records
+    }
+
+    /// Names of the rules currently firing
+    pub fn firing(&self) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter(|x| x.firing)
+            .map(|x| x.rule.name.as_str())
+            .collect()
+    }
+
+    /// Fired and resolved alerts, oldest first
+    pub fn history(&self) -> &[AlertRecord] {
+        self.history.vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::{AlertEngine, AlertMetric, AlertMetrics, AlertRule, AlertStatus};
+
+    fn rule(
+        metric: AlertMetric,
+        above: Option<f64>,
+        below: Option<f64>,
+        for_secs: u64,
+    ) -> AlertRule {
+        AlertRule {
+            name: format!("{:?}", metric),
+            metric,
+            above,
+            below,
+            for_secs,
+        }
+    }
+
+    #[test]
+    fn fires_after_duration_and_resolves() {
+        let mut engine = AlertEngine::new(vec![rule(AlertMetric::Cpu, Some(90.0), None, 300)]);
+        let start = Local::now();
+        let metrics = AlertMetrics {
+            cpu: Some(95.0),
+            ..Default::default()
+        };
+        assert!(engine.evaluate("node", &metrics, start).is_empty());
+        assert!(
+            engine
+                .evaluate("node", &metrics, start + Duration::seconds(299))
+                .is_empty()
+        );
+        let fired = engine.evaluate("node", &metrics, start + Duration::seconds(300));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].status, AlertStatus::Fired);
+        assert_eq!(engine.firing(), vec!["Cpu"]);
+
+        let metrics = AlertMetrics {
+            cpu: Some(10.0),
+            ..Default::default()
+        };
+        let resolved = engine.evaluate("node", &metrics, start + Duration::seconds(301));
+        assert_eq!(resolved[0].status, AlertStatus::Resolved);
+        assert!(engine.firing().is_empty());
+        assert_eq!(engine.history().len(), 2);
+    }
+
+    #[test]
+    fn tracks_tip_age_and_skips_unavailable_metrics() {
+        let mut engine = AlertEngine::new(vec![
+            rule(AlertMetric::TipAge, Some(600.0), None, 0),
+            rule(AlertMetric::Peers, None, Some(3.0), 0),
+        ]);
+        let start = Local::now();
+        let mut metrics = AlertMetrics::default();
+        metrics.observe_tip(100);
+        assert!(engine.evaluate("node", &metrics, start).is_empty());
+        let fired = engine.evaluate("node", &metrics, start + Duration::seconds(601));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule, "TipAge");
+
+        metrics.observe_tip(101);
+        let resolved = engine.evaluate("node", &metrics, start + Duration::seconds(602));
+        assert_eq!(resolved[0].status, AlertStatus::Resolved);
+    }
+}
@@ -0,0 +1,106 @@
+use cursive::{
+    theme::{BaseColor, ColorStyle},
+    utils::markup::StyledString,
+    view::{IntoBoxedView, Nameable, Resizable},
+    views::{Dialog, LinearLayout, TextView},
+};
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    alerts::{AlertEngine, AlertRecord, AlertStatus},
+    components::{
+        UpdateToView,
+        dashboard::alerts::names::{ALERT_BANNER, ALERTS_TABLE},
+    },
+    declare_names,
+};
+
+declare_names!(names, "alerts_", ALERT_BANNER, ALERTS_TABLE);
+
+/// One-line banner listing the alerts currently firing, empty if there are none
+pub fn alert_banner() -> impl IntoBoxedView + use<> {
+    TextView::empty().with_name(ALERT_BANNER)
+}
+
+impl UpdateToView for AlertEngine {
+    fn update_to_view(&self, siv: &mut cursive::Cursive) {
+        let firing = self.firing();
+        siv.call_on_name(ALERT_BANNER, |view: &mut TextView| {
+            if firing.is_empty() {
+                view.set_content("");
+            } else {
+                view.set_content(StyledString::styled(
+                    format!(" ⚠ {} alert(s) firing: {}", firing.len(), firing.join(", ")),
+                    ColorStyle::front(BaseColor::Red),
+                ));
+            }
+        });
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum AlertsColumn {
+    Time,
+    Node,
+    Rule,
+    Status,
+    Message,
+}
+
+impl TableViewItem<AlertsColumn> for AlertRecord {
+    fn to_column(&self, column: AlertsColumn) -> String {
+        match column {
+            AlertsColumn::Time => self.time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            AlertsColumn::Node => self.node.clone(),
+            AlertsColumn::Rule => self.rule.clone(),
+            AlertsColumn::Status => match self.status {
+                AlertStatus::Fired => String::from("🔴 Fired"),
+                AlertStatus::Resolved => String::from("🟢 Resolved"),
+            },
+            AlertsColumn::Message => self.message.clone(),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: AlertsColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            AlertsColumn::Time => self.time.cmp(&other.time).reverse(),
+            AlertsColumn::Node => self.node.cmp(&other.node),
+            AlertsColumn::Rule => self.rule.cmp(&other.rule),
+            AlertsColumn::Status => (self.status as u8).cmp(&(other.status as u8)),
+            AlertsColumn::Message => self.message.cmp(&other.message),
+        }
+    }
+}
+
+/// History of fired and resolved alerts of this session, newest first
+pub fn alerts_history_dialog(engine: &AlertEngine) -> impl IntoBoxedView + use<> {
+    let firing = engine.firing();
+    Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(if firing.is_empty() {
+                String::from("No alerts are firing")
+            } else {
+                format!("Firing: {}", firing.join(", "))
+            }))
+            .child(TextView::new(" "))
+            .child(
+                TableView::<AlertRecord, AlertsColumn>::new()
+                    .column(AlertsColumn::Time, "Time", |c| c.width(20))
+                    .column(AlertsColumn::Node, "Node", |c| c.width(12))
+                    .column(AlertsColumn::Rule, "Rule", |c| c.width(20))
+                    .column(AlertsColumn::Status, "Status", |c| c.width(12))
+                    .column(AlertsColumn::Message, "Message", |c| c)
+                    .default_column(AlertsColumn::Time)
+                    .items(engine.history().to_vec())
+                    .with_name(ALERTS_TABLE)
+                    .min_size((120, 15)),
+            ),
+    )
+    .title("Alerts")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
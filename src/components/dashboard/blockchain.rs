@@ -3,19 +3,19 @@ use std::sync::{Arc, RwLock, mpsc};
 use anyhow::{Context, anyhow};
 use chrono::{DateTime, Local, TimeZone, Utc};
 use ckb_fixed_hash_core::H256;
-use ckb_jsonrpc_types::{BlockView, Consensus, Overview};
-use ckb_sdk::CkbRpcClient;
+use ckb_jsonrpc_types::{BlockView, Consensus};
 use cursive::{
     view::{IntoBoxedView, Nameable, Resizable, Scrollable},
-    views::{Button, Dialog, LinearLayout, ListView, NamedView, Panel, TextView},
+    views::{Button, Dialog, LinearLayout, NamedView, Panel, TextView},
 };
 use cursive_table_view::{TableView, TableViewItem};
 use queue::Queue;
+use serde::Serialize;
 use thousands::Separable;
-use tokio_stream::StreamExt;
 
 use crate::{
     CURRENT_TAB,
+    alerts::{AlertMetrics, AlertSource},
     components::{
         DashboardData, DashboardState, UpdateToView,
         dashboard::{
@@ -26,12 +26,19 @@ use crate::{
                 LIVE_CELLS_HISTORY, OCCUPIED_CAPACITY, OCCUPIED_CAPACITY_HISTORY, SCRIPT_TABLE,
             },
         },
+        details::block_explorer::{BlockQuery, block_detail_dialog},
         extract_epoch, get_average_block_time_and_estimated_epoch_time,
     },
-    declare_names, update_text,
+    declare_names,
+    exporter::{ExportMetrics, MetricsWriter},
+    history::{self, NodeHistory},
+    update_text,
     utils::{
-        bar_chart::SimpleBarChart, create_subscription_client, difficulty_to_string,
-        hash_rate_to_string, shorten_hex,
+        bar_chart::SimpleBarChart,
+        difficulty_to_string, hash_rate_to_string,
+        rpc_cache::CachedRpcClient,
+        shorten_hex,
+        subscription::{SubscriptionHandle, SubscriptionMultiplexer},
     },
 };
 
@@ -106,18 +113,19 @@ impl TableViewItem<BlockListColumn> for BlockListItem {
 
 #[derive(Clone)]
 pub struct BlockchainDashboardState {
-    client: CkbRpcClient,
+    client: CachedRpcClient,
     consensus: Option<Consensus>,
     overview_data: Option<GetOverviewOfBlockchainDasboardState>,
     subscription: BlockChainDashboardSubscriptionState,
+    history: NodeHistory,
 }
 #[derive(Clone)]
 pub struct BlockChainDashboardWithTcpConnState {
     blocks: Arc<RwLock<Queue<BlockListItem>>>,
-    stop_tx: tokio::sync::mpsc::Sender<()>,
+    subscription: SubscriptionHandle,
 }
-fn update_blocks(state: &BlockChainDashboardWithTcpConnState, block_view: BlockView) {
-    let mut guard = state.blocks.write().unwrap();
+fn update_blocks(blocks: &RwLock<Queue<BlockListItem>>, block_view: BlockView) {
+    let mut guard = blocks.write().unwrap();
     guard
         .queue(BlockListItem {
             time: Utc
@@ -139,6 +147,22 @@ pub enum BlockChainDashboardSubscriptionState {
     WithoutTcpConn,
 }
 
+/// Scales the chart to the recorded values, gaps in the history are left blank
+fn set_history_chart_data(view: &mut SimpleBarChart, series: &[f64]) {
+    let recorded = series.iter().copied().filter(|x| *x > 0.0);
+    let max_value = recorded.clone().fold(0.0, f64::max);
+    let min_value = recorded.fold(max_value, f64::min);
+    if max_value > 0.0 {
+        view.set_max_value(max_value);
+        view.set_diff_value(Some(min_value * 0.9));
+    } else {
+        // Nothing recorded yet, the chart stays blank
+        view.set_max_value(1.0);
+        view.set_diff_value(None);
+    }
+    view.set_data(series).unwrap();
+}
+
 impl UpdateToView for BlockchainDashboardState {
     fn update_to_view(&self, siv: &mut cursive::Cursive) {
         if let Some(data) = &self.overview_data {
@@ -148,20 +172,34 @@ impl UpdateToView for BlockchainDashboardState {
                 format!("{}", data.live_cells.separate_with_commas())
             );
             siv.call_on_name(LIVE_CELLS_HISTORY, |view: &mut SimpleBarChart| {
-                view.set_max_value(data.max_live_cells as f64);
-                view.set_diff_value(Some(data.min_live_cells as f64 * 0.9));
-                view.set_data(data.live_cells_history.vec()).unwrap();
+                match self.history.current_series(history::LIVE_CELLS) {
+                    Some(series) => set_history_chart_data(view, &series),
+                    None => {
+                        view.set_max_value(data.max_live_cells as f64);
+                        view.set_diff_value(Some(data.min_live_cells as f64 * 0.9));
+                        view.set_data(data.live_cells_history.vec()).unwrap();
+                    }
+                }
             });
             update_text!(
                 siv,
                 OCCUPIED_CAPACITY,
                 format!("{} CKB ", data.occupied_capacity.separate_with_commas())
             );
-            siv.call_on_name(OCCUPIED_CAPACITY_HISTORY, |view: &mut SimpleBarChart| {
-                view.set_max_value(data.max_occupied_capacity as f64);
-                view.set_diff_value(Some(data.min_occupied_capacity as f64 * 0.9));
-                view.set_data(data.occupied_capacity_history.vec()).unwrap();
-            });
+            siv.call_on_name(
+                OCCUPIED_CAPACITY_HISTORY,
+                |view: &mut SimpleBarChart| match self
+                    .history
+                    .current_series(history::OCCUPIED_CAPACITY)
+                {
+                    Some(series) => set_history_chart_data(view, &series),
+                    None => {
+                        view.set_max_value(data.max_occupied_capacity as f64);
+                        view.set_diff_value(Some(data.min_occupied_capacity as f64 * 0.9));
+                        view.set_data(data.occupied_capacity_history.vec()).unwrap();
+                    }
+                },
+            );
         } else {
             update_text!(siv, LIVE_CELLS, format!("N/A"));
             siv.call_on_name(LIVE_CELLS_HISTORY, |view: &mut SimpleBarChart| {
@@ -186,7 +224,16 @@ impl UpdateToView for BlockchainDashboardState {
                     }
                 },
             );
+            update_text!(
+                siv,
+                BLOCKS_SUBSCRIPTION_WARNING,
+                conn_data.subscription.describe()
+            );
         } else {
+            siv.call_on_name(
+                BLOCKS_TABLE,
+                |view: &mut TableView<BlockListItem, BlockListColumn>| view.clear(),
+            );
             siv.call_on_name(BLOCKS_SUBSCRIPTION_WARNING, |view:&mut TextView|{
                 view.set_content( "Subscribe TCP address is not set, latest transactions and rejected transactions won't be updated");
             });
@@ -194,6 +241,41 @@ impl UpdateToView for BlockchainDashboardState {
     }
 }
 
+impl AlertSource for BlockchainDashboardState {
+    fn collect_alert_metrics(&self, metrics: &mut AlertMetrics) {
+        // Pushed tips arrive earlier than the polled ones
+        if let BlockChainDashboardSubscriptionState::WithTcpConn(conn_data) = &self.subscription
+            && let Some(tip) = conn_data
+                .blocks
+                .read()
+                .unwrap()
+                .vec()
+                .iter()
+                .map(|x| x.block_number)
+                .max()
+        {
+            metrics.observe_tip(tip);
+        }
+    }
+}
+
+impl ExportMetrics for BlockchainDashboardState {
+    fn export_metrics(&self, metrics: &mut MetricsWriter) {
+        if let Some(data) = &self.overview_data {
+            metrics.gauge(
+                "ckb_live_cells",
+                "Estimated number of live cells",
+                data.live_cells as f64,
+            );
+            metrics.gauge(
+                "ckb_occupied_capacity",
+                "Total occupied capacity of live cells",
+                data.occupied_capacity as f64,
+            );
+        }
+    }
+}
+
 impl DashboardState for BlockchainDashboardState {
     fn accept_event(&mut self, event: &TUIEvent) {
         if let TUIEvent::OpenConsensusModal(sender) = event
@@ -205,12 +287,23 @@ impl DashboardState for BlockchainDashboardState {
                 }))
                 .unwrap();
         }
+        if let TUIEvent::OpenBlockDetail(block_hash, sender) = event {
+            let client = self.client.clone();
+            let block_hash = block_hash.clone();
+            sender
+                .send(Box::new(move |siv| {
+                    let dialog =
+                        block_detail_dialog(siv, client.inner(), BlockQuery::Hash(block_hash));
+                    siv.add_layer(dialog);
+                }))
+                .unwrap();
+        }
     }
     fn update_state(&mut self) -> anyhow::Result<()> {
         if let Some(data) = &mut self.overview_data {
             let overview = self
                 .client
-                .post::<(), Overview>("get_overview", ())
+                .get_overview()
                 .with_context(|| anyhow!("Unable to get overview data"))?;
             let occupied_capacity = overview.cells.total_occupied_capacities.value();
             data.max_occupied_capacity = data.max_occupied_capacity.max(occupied_capacity);
@@ -219,6 +312,8 @@ impl DashboardState for BlockchainDashboardState {
             data.occupied_capacity_history
                 .queue(occupied_capacity as f64)
                 .unwrap();
+            self.history
+                .record(history::OCCUPIED_CAPACITY, occupied_capacity as f64);
             if data.occupied_capacity_history.len() > 20 {
                 data.occupied_capacity_history.dequeue();
             }
@@ -228,6 +323,7 @@ impl DashboardState for BlockchainDashboardState {
             data.min_live_cells = data.min_live_cells.min(live_cells);
             data.live_cells = live_cells;
             data.live_cells_history.queue(live_cells as f64).unwrap();
+            self.history.record(history::LIVE_CELLS, live_cells as f64);
             if data.live_cells_history.len() > 20 {
                 data.live_cells_history.dequeue();
             }
@@ -244,74 +340,29 @@ impl DashboardState for BlockchainDashboardState {
 }
 
 impl BlockchainDashboardState {
-    #[allow(unused)]
     pub fn stop(&self) {
-        match &self.subscription {
-            BlockChainDashboardSubscriptionState::WithTcpConn(
-                block_chain_dashboard_with_tcp_conn_state,
-            ) => block_chain_dashboard_with_tcp_conn_state
-                .stop_tx
-                .blocking_send(())
-                .unwrap(),
-            BlockChainDashboardSubscriptionState::WithoutTcpConn => {}
-        };
+        if let BlockChainDashboardSubscriptionState::WithTcpConn(conn_data) = &self.subscription {
+            conn_data.subscription.stop();
+        }
     }
     pub fn new(
-        client: CkbRpcClient,
+        client: CachedRpcClient,
         fetch_overview_data: bool,
-        subscription_url: Option<String>,
+        multiplexer: Option<&SubscriptionMultiplexer>,
+        history: NodeHistory,
     ) -> Self {
-        let subscription = if let Some(url) = subscription_url {
-            let (stop_tx, mut stop_rx) = tokio::sync::mpsc::channel(1);
-            let result = BlockChainDashboardSubscriptionState::WithTcpConn(
-                BlockChainDashboardWithTcpConnState {
-                    blocks: Arc::new(RwLock::new(Queue::new())),
-                    stop_tx,
-                },
-            );
-            let self_cloned = result.clone();
-            std::thread::spawn(move || {
-                log::info!("Subscription thread of blockchain started");
-                let result = match tokio::runtime::Builder::new_current_thread()
-                    .enable_all()
-                    .build()
-                {
-                    Ok(o) => o,
-                    Err(e) => {
-                        log::error!("{:?}", e);
-                        panic!("Unable to start tokio runtime");
-                    }
-                }
-                .block_on(async move {
-                    let mut block_sub = create_subscription_client(&url)
-                        .await
-                        .with_context(|| anyhow!("Unable to connect to:{}", url))?
-                        .subscribe::<BlockView>("new_tip_block")
-                        .await
-                        .with_context(|| anyhow!("Unable to subscribe new blocks"))?;
-                    loop {
-                        tokio::select! {
-                            _ = stop_rx.recv() => {
-                                log::trace!("Exiting tx subscribing thread");
-                                break;
-                            }
-                            Some(Ok(r)) = block_sub.next() => {
-                                log::trace!("Received block sub: {:?}", r);
-                                update_blocks(match self_cloned {
-                                    BlockChainDashboardSubscriptionState::WithTcpConn(ref  block_chain_dashboard_with_tcp_conn_state) => block_chain_dashboard_with_tcp_conn_state,
-                                    BlockChainDashboardSubscriptionState::WithoutTcpConn => unreachable!(),
-                                }, r.1);
-                            }
-                        }
-                    }
-                    anyhow::Ok(())
+        let subscription = if let Some(multiplexer) = multiplexer {
+            let blocks = Arc::new(RwLock::new(Queue::new()));
+            let blocks_cloned = Arc::clone(&blocks);
+            let subscription =
+                multiplexer.subscribe("new_tip_block", move |block_view: BlockView| {
+                    log::trace!("Received block sub: {:?}", block_view);
+                    update_blocks(&blocks_cloned, block_view);
                 });
-                log::info!(
-                    "Tokio runtime of blockchain subscription exited: {:?}",
-                    result
-                );
-            });
-            result
+            BlockChainDashboardSubscriptionState::WithTcpConn(BlockChainDashboardWithTcpConnState {
+                blocks,
+                subscription,
+            })
         } else {
             BlockChainDashboardSubscriptionState::WithoutTcpConn
         };
@@ -325,16 +376,17 @@ impl BlockchainDashboardState {
                 None
             },
             subscription,
+            history,
         }
     }
 }
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Serialize)]
 enum ScriptType {
     Lock,
     Type,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 struct ScriptItem {
     name: String,
     script_type: ScriptType,
@@ -379,13 +431,13 @@ impl TableViewItem<ScriptColumn> for ScriptItem {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 pub struct GetOverviewOfBlockchainDashboardData {
     difficulty: f64,
     hash_rate: f64,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize)]
 pub struct BlockchainDashboardData {
     epoch: u64,
     epoch_block: u64,
@@ -400,6 +452,7 @@ pub struct BlockchainDashboardData {
 
     scripts: Vec<ScriptItem>,
 
+    #[serde(skip)]
     enable_overview_data: bool,
 }
 
@@ -407,12 +460,15 @@ impl DashboardData for BlockchainDashboardData {
     fn set_enable_overview_data(&mut self, flag: bool) {
         self.enable_overview_data = flag;
     }
+    fn panel_name(&self) -> &'static str {
+        "Blockchain"
+    }
     fn should_update(&self) -> bool {
         CURRENT_TAB.load(std::sync::atomic::Ordering::SeqCst) == 1
     }
     fn fetch_data_through_client(
         &mut self,
-        client: &ckb_sdk::CkbRpcClient,
+        client: &CachedRpcClient,
     ) -> anyhow::Result<Box<dyn DashboardData + Send + Sync>> {
         log::debug!("Updating: BlockchainDashboardData");
         let tip_header = client
@@ -422,7 +478,7 @@ impl DashboardData for BlockchainDashboardData {
         let (average_block_time, estimated_epoch_time) =
             get_average_block_time_and_estimated_epoch_time(&tip_header, client)?;
         let overview_data = if self.enable_overview_data {
-            let data = client.post::<(), Overview>("get_overview", ())?;
+            let data = client.get_overview()?;
             Some(GetOverviewOfBlockchainDashboardData {
                 difficulty: data.mining.difficulty.to_string().parse::<f64>().unwrap(),
                 hash_rate: data.mining.hash_rate.to_string().parse::<f64>().unwrap(),
@@ -529,6 +585,7 @@ impl UpdateToView for BlockchainDashboardData {
 }
 
 pub fn blockchain_dashboard(event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxedView + use<> {
+    let event_sender_cloned = event_sender.clone();
     LinearLayout::vertical()
         .child(
             LinearLayout::horizontal()
@@ -660,7 +717,7 @@ pub fn blockchain_dashboard(event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBo
                         .column(BlockListColumn::Time, "Time", |c| c)
                         .column(BlockListColumn::BlockNumber, "Block Number", |c| c)
                         .column(BlockListColumn::BlockHash, "Block Hash", |c| c)
-                        .on_submit(|siv, _row, index| {
+                        .on_submit(move |siv, _row, index| {
                             let line = siv
                                 .call_on_name(
                                     BLOCKS_TABLE,
@@ -669,7 +726,12 @@ pub fn blockchain_dashboard(event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBo
                                     },
                                 )
                                 .unwrap();
-                            siv.add_layer(block_modal(&line));
+                            event_sender_cloned
+                                .send(TUIEvent::OpenBlockDetail(
+                                    line.block_hash,
+                                    siv.cb_sink().clone(),
+                                ))
+                                .unwrap();
                         })
                         .with_name(BLOCKS_TABLE)
                         .min_size((100, 8)),
@@ -774,15 +836,73 @@ fn consensus_modal(data: &Consensus) -> impl IntoBoxedView + use<> {
     })
 }
 
-fn block_modal(data: &BlockListItem) -> impl IntoBoxedView {
-    Dialog::around(
-        ListView::new()
-            .child("Block Hash", TextView::new(data.block_hash.to_string()))
-            .child("Block Number", TextView::new(data.block_number.to_string()))
-            .child("Time", TextView::new(data.time.to_rfc2822())),
-    )
-    .title("Details of block")
-    .button("Close", |siv| {
-        siv.pop_layer();
-    })
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{
+        BlockChainDashboardSubscriptionState, BlockchainDashboardData, BlockchainDashboardState,
+    };
+    use crate::{
+        components::{DashboardData, DashboardState},
+        history::{MetricsHistory, NodeHistory},
+        mock_node::{MockNode, fixtures, wait_until},
+        utils::{rpc_cache::CachedRpcClient, subscription::SubscriptionMultiplexer},
+    };
+
+    #[test]
+    fn fetches_scripts_and_fails_without_consensus() {
+        let node = MockNode::start();
+        let mut data = BlockchainDashboardData::default();
+        data.set_enable_overview_data(true);
+        data.fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .unwrap();
+        assert_eq!(data.block_height, fixtures::TIP_NUMBER);
+        assert_eq!(data.scripts.len(), 4);
+        assert!(data.scripts[2].code_hash.ends_with(&"82".repeat(32)));
+        assert!(data.overview_data.is_some());
+
+        node.set_error("get_consensus", "consensus is unavailable");
+        let error = data
+            .fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .err()
+            .unwrap();
+        assert!(format!("{:#}", error).contains("consensus is unavailable"));
+    }
+
+    #[test]
+    fn state_receives_new_tip_blocks() {
+        let node = MockNode::start();
+        let multiplexer = SubscriptionMultiplexer::new(node.tcp_url());
+        let history = NodeHistory::new(Arc::new(MetricsHistory::in_memory()), "mock");
+        let mut state = BlockchainDashboardState::new(
+            CachedRpcClient::new(&node.rpc_url()),
+            true,
+            Some(&multiplexer),
+            history,
+        );
+        state.update_state().unwrap();
+        assert!(state.consensus.is_some());
+        let BlockChainDashboardSubscriptionState::WithTcpConn(conn) = &state.subscription else {
+            panic!("a state with a pubsub endpoint should subscribe");
+        };
+        node.wait_for_subscriber("new_tip_block");
+        for number in 1..=12 {
+            node.publish(
+                "new_tip_block",
+                fixtures::new_tip_block(fixtures::TIP_NUMBER + number),
+            );
+        }
+        wait_until("published blocks", || {
+            conn.blocks
+                .read()
+                .unwrap()
+                .vec()
+                .first()
+                .is_some_and(|x| x.block_number == fixtures::TIP_NUMBER + 3)
+        });
+        // Only the latest 10 blocks are kept
+        assert_eq!(conn.blocks.read().unwrap().len(), 10);
+        state.stop();
+    }
 }
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use crate::utils::rpc_cache::CachedRpcClient;
+
+/// Numbers of recent blocks the fee rate statistics are queried for, the node defaults to 21
+pub const TARGET_WINDOWS: [u64; 3] = [5, 21, 101];
+/// Lowest fee rate accepted by nodes with the default `min_fee_rate` config, in shannons/KB
+pub const MIN_FEE_RATE: u64 = 1000;
+/// Live fee rates kept for the percentiles and the histogram
+const MAX_SAMPLES: usize = 1000;
+/// Live percentiles are only taken into account with at least so many samples
+const MIN_SAMPLES: usize = 10;
+/// Lower bounds of the buckets of the histogram, in shannons/KB
+pub const HISTOGRAM_BUCKETS: [u64; 8] = [0, 1000, 1500, 2000, 3000, 5000, 10000, 50000];
+/// Width of the longest bar of the histogram
+const HISTOGRAM_WIDTH: usize = 30;
+
+/// Fee rate statistics of the transactions in the last `blocks` blocks
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FeeRateWindow {
+    pub blocks: u64,
+    pub mean: u64,
+    pub median: u64,
+}
+
+/// Queries the statistics of each of `TARGET_WINDOWS`, skipping windows without transactions
+pub fn fetch_fee_rate_windows(client: &CachedRpcClient) -> anyhow::Result<Vec<FeeRateWindow>> {
+    let mut windows = vec![];
+    for blocks in TARGET_WINDOWS {
+        if let Some(statistics) = client.get_fee_rate_statistics(Some(blocks.into()))? {
+            windows.push(FeeRateWindow {
+                blocks,
+                mean: statistics.mean.value(),
+                median: statistics.median.value(),
+            });
+        }
+    }
+    Ok(windows)
+}
+
+/// Fee rates of the latest transactions received through the `new_transaction` subscription
+#[derive(Clone, Default)]
+pub struct FeeRateSamples {
+    rates: VecDeque<u64>,
+}
+
+impl FeeRateSamples {
+    pub fn push(&mut self, fee_rate: u64) {
+        self.rates.push_back(fee_rate);
+        if self.rates.len() > MAX_SAMPLES {
+            self.rates.pop_front();
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.rates.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rates.is_empty()
+    }
+    /// Nearest-rank percentile, `percent` in range [0, 100]
+    pub fn percentile(&self, percent: u64) -> Option<u64> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut sorted = self.rates.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let rank = (percent as usize * sorted.len()).div_ceil(100).max(1);
+        Some(sorted[rank.min(sorted.len()) - 1])
+    }
+    /// Number of samples in each of `HISTOGRAM_BUCKETS`
+    pub fn histogram(&self) -> [usize; HISTOGRAM_BUCKETS.len()] {
+        let mut counts = [0; HISTOGRAM_BUCKETS.len()];
+        for rate in self.rates.iter() {
+            let bucket = HISTOGRAM_BUCKETS
+                .iter()
+                .rposition(|lower| rate >= lower)
+                .unwrap_or_default();
+            counts[bucket] += 1;
+        }
+        counts
+    }
+    /// One line per bucket with a bar scaled to the largest bucket
+    pub fn histogram_text(&self) -> String {
+        let counts = self.histogram();
+        let max_count = counts.iter().copied().max().unwrap_or_default().max(1);
+        let mut lines = vec![];
+        for (index, count) in counts.iter().enumerate() {
+            let range = match HISTOGRAM_BUCKETS.get(index + 1) {
+                Some(upper) => format!("{}-{}", HISTOGRAM_BUCKETS[index], upper - 1),
+                None => format!("{}+", HISTOGRAM_BUCKETS[index]),
+            };
+            lines.push(format!(
+                "{:>11} {:<width$} {}",
+                range,
+                "█".repeat(count * HISTOGRAM_WIDTH / max_count),
+                count,
+                width = HISTOGRAM_WIDTH
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Fee rates to use in shannons/KB, for being committed eventually, soon and as soon as possible
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeRecommendation {
+    pub low: u64,
+    pub medium: u64,
+    pub high: u64,
+}
+
+/// Low follows the median of the longest window, medium the median of the default window and
+/// high the mean of the shortest window. Each of them is raised to the 25th, 50th and 90th
+/// percentile of the live fee rates if the pool is busier than the committed blocks
+pub fn recommend_fee_rates(
+    windows: &[FeeRateWindow],
+    samples: &FeeRateSamples,
+) -> FeeRecommendation {
+    let live = |percent| {
+        if samples.len() >= MIN_SAMPLES {
+            samples.percentile(percent).unwrap_or_default()
+        } else {
+            0
+        }
+    };
+    let window = |blocks| windows.iter().find(|x| x.blocks == blocks);
+    let low = MIN_FEE_RATE
+        .max(
+            window(TARGET_WINDOWS[2])
+                .map(|x| x.median)
+                .unwrap_or_default(),
+        )
+        .max(live(25));
+    let medium = low
+        .max(
+            window(TARGET_WINDOWS[1])
+                .map(|x| x.median)
+                .unwrap_or_default(),
+        )
+        .max(live(50));
+    let high = medium
+        .max(
+            window(TARGET_WINDOWS[0])
+                .map(|x| x.mean)
+                .unwrap_or_default(),
+        )
+        .max(live(90));
+    FeeRecommendation { low, medium, high }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FeeRateSamples, FeeRateWindow, MIN_FEE_RATE, recommend_fee_rates};
+
+    fn samples(rates: impl IntoIterator<Item = u64>) -> FeeRateSamples {
+        let mut samples = FeeRateSamples::default();
+        for rate in rates {
+            samples.push(rate);
+        }
+        samples
+    }
+
+    #[test]
+    fn computes_percentiles_and_histogram() {
+        let samples = samples((1..=100).map(|x| x * 100));
+        assert_eq!(samples.percentile(25), Some(2500));
+        assert_eq!(samples.percentile(50), Some(5000));
+        assert_eq!(samples.percentile(100), Some(10000));
+        assert_eq!(samples.percentile(0), Some(100));
+        assert_eq!(FeeRateSamples::default().percentile(50), None);
+        assert_eq!(samples.histogram(), [9, 5, 5, 10, 20, 50, 1, 0]);
+        let text = samples.histogram_text();
+        assert_eq!(text.lines().count(), 8);
+        assert!(
+            text.lines()
+                .last()
+                .unwrap()
+                .trim_start()
+                .starts_with("50000+")
+        );
+    }
+
+    #[test]
+    fn recommends_from_windows_and_live_rates() {
+        let windows = [
+            FeeRateWindow {
+                blocks: 5,
+                mean: 4000,
+                median: 3000,
+            },
+            FeeRateWindow {
+                blocks: 21,
+                mean: 2500,
+                median: 2000,
+            },
+            FeeRateWindow {
+                blocks: 101,
+                mean: 1500,
+                median: 1200,
+            },
+        ];
+        let recommendation = recommend_fee_rates(&windows, &FeeRateSamples::default());
+        assert_eq!(
+            (
+                recommendation.low,
+                recommendation.medium,
+                recommendation.high
+            ),
+            (1200, 2000, 4000)
+        );
+
+        // A busy pool raises the recommendations
+        let recommendation = recommend_fee_rates(&windows, &samples([10000; 20]));
+        assert_eq!(recommendation.low, 10000);
+        assert_eq!(recommendation.high, 10000);
+
+        // Too few samples are ignored, and nothing is below the minimal fee rate
+        let recommendation = recommend_fee_rates(&[], &samples([10000; 5]));
+        assert_eq!(recommendation.low, MIN_FEE_RATE);
+        assert_eq!(recommendation.high, MIN_FEE_RATE);
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::{atomic::Ordering, mpsc};
+
+use anyhow::{Context, anyhow};
+use cursive::{
+    Cursive,
+    view::{IntoBoxedView, Nameable, Resizable},
+    views::{Dialog, LinearLayout, SelectView, TextView},
+};
+use cursive_async_view::AsyncView;
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    CURRENT_NODE, NodeProfile,
+    components::{
+        DashboardData, dashboard::TUIEvent, dashboard::overview::OverviewDashboardData,
+        is_overview_supported,
+    },
+    declare_names,
+    utils::rpc_cache::CachedRpcClient,
+};
+
+declare_names!(names, "fleet_", FLEET_TABLE);
+
+#[derive(Clone)]
+struct FleetNodeSummary {
+    tip_block_number: u64,
+    best_known_block_number: u64,
+    peers: usize,
+    pool_txs: Option<u64>,
+}
+
+impl FleetNodeSummary {
+    fn sync_progress(&self) -> f64 {
+        (self.tip_block_number as f64 / self.best_known_block_number.max(1) as f64 * 100.0)
+            .min(100.0)
+    }
+}
+
+#[derive(Clone)]
+struct FleetItem {
+    index: usize,
+    name: String,
+    rpc_url: String,
+    summary: Result<FleetNodeSummary, String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum FleetColumn {
+    Name,
+    RpcUrl,
+    TipHeight,
+    SyncProgress,
+    Peers,
+    PoolSize,
+    Status,
+}
+
+impl TableViewItem<FleetColumn> for FleetItem {
+    fn to_column(&self, column: FleetColumn) -> String {
+        let summary = self.summary.as_ref().ok();
+        match column {
+            FleetColumn::Name => {
+                if CURRENT_NODE.load(Ordering::SeqCst) == self.index {
+                    format!("* {}", self.name)
+                } else {
+                    self.name.clone()
+                }
+            }
+            FleetColumn::RpcUrl => self.rpc_url.clone(),
+            FleetColumn::TipHeight => summary
+                .map(|x| x.tip_block_number.to_string())
+                .unwrap_or("-".to_string()),
+            FleetColumn::SyncProgress => summary
+                .map(|x| format!("{:.2}%", x.sync_progress()))
+                .unwrap_or("-".to_string()),
+            FleetColumn::Peers => summary
+                .map(|x| x.peers.to_string())
+                .unwrap_or("-".to_string()),
+            FleetColumn::PoolSize => summary
+                .and_then(|x| x.pool_txs)
+                .map(|x| format!("{} txs", x))
+                .unwrap_or("N/A".to_string()),
+            FleetColumn::Status => match &self.summary {
+                Ok(_) => String::from("✓ OK"),
+                Err(e) => e.clone(),
+            },
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: FleetColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        let summary = self.summary.as_ref().ok();
+        let other_summary = other.summary.as_ref().ok();
+        match column {
+            FleetColumn::Name => self.name.cmp(&other.name),
+            FleetColumn::RpcUrl => self.rpc_url.cmp(&other.rpc_url),
+            FleetColumn::TipHeight => summary
+                .map(|x| x.tip_block_number)
+                .cmp(&other_summary.map(|x| x.tip_block_number)),
+            FleetColumn::SyncProgress => summary
+                .map(|x| x.sync_progress())
+                .partial_cmp(&other_summary.map(|x| x.sync_progress()))
+                .unwrap_or(std::cmp::Ordering::Equal),
+            FleetColumn::Peers => summary
+                .map(|x| x.peers)
+                .cmp(&other_summary.map(|x| x.peers)),
+            FleetColumn::PoolSize => summary
+                .and_then(|x| x.pool_txs)
+                .cmp(&other_summary.and_then(|x| x.pool_txs)),
+            FleetColumn::Status => self.summary.is_ok().cmp(&other.summary.is_ok()),
+        }
+    }
+}
+
+fn fetch_fleet_node_summary(client: &CachedRpcClient) -> anyhow::Result<FleetNodeSummary> {
+    let mut data = OverviewDashboardData::default();
+    data.set_enable_overview_data(is_overview_supported(client));
+    data.fetch_data_through_client(client)?;
+    let sync_state = client
+        .sync_state()
+        .with_context(|| anyhow!("Unable to get sync_state"))?;
+    Ok(FleetNodeSummary {
+        tip_block_number: data.tip_block_number,
+        best_known_block_number: sync_state.best_known_block_number.value(),
+        peers: data.inbound_peers + data.outbound_peers,
+        pool_txs: data.total_pool_txs(),
+    })
+}
+
+fn fetch_fleet(nodes: &[NodeProfile]) -> Vec<FleetItem> {
+    std::thread::scope(|scope| {
+        let handles = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, profile)| {
+                scope.spawn(move || FleetItem {
+                    index,
+                    name: profile.name.clone(),
+                    rpc_url: profile.rpc_url.clone(),
+                    summary: fetch_fleet_node_summary(&CachedRpcClient::new(&profile.rpc_url))
+                        .map_err(|e| format!("{:#}", e)),
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|x| x.join().unwrap()).collect()
+    })
+}
+
+/// Summary of all configured nodes, selecting a row switches the dashboard to that node
+pub fn fleet_summary_dialog(
+    siv: &mut Cursive,
+    nodes: &[NodeProfile],
+    event_sender: mpsc::Sender<TUIEvent>,
+) -> impl IntoBoxedView + use<> {
+    let nodes = nodes.to_vec();
+    let nodes_cloned = nodes.clone();
+    let event_sender_cloned = event_sender.clone();
+    Dialog::around(
+        AsyncView::new_with_bg_creator(
+            siv,
+            move || Ok(fetch_fleet(&nodes)),
+            move |items| {
+                let event_sender = event_sender.clone();
+                TableView::<FleetItem, FleetColumn>::new()
+                    .column(FleetColumn::Name, "Node", |c| c.width(15))
+                    .column(FleetColumn::RpcUrl, "RPC URL", |c| c.width(30))
+                    .column(FleetColumn::TipHeight, "Tip Height", |c| c.width(12))
+                    .column(FleetColumn::SyncProgress, "Sync", |c| c.width(9))
+                    .column(FleetColumn::Peers, "Peers", |c| c.width(7))
+                    .column(FleetColumn::PoolSize, "Pool Size", |c| c.width(12))
+                    .column(FleetColumn::Status, "Status", |c| c)
+                    .items(items)
+                    .on_submit(move |siv, _row, index| {
+                        let node_index = siv
+                            .call_on_name(
+                                names::FLEET_TABLE,
+                                |view: &mut TableView<FleetItem, FleetColumn>| {
+                                    view.borrow_item(index).unwrap().index
+                                },
+                            )
+                            .unwrap();
+                        event_sender.send(TUIEvent::SwitchNode(node_index)).ok();
+                        siv.pop_layer();
+                    })
+                    .with_name(names::FLEET_TABLE)
+                    .min_size((120, 10))
+            },
+        )
+        .with_width(120),
+    )
+    .title("Fleet")
+    .button("Refresh", move |siv| {
+        siv.pop_layer();
+        let dialog = fleet_summary_dialog(siv, &nodes_cloned, event_sender_cloned.clone());
+        siv.add_layer(dialog);
+    })
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
+
+pub fn node_selector_dialog(
+    nodes: &[NodeProfile],
+    event_sender: mpsc::Sender<TUIEvent>,
+) -> impl IntoBoxedView + use<> {
+    let current_node = CURRENT_NODE.load(Ordering::SeqCst);
+    let mut select_view = SelectView::<usize>::new();
+    for (index, profile) in nodes.iter().enumerate() {
+        select_view.add_item(
+            format!(
+                "{} {} ({})",
+                if index == current_node { "*" } else { " " },
+                profile.name,
+                profile.rpc_url
+            ),
+            index,
+        );
+    }
+    select_view.set_selection(current_node);
+    Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new("Select a node to monitor:"))
+            .child(select_view.on_submit(move |siv, index: &usize| {
+                event_sender.send(TUIEvent::SwitchNode(*index)).ok();
+                siv.pop_layer();
+            })),
+    )
+    .title("Switch Node")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
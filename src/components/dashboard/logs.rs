@@ -1,6 +1,5 @@
 use std::sync::{Arc, Mutex, mpsc};
 
-use anyhow::{Context, anyhow};
 use chrono::{DateTime, Local};
 use cursive::{
     reexports::ahash::HashMap,
@@ -10,7 +9,6 @@ use cursive::{
 use cursive_table_view::{TableView, TableViewItem};
 use queue::Queue;
 use serde::{Deserialize, Serialize};
-use tokio_stream::StreamExt;
 
 use crate::{
     components::{
@@ -19,12 +17,12 @@ use crate::{
             TUIEvent,
             logs::names::{
                 LOGS_TABLE, SESSION_OVERVIEW_DEBUG, SESSION_OVERVIEW_ERROR, SESSION_OVERVIEW_INFO,
-                SESSION_OVERVIEW_TRACE, SESSION_OVERVIEW_WARN,
+                SESSION_OVERVIEW_TRACE, SESSION_OVERVIEW_WARN, SUBSCRIPTION_STATUS,
             },
         },
     },
     declare_names, update_text,
-    utils::create_subscription_client,
+    utils::subscription::{SubscriptionHandle, SubscriptionMultiplexer},
 };
 
 declare_names!(
@@ -35,7 +33,8 @@ declare_names!(
     SESSION_OVERVIEW_ERROR,
     SESSION_OVERVIEW_DEBUG,
     SESSION_OVERVIEW_TRACE,
-    LOGS_TABLE
+    LOGS_TABLE,
+    SUBSCRIPTION_STATUS
 );
 #[derive(Copy, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -102,7 +101,7 @@ pub struct LogsDashboardInnerState {
     logs: Arc<Mutex<Queue<LogsItem>>>,
     category_sum: Arc<Mutex<HashMap<LogCategory, usize>>>,
     filter_option: FilterLogOption,
-    stop_tx: tokio::sync::mpsc::Sender<()>,
+    subscription: SubscriptionHandle,
 }
 #[derive(Clone)]
 pub enum LogsDashboardState {
@@ -120,8 +119,12 @@ pub struct CkbLogEntry {
     /// The date
     pub date: String,
 }
-fn update_log(data: &LogsDashboardInnerState, logs_entry: CkbLogEntry) {
-    match data.category_sum.lock().unwrap().entry(logs_entry.level) {
+fn update_log(
+    logs: &Mutex<Queue<LogsItem>>,
+    category_sum: &Mutex<HashMap<LogCategory, usize>>,
+    logs_entry: CkbLogEntry,
+) {
+    match category_sum.lock().unwrap().entry(logs_entry.level) {
         std::collections::hash_map::Entry::Occupied(mut occupied_entry) => {
             *occupied_entry.get_mut() += 1;
         }
@@ -129,7 +132,7 @@ fn update_log(data: &LogsDashboardInnerState, logs_entry: CkbLogEntry) {
             vacant_entry.insert(1);
         }
     };
-    let mut logs_guard = data.logs.lock().unwrap();
+    let mut logs_guard = logs.lock().unwrap();
 
     logs_guard
         .queue(LogsItem {
@@ -147,68 +150,29 @@ fn update_log(data: &LogsDashboardInnerState, logs_entry: CkbLogEntry) {
 }
 
 impl LogsDashboardState {
-    #[allow(unused)]
     pub fn stop(&self) {
-        match self {
-            LogsDashboardState::WithTcpConn(logs_dashboard_inner_state) => {
-                logs_dashboard_inner_state
-                    .stop_tx
-                    .blocking_send(())
-                    .unwrap();
-            }
-            LogsDashboardState::WithoutTcpConn => {}
+        if let LogsDashboardState::WithTcpConn(logs_dashboard_inner_state) = self {
+            logs_dashboard_inner_state.subscription.stop();
         }
     }
 
-    pub fn new(subscribe_addr: Option<String>) -> Self {
-        if let Some(addr) = subscribe_addr {
-            let (stop_tx, mut stop_rx) = tokio::sync::mpsc::channel(1);
-            let result = Self::WithTcpConn(LogsDashboardInnerState {
-                logs: Arc::new(Mutex::new(Queue::new())),
-                category_sum: Default::default(),
+    pub fn new(multiplexer: Option<&SubscriptionMultiplexer>) -> Self {
+        if let Some(multiplexer) = multiplexer {
+            let logs = Arc::new(Mutex::new(Queue::new()));
+            let category_sum: Arc<Mutex<HashMap<LogCategory, usize>>> = Default::default();
+            let subscription = {
+                let logs = Arc::clone(&logs);
+                let category_sum = Arc::clone(&category_sum);
+                multiplexer.subscribe("log", move |entry: CkbLogEntry| {
+                    update_log(&logs, &category_sum, entry);
+                })
+            };
+            Self::WithTcpConn(LogsDashboardInnerState {
+                logs,
+                category_sum,
                 filter_option: FilterLogOption::All,
-                stop_tx,
-            });
-            let self_cloned = result.clone();
-            let tcp_addr = addr.to_string();
-            std::thread::spawn(move || {
-                log::info!("Logs subscription thread started");
-                let result = match tokio::runtime::Builder::new_current_thread()
-                    .enable_all()
-                    .build()
-                {
-                    Ok(o) => o,
-                    Err(e) => {
-                        log::error!("{:?}", e);
-                        panic!("Unable to start tokio runtime");
-                    }
-                }
-                .block_on(async move {
-                    let mut logs_sub = create_subscription_client(&tcp_addr)
-                        .await
-                        .with_context(|| anyhow!("Unable to connect to: {}", tcp_addr))?
-                        .subscribe::<CkbLogEntry>("log")
-                        .await
-                        .with_context(|| anyhow!("Unable to subscribe logs"))?;
-
-                    loop {
-                        tokio::select! {
-                            _ = stop_rx.recv() => {
-                                break;
-                            }
-                            Some(Ok(r)) = logs_sub.next() => {
-                                update_log(match self_cloned {
-                                    LogsDashboardState::WithTcpConn(ref logs_dashboard_inner_state) => logs_dashboard_inner_state,
-                                    LogsDashboardState::WithoutTcpConn =>unreachable!(),
-                                }, r.1);
-                            }
-                        };
-                    }
-                    anyhow::Ok(())
-                });
-                log::info!("Tokio runtime exited: {:?}", result);
-            });
-            result
+                subscription,
+            })
         } else {
             Self::WithoutTcpConn
         }
@@ -244,6 +208,11 @@ impl UpdateToView for LogsDashboardState {
                 let logs_guard = logs_dashboard_inner_state.logs.lock().unwrap();
 
                 let count_guard = logs_dashboard_inner_state.category_sum.lock().unwrap();
+                update_text!(
+                    siv,
+                    SUBSCRIPTION_STATUS,
+                    logs_dashboard_inner_state.subscription.describe()
+                );
                 update_text!(
                     siv,
                     SESSION_OVERVIEW_TRACE,
@@ -316,7 +285,16 @@ impl UpdateToView for LogsDashboardState {
                     }
                 });
             }
-            LogsDashboardState::WithoutTcpConn => {}
+            LogsDashboardState::WithoutTcpConn => {
+                siv.call_on_name(LOGS_TABLE, |view: &mut TableView<LogsItem, LogsColumn>| {
+                    view.clear()
+                });
+                update_text!(
+                    siv,
+                    SUBSCRIPTION_STATUS,
+                    "Subscribe TCP address is not set, logs won't be updated"
+                );
+            }
         }
     }
 }
@@ -363,6 +341,7 @@ pub fn logs_dashboard(event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxedVie
         .child(Panel::new(
             LinearLayout::vertical()
                 .child(TextView::new("[Stream]"))
+                .child(TextView::new(" ").with_name(SUBSCRIPTION_STATUS))
                 .child(
                     LinearLayout::horizontal()
                         .child(TextView::new("Filters:").min_width(10))
@@ -435,3 +414,31 @@ fn log_entry_modal(data: &LogsItem) -> impl IntoBoxedView {
         siv.pop_layer();
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{LogCategory, LogsDashboardState};
+    use crate::{
+        mock_node::{MockNode, fixtures, wait_until},
+        utils::subscription::SubscriptionMultiplexer,
+    };
+
+    #[test]
+    fn counts_logs_per_category() {
+        let node = MockNode::start();
+        let multiplexer = SubscriptionMultiplexer::new(node.tcp_url());
+        let state = LogsDashboardState::new(Some(&multiplexer));
+        let LogsDashboardState::WithTcpConn(inner) = &state else {
+            panic!("a state with a pubsub endpoint should subscribe");
+        };
+        node.wait_for_subscriber("log");
+        node.publish("log", fixtures::log("warn", "Peer misbehaved"));
+        node.publish("log", fixtures::log("info", "Imported block"));
+        wait_until("published logs", || inner.logs.lock().unwrap().len() == 2);
+        let count = inner.category_sum.lock().unwrap();
+        assert_eq!(count.get(&LogCategory::Warn), Some(&1));
+        assert_eq!(count.get(&LogCategory::Error), None);
+        drop(count);
+        state.stop();
+    }
+}
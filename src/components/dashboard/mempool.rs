@@ -1,46 +1,69 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::RwLock;
 use std::sync::atomic::AtomicUsize;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
 use anyhow::anyhow;
 use chrono::Local;
 use chrono::TimeZone;
 use chrono::Utc;
+use ckb_fixed_hash_core::H256;
 use ckb_jsonrpc_types::Overview;
 use ckb_jsonrpc_types::PoolTransactionEntry;
 use ckb_jsonrpc_types::PoolTransactionReject;
-use ckb_sdk::CkbRpcClient;
 use cursive::view::Scrollable;
 use cursive::{
     view::{IntoBoxedView, Nameable, Resizable},
-    views::{LinearLayout, Panel, TextView},
+    views::{Dialog, LinearLayout, ListView, NamedView, Panel, SelectView, TextView},
 };
 use cursive_table_view::{TableView, TableViewItem};
 use queue::Queue;
+use serde::Serialize;
 use std::sync::mpsc;
-use tokio_stream::StreamExt;
 
+use crate::alerts::{AlertMetrics, AlertSource};
 use crate::components::DashboardState;
 use crate::components::dashboard::TUIEvent;
+use crate::components::dashboard::fee_advisor::{
+    FeeRateSamples, FeeRateWindow, FeeRecommendation, TARGET_WINDOWS, fetch_fee_rate_windows,
+    recommend_fee_rates,
+};
 use crate::components::dashboard::mempool::names::SUBSCRIBE_WARNING;
+use crate::components::dashboard::throughput::Throughput;
+use crate::components::details::transaction_inspector::transaction_detail_dialog;
 use crate::components::get_average_block_time_and_estimated_epoch_time;
 use crate::components::map_pool_transaction_to_reason;
-use crate::utils::create_subscription_client;
+use crate::components::pool_transaction_reject_description;
+use crate::exporter::{ExportMetrics, MetricsWriter};
+use crate::history::{self, NodeHistory};
+use crate::notifications::NotificationCenter;
+use crate::utils::bar_chart::SimpleBarChart;
+use crate::utils::rpc_cache::CachedRpcClient;
 use crate::utils::shorten_hex;
+use crate::utils::subscription::{SubscriptionHandle, SubscriptionMultiplexer};
 use crate::{
     CURRENT_TAB,
     components::{
         DashboardData, UpdateToView,
         dashboard::mempool::names::{
-            AVG_BLOCK_TIME, AVG_FEE_RATE, COMMITTING, LATEST_INCOMING_TX_TABLE, PENDING, PROPOSED,
-            REJECTION_RATE, REJECTION_TABLE, TOTAL_POOL_SIZE, TOTAL_REJECTION, TX_IN, TX_OUT,
+            AVG_BLOCK_TIME, AVG_FEE_RATE, COMMITTING, FEE_RATE_HISTOGRAM, FEE_RATE_WINDOWS,
+            LATEST_INCOMING_TX_TABLE, LIVE_FEE_PERCENTILES, PENDING, POOL_TREND, PROPOSED,
+            RECOMMENDED_HIGH, RECOMMENDED_LOW, RECOMMENDED_MEDIUM, REJECTED_TX_FILTER,
+            REJECTED_TX_TABLE, REJECTION_RATE, REJECTION_TABLE, TOTAL_POOL_SIZE, TOTAL_REJECTION,
+            TX_IN, TX_IN_HISTORY, TX_OUT, TX_OUT_HISTORY,
         },
     },
     declare_names, update_text,
 };
 
+/// Rejected transactions kept for the list of the session
+const MAX_REJECTED_TXS: usize = 100;
+/// Interval of refetching the fee rate statistics, which only change with new blocks
+const FEE_RATE_WINDOWS_INTERVAL: Duration = Duration::from_secs(30);
+
 declare_names!(
     names,
     "mempool_dashboard_",
@@ -56,7 +79,18 @@ declare_names!(
     REJECTION_RATE,
     REJECTION_TABLE,
     LATEST_INCOMING_TX_TABLE,
-    SUBSCRIBE_WARNING
+    SUBSCRIBE_WARNING,
+    FEE_RATE_WINDOWS,
+    LIVE_FEE_PERCENTILES,
+    FEE_RATE_HISTOGRAM,
+    RECOMMENDED_LOW,
+    RECOMMENDED_MEDIUM,
+    RECOMMENDED_HIGH,
+    TX_IN_HISTORY,
+    TX_OUT_HISTORY,
+    POOL_TREND,
+    REJECTED_TX_FILTER,
+    REJECTED_TX_TABLE
 );
 
 #[derive(Clone)]
@@ -65,39 +99,89 @@ pub struct MempoolDashboatdInnerState {
     total_transaction: Arc<AtomicUsize>,
     rejection_details: Arc<RwLock<HashMap<String, usize>>>,
     latest_incoming_txs: Arc<RwLock<Queue<LatestIncomingTxItem>>>,
-    stop_tx: tokio::sync::mpsc::Sender<()>,
+    fee_rate_samples: Arc<RwLock<FeeRateSamples>>,
+    rejected_txs: Arc<RwLock<Queue<RejectedTxItem>>>,
+    /// Reason category of the rejected transactions to show, all of them if `None`
+    rejected_tx_filter: Option<String>,
+    new_tx_subscription: SubscriptionHandle,
+    rejected_tx_subscription: SubscriptionHandle,
 }
 
 #[derive(Clone)]
-pub enum MempoolDashboardState {
+pub enum MempoolDashboardSubscriptionState {
     WithTcpConn(MempoolDashboatdInnerState),
     WithoutTcpConn,
 }
 
-fn update_latest_tx(state: &MempoolDashboatdInnerState, tx: PoolTransactionEntry) {
-    let mut guard = state.latest_incoming_txs.write().unwrap();
+#[derive(Clone)]
+pub struct MempoolDashboardState {
+    client: CachedRpcClient,
+    // Last fetched windows, kept if a later fetch fails
+    fee_rate_windows: Vec<FeeRateWindow>,
+    fee_rate_windows_fetched_at: Option<Instant>,
+    throughput: Throughput,
+    subscription: MempoolDashboardSubscriptionState,
+    history: NodeHistory,
+    notifications: Arc<Mutex<NotificationCenter>>,
+}
+
+fn update_latest_tx(
+    latest_incoming_txs: &RwLock<Queue<LatestIncomingTxItem>>,
+    fee_rate_samples: &RwLock<FeeRateSamples>,
+    total_transaction: &AtomicUsize,
+    tx: PoolTransactionEntry,
+) {
+    let fee_rate = tx.fee.value() * 1000 / tx.size.value();
+    let mut guard = latest_incoming_txs.write().unwrap();
     guard
         .queue(LatestIncomingTxItem {
-            tx_hash: shorten_hex(tx.transaction.hash.to_string(), 10, 11),
+            tx_hash: tx.transaction.hash,
             time: Utc
                 .timestamp_millis_opt(tx.timestamp.value() as i64)
                 .unwrap()
                 .into(),
             size_in_bytes: tx.size.value(),
-            fee_rate: tx.fee.value() * 1000 / tx.size.value(),
+            fee_rate,
         })
         .unwrap();
     if guard.len() > 20 {
         guard.dequeue();
     }
-    state
-        .total_transaction
-        .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    fee_rate_samples.write().unwrap().push(fee_rate);
+    total_transaction.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
 }
 
-fn update_rejected_tx(state: &MempoolDashboatdInnerState, rej_tx: PoolTransactionReject) {
-    let mut guard = state.rejection_details.write().unwrap();
+fn update_rejected_tx(
+    rejection_details: &RwLock<HashMap<String, usize>>,
+    rejected_txs: &RwLock<Queue<RejectedTxItem>>,
+    total_rejection: &AtomicUsize,
+    tx: PoolTransactionEntry,
+    rej_tx: PoolTransactionReject,
+) {
     let reason = map_pool_transaction_to_reason(&rej_tx);
+    {
+        let mut guard = rejected_txs.write().unwrap();
+        guard
+            .queue(RejectedTxItem {
+                tx_hash: tx.transaction.hash.clone(),
+                time: Utc
+                    .timestamp_millis_opt(tx.timestamp.value() as i64)
+                    .unwrap()
+                    .into(),
+                size_in_bytes: tx.size.value(),
+                cycles: tx.cycles.value(),
+                fee: tx.fee.value(),
+                inputs: tx.transaction.inner.inputs.len(),
+                outputs: tx.transaction.inner.outputs.len(),
+                category: reason,
+                description: pool_transaction_reject_description(&rej_tx).to_string(),
+            })
+            .unwrap();
+        if guard.len() > MAX_REJECTED_TXS {
+            guard.dequeue();
+        }
+    }
+    let mut guard = rejection_details.write().unwrap();
     match guard.entry(reason.to_string()) {
         std::collections::hash_map::Entry::Occupied(mut occupied_entry) => {
             *occupied_entry.get_mut() += 1;
@@ -106,105 +190,338 @@ fn update_rejected_tx(state: &MempoolDashboatdInnerState, rej_tx: PoolTransactio
             vacant_entry.insert(1);
         }
     };
-    state.total_rejection.store(
+    total_rejection.store(
         guard.iter().map(|x| *x.1).sum(),
         std::sync::atomic::Ordering::SeqCst,
     );
 }
 
+impl MempoolDashboatdInnerState {
+    /// Rejected transactions per received transactions of the session, in range [0, 1]
+    fn rejection_rate(&self) -> f64 {
+        self.total_rejection
+            .load(std::sync::atomic::Ordering::SeqCst) as f64
+            / self
+                .total_transaction
+                .load(std::sync::atomic::Ordering::SeqCst)
+                .max(1) /*Avoid NaN*/ as f64
+    }
+    /// Rejected transactions of the selected category, all if no category is selected
+    fn filtered_rejected_txs(&self) -> Vec<RejectedTxItem> {
+        self.rejected_txs
+            .read()
+            .unwrap()
+            .vec()
+            .iter()
+            .filter(|item| {
+                self.rejected_tx_filter
+                    .as_ref()
+                    .is_none_or(|category| item.category == category.as_str())
+            })
+            .cloned()
+            .collect()
+    }
+}
+
 impl MempoolDashboardState {
-    #[allow(unused)]
     pub fn stop(&self) {
-        match self {
-            MempoolDashboardState::WithTcpConn(mempool_dashboatd_inner_state) => {
-                mempool_dashboatd_inner_state.stop_tx.blocking_send(()).ok();
+        if let MempoolDashboardSubscriptionState::WithTcpConn(state) = &self.subscription {
+            state.new_tx_subscription.stop();
+            state.rejected_tx_subscription.stop();
+        }
+    }
+    /// Recommended fee rates, taking the live fee rates into account if subscribed
+    fn fee_recommendation(&self) -> FeeRecommendation {
+        match &self.subscription {
+            MempoolDashboardSubscriptionState::WithTcpConn(state) => recommend_fee_rates(
+                &self.fee_rate_windows,
+                &state.fee_rate_samples.read().unwrap(),
+            ),
+            MempoolDashboardSubscriptionState::WithoutTcpConn => {
+                recommend_fee_rates(&self.fee_rate_windows, &FeeRateSamples::default())
             }
-            MempoolDashboardState::WithoutTcpConn => {}
-        };
+        }
     }
-    pub fn new(subscribe_addr: Option<String>) -> Self {
-        if let Some(subscribe_addr) = subscribe_addr {
-            let (stop_tx, mut stop_rx) = tokio::sync::mpsc::channel(1);
-            let result = Self::WithTcpConn(MempoolDashboatdInnerState {
-                total_rejection: Arc::new(AtomicUsize::new(0)),
-                total_transaction: Arc::new(AtomicUsize::new(0)),
-                rejection_details: Arc::new(RwLock::new(HashMap::new())),
-                latest_incoming_txs: Arc::new(RwLock::new(Queue::new())),
-                stop_tx,
-            });
-            let self_cloned = result.clone();
-            let tcp_addr = subscribe_addr.to_string();
-            std::thread::spawn(move || {
-                log::info!("Subscribing thread started");
-
-                let result = match tokio::runtime::Builder::new_current_thread()
-                    .enable_all()
-                    .build()
-                {
-                    Ok(o) => o,
-                    Err(e) => {
-                        log::error!("{:?}", e);
-                        panic!("Unable to start tokio runtime");
-                    }
-                }
-                .block_on(async move {
-                    let mut new_tx_sub = create_subscription_client(&tcp_addr)
-                        .await
-                        .with_context(|| anyhow!("Unable to connect to: {}", tcp_addr))?
-                        .subscribe::<PoolTransactionEntry>("new_transaction")
-                        .await
-                        .with_context(|| anyhow!("Unable to subscribe new_transaction"))?;
-                    let mut new_rejection_sub = create_subscription_client(&tcp_addr)
-                        .await
-                        .with_context(|| anyhow!("Unable to connect to: {}", tcp_addr))?
-                        .subscribe::<(PoolTransactionEntry, PoolTransactionReject)>(
-                            "rejected_transaction",
-                        )
-                        .await
-                        .with_context(|| anyhow!("Unable to subscribe rejected_transaction"))?;
-                    log::trace!("Before subscribe select loop");
-                    loop {
-                        tokio::select! {
-                            _ = stop_rx.recv() => {
-                                log::trace!("Exiting tx subscribing thread");
-                                break;
-                            }
-                            Some(Ok(r)) = new_tx_sub.next() => {
-                                log::trace!("Received transaction sub: {:?}", r);
-                                update_latest_tx(match self_cloned{
-                                    MempoolDashboardState::WithTcpConn(ref mempool_dashboatd_inner_state) => mempool_dashboatd_inner_state,
-                                    MempoolDashboardState::WithoutTcpConn => unreachable!(),
-                                }, r.1);
-                            }
-                            Some(Ok(r)) = new_rejection_sub.next() => {
-                                log::trace!("Received rejected tx sub: {:?}", r);
-                                update_rejected_tx(match self_cloned{
-                                    MempoolDashboardState::WithTcpConn(ref mempool_dashboatd_inner_state) => mempool_dashboatd_inner_state,
-                                    MempoolDashboardState::WithoutTcpConn => unreachable!(),
-                                }, r.1.1);
-                            }
-                        }
-                    }
-                    anyhow::Ok(())
-                });
-                log::info!("Tokio runtime exited: {:?}", result);
-            });
-            result
+    pub fn new(
+        client: CachedRpcClient,
+        multiplexer: Option<&SubscriptionMultiplexer>,
+        history: NodeHistory,
+        notifications: Arc<Mutex<NotificationCenter>>,
+    ) -> Self {
+        let subscription = if let Some(multiplexer) = multiplexer {
+            let total_rejection = Arc::new(AtomicUsize::new(0));
+            let total_transaction = Arc::new(AtomicUsize::new(0));
+            let rejection_details = Arc::new(RwLock::new(HashMap::new()));
+            let latest_incoming_txs = Arc::new(RwLock::new(Queue::new()));
+            let fee_rate_samples = Arc::new(RwLock::new(FeeRateSamples::default()));
+            let rejected_txs = Arc::new(RwLock::new(Queue::new()));
+            let new_tx_subscription = {
+                let latest_incoming_txs = Arc::clone(&latest_incoming_txs);
+                let fee_rate_samples = Arc::clone(&fee_rate_samples);
+                let total_transaction = Arc::clone(&total_transaction);
+                multiplexer.subscribe("new_transaction", move |tx: PoolTransactionEntry| {
+                    log::trace!("Received transaction sub: {:?}", tx);
+                    update_latest_tx(
+                        &latest_incoming_txs,
+                        &fee_rate_samples,
+                        &total_transaction,
+                        tx,
+                    );
+                })
+            };
+            let rejected_tx_subscription = {
+                let rejection_details = Arc::clone(&rejection_details);
+                let rejected_txs = Arc::clone(&rejected_txs);
+                let total_rejection = Arc::clone(&total_rejection);
+                multiplexer.subscribe(
+                    "rejected_transaction",
+                    move |rej: (PoolTransactionEntry, PoolTransactionReject)| {
+                        log::trace!("Received rejected tx sub: {:?}", rej);
+                        update_rejected_tx(
+                            &rejection_details,
+                            &rejected_txs,
+                            &total_rejection,
+                            rej.0,
+                            rej.1,
+                        );
+                    },
+                )
+            };
+            MempoolDashboardSubscriptionState::WithTcpConn(MempoolDashboatdInnerState {
+                total_rejection,
+                total_transaction,
+                rejection_details,
+                latest_incoming_txs,
+                fee_rate_samples,
+                rejected_txs,
+                rejected_tx_filter: None,
+                new_tx_subscription,
+                rejected_tx_subscription,
+            })
         } else {
-            Self::WithoutTcpConn
+            MempoolDashboardSubscriptionState::WithoutTcpConn
+        };
+        Self {
+            client,
+            fee_rate_windows: vec![],
+            fee_rate_windows_fetched_at: None,
+            throughput: Throughput::default(),
+            subscription,
+            history,
+            notifications,
+        }
+    }
+    /// Refetches the fee rate windows every `FEE_RATE_WINDOWS_INTERVAL`. Errors are reported to
+    /// the notifications, the last fetched windows stay shown meanwhile
+    fn update_fee_rate_windows(&mut self) {
+        if self
+            .fee_rate_windows_fetched_at
+            .is_some_and(|x| x.elapsed() < FEE_RATE_WINDOWS_INTERVAL)
+        {
+            return;
+        }
+        self.fee_rate_windows_fetched_at = Some(Instant::now());
+        let result = fetch_fee_rate_windows(&self.client)
+            .with_context(|| anyhow!("Unable to get fee rate statistics"));
+        self.notifications
+            .lock()
+            .unwrap()
+            .track("FeeRateStatistics", &result);
+        if let Ok(windows) = result {
+            self.fee_rate_windows = windows;
+            if let Some(window) = self
+                .fee_rate_windows
+                .iter()
+                .find(|x| x.blocks == TARGET_WINDOWS[1])
+            {
+                self.history.record(history::FEE_RATE, window.median as f64);
+            }
         }
     }
 }
 
 impl DashboardState for MempoolDashboardState {
     fn update_state(&mut self) -> anyhow::Result<()> {
+        self.update_fee_rate_windows();
+        let received = match &self.subscription {
+            MempoolDashboardSubscriptionState::WithTcpConn(state) => Some(
+                state
+                    .total_transaction
+                    .load(std::sync::atomic::Ordering::SeqCst),
+            ),
+            MempoolDashboardSubscriptionState::WithoutTcpConn => None,
+        };
+        self.throughput.update(&self.client, received)?;
+        if let Some(pool_size) = self.throughput.pool_size() {
+            self.history.record(history::POOL_SIZE, pool_size as f64);
+        }
         Ok(())
     }
+    fn accept_event(&mut self, event: &TUIEvent) {
+        if let TUIEvent::FilterRejectedTxEvent(category) = event
+            && let MempoolDashboardSubscriptionState::WithTcpConn(state) = &mut self.subscription
+        {
+            state.rejected_tx_filter = category.clone();
+        }
+        if let TUIEvent::OpenTransactionDetail(tx_hash, sender) = event
+            && let MempoolDashboardSubscriptionState::WithTcpConn(_) = &self.subscription
+        {
+            let client = self.client.clone();
+            let tx_hash = tx_hash.clone();
+            sender
+                .send(Box::new(move |siv| {
+                    let dialog = transaction_detail_dialog(siv, client.inner(), tx_hash);
+                    siv.add_layer(dialog);
+                }))
+                .unwrap();
+        }
+    }
+}
+impl AlertSource for MempoolDashboardState {
+    fn collect_alert_metrics(&self, metrics: &mut AlertMetrics) {
+        if let MempoolDashboardSubscriptionState::WithTcpConn(state) = &self.subscription {
+            metrics.rejection_rate = Some(state.rejection_rate() * 100.0);
+        }
+    }
+}
+
+impl ExportMetrics for MempoolDashboardState {
+    fn export_metrics(&self, metrics: &mut MetricsWriter) {
+        if let Some((tx_in, tx_out)) = self.throughput.latest() {
+            metrics.gauge(
+                "ckb_pool_tx_in_per_minute",
+                "Transactions entering the pool per minute",
+                tx_in,
+            );
+            metrics.gauge(
+                "ckb_pool_tx_out_per_minute",
+                "Transactions committed or dropped from the pool per minute",
+                tx_out,
+            );
+        }
+        let recommendation = self.fee_recommendation();
+        for (level, fee_rate) in [
+            ("low", recommendation.low),
+            ("medium", recommendation.medium),
+            ("high", recommendation.high),
+        ] {
+            metrics.gauge_with_labels(
+                "ckb_recommended_fee_rate",
+                "Recommended fee rate in shannons/KB, by urgency",
+                vec![("level", level.to_string())],
+                fee_rate as f64,
+            );
+        }
+        if let MempoolDashboardSubscriptionState::WithTcpConn(state) = &self.subscription {
+            metrics.counter(
+                "ckb_received_transactions_total",
+                "Transactions received through the subscription in this session",
+                state
+                    .total_transaction
+                    .load(std::sync::atomic::Ordering::SeqCst) as f64,
+            );
+            for (reason, count) in state.rejection_details.read().unwrap().iter() {
+                metrics.counter_with_labels(
+                    "ckb_rejected_transactions_total",
+                    "Transactions rejected in this session, by reason",
+                    vec![("reason", reason.clone())],
+                    *count as f64,
+                );
+            }
+        }
+    }
 }
+
 impl UpdateToView for MempoolDashboardState {
     fn update_to_view(&self, siv: &mut cursive::Cursive) {
-        match self {
-            MempoolDashboardState::WithTcpConn(state) => {
+        update_text!(
+            siv,
+            FEE_RATE_WINDOWS,
+            if self.fee_rate_windows.is_empty() {
+                "N/A".to_string()
+            } else {
+                self.fee_rate_windows
+                    .iter()
+                    .map(|x| {
+                        format!(
+                            "Last {:>3} blocks: median {} / mean {}",
+                            x.blocks, x.median, x.mean
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        );
+        match self.throughput.latest() {
+            Some((tx_in, tx_out)) => {
+                update_text!(siv, TX_IN, format!("{:.1} tx/min", tx_in));
+                update_text!(siv, TX_OUT, format!("{:.1} tx/min", tx_out));
+            }
+            None => {
+                update_text!(siv, TX_IN, "Measuring..");
+                update_text!(siv, TX_OUT, "Measuring..");
+            }
+        }
+        // Both sparklines share the scale so that they can be compared
+        let max_rate = self
+            .throughput
+            .tx_in_history()
+            .iter()
+            .chain(self.throughput.tx_out_history())
+            .fold(0.0, |x: f64, y| x.max(*y));
+        for (name, history) in [
+            (TX_IN_HISTORY, self.throughput.tx_in_history()),
+            (TX_OUT_HISTORY, self.throughput.tx_out_history()),
+        ] {
+            siv.call_on_name(name, |view: &mut SimpleBarChart| {
+                view.set_max_value(max_rate.max(1.0));
+                view.set_data(history).unwrap();
+            });
+        }
+        update_text!(
+            siv,
+            POOL_TREND,
+            match self.throughput.net_outflow() {
+                None => "N/A".to_string(),
+                Some(x) if x > 0.0 => format!("🟢 Draining ({:.1} tx/min)", x),
+                Some(x) if x < 0.0 => format!("🔴 Backing up ({:.1} tx/min)", -x),
+                Some(_) => "Steady".to_string(),
+            }
+        );
+        let recommendation = self.fee_recommendation();
+        update_text!(
+            siv,
+            RECOMMENDED_LOW,
+            format!("{} shannons/KB", recommendation.low)
+        );
+        update_text!(
+            siv,
+            RECOMMENDED_MEDIUM,
+            format!("{} shannons/KB", recommendation.medium)
+        );
+        update_text!(
+            siv,
+            RECOMMENDED_HIGH,
+            format!("{} shannons/KB", recommendation.high)
+        );
+        match &self.subscription {
+            MempoolDashboardSubscriptionState::WithTcpConn(state) => {
+                let samples = state.fee_rate_samples.read().unwrap();
+                update_text!(
+                    siv,
+                    LIVE_FEE_PERCENTILES,
+                    match (
+                        samples.percentile(25),
+                        samples.percentile(50),
+                        samples.percentile(90)
+                    ) {
+                        (Some(p25), Some(p50), Some(p90)) =>
+                            format!("{} / {} / {} ({} txs)", p25, p50, p90, samples.len()),
+                        _ => "N/A".to_string(),
+                    }
+                );
+                update_text!(siv, FEE_RATE_HISTOGRAM, samples.histogram_text());
+                drop(samples);
                 update_text!(
                     siv,
                     TOTAL_REJECTION,
@@ -231,6 +548,49 @@ impl UpdateToView for MempoolDashboardState {
                         }
                     },
                 );
+                let mut categories = state
+                    .rejection_details
+                    .read()
+                    .unwrap()
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                categories.sort();
+                siv.call_on_name(
+                    REJECTED_TX_FILTER,
+                    |view: &mut SelectView<Option<String>>| {
+                        // Categories are only added within a session
+                        if view.len() != categories.len() + 1 {
+                            view.clear();
+                            view.add_item("All", None);
+                            for category in categories.iter() {
+                                view.add_item(category.clone(), Some(category.clone()));
+                            }
+                            let index = view
+                                .iter()
+                                .position(|(_, value)| *value == state.rejected_tx_filter)
+                                .unwrap_or_default();
+                            view.set_selection(index);
+                        }
+                    },
+                );
+                siv.call_on_name(
+                    REJECTED_TX_TABLE,
+                    |v: &mut TableView<RejectedTxItem, RejectedTxColumn>| {
+                        // Rows move as transactions arrive, so the selection follows the hash
+                        let selected = v
+                            .item()
+                            .and_then(|index| v.borrow_item(index))
+                            .map(|x| x.tx_hash.clone());
+                        let items = state.filtered_rejected_txs();
+                        let selected_index = selected
+                            .and_then(|tx_hash| items.iter().position(|x| x.tx_hash == tx_hash));
+                        v.set_items(items);
+                        if let Some(index) = selected_index {
+                            v.set_selected_item(index);
+                        }
+                    },
+                );
                 siv.call_on_name(
                     LATEST_INCOMING_TX_TABLE,
                     |v: &mut TableView<LatestIncomingTxItem, LatestIncomingTxColumn>| {
@@ -244,21 +604,37 @@ impl UpdateToView for MempoolDashboardState {
                         }
                     },
                 );
-                update_text!(siv, SUBSCRIBE_WARNING, " ");
-                let rejection_rate = state
-                    .total_rejection
-                    .load(std::sync::atomic::Ordering::SeqCst)
-                    as f64
-                    / state
-                        .total_transaction
-                        .load(std::sync::atomic::Ordering::SeqCst).max(1) /*Avoid NaN*/ as f64;
+                let mut status = state.new_tx_subscription.describe();
+                status.append_plain("   ");
+                status.append(state.rejected_tx_subscription.describe());
+                update_text!(siv, SUBSCRIBE_WARNING, status);
                 update_text!(
                     siv,
                     REJECTION_RATE,
-                    format!("{:.2} %", rejection_rate * 100.0)
+                    format!("{:.2} %", state.rejection_rate() * 100.0)
                 );
             }
-            MempoolDashboardState::WithoutTcpConn => {
+            MempoolDashboardSubscriptionState::WithoutTcpConn => {
+                update_text!(siv, LIVE_FEE_PERCENTILES, "N/A");
+                update_text!(
+                    siv,
+                    FEE_RATE_HISTOGRAM,
+                    "Subscribe TCP address is not set, the live fee rates are unavailable"
+                );
+                update_text!(siv, TOTAL_REJECTION, "N/A");
+                update_text!(siv, REJECTION_RATE, "N/A");
+                siv.call_on_name(
+                    REJECTION_TABLE,
+                    |v: &mut TableView<RejectionItem, RejectionColumn>| v.clear(),
+                );
+                siv.call_on_name(
+                    LATEST_INCOMING_TX_TABLE,
+                    |v: &mut TableView<LatestIncomingTxItem, LatestIncomingTxColumn>| v.clear(),
+                );
+                siv.call_on_name(
+                    REJECTED_TX_TABLE,
+                    |v: &mut TableView<RejectedTxItem, RejectedTxColumn>| v.clear(),
+                );
                 update_text!(
                     siv,
                     SUBSCRIBE_WARNING,
@@ -269,7 +645,7 @@ impl UpdateToView for MempoolDashboardState {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 pub struct GetOverviewOfMempoolDashboardData {
     total_pool_size_in_bytes: u64,
     pending_tx: u64,
@@ -277,20 +653,19 @@ pub struct GetOverviewOfMempoolDashboardData {
     committing_tx: u64,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize)]
 pub struct MempoolDashboardData {
     avg_fee_rate: Option<u64>,
-    tx_in: usize,
-    tx_out: usize,
     average_block_time: f64,
     overview_data: Option<GetOverviewOfMempoolDashboardData>,
+    #[serde(skip)]
     enable_fetch_overview: bool,
 }
 
 impl DashboardData for MempoolDashboardData {
     fn fetch_data_through_client(
         &mut self,
-        client: &CkbRpcClient,
+        client: &CachedRpcClient,
     ) -> anyhow::Result<Box<dyn DashboardData + Send + Sync>> {
         log::debug!("Updating: MempoolDashboardData");
         let fee_rate_statistics = client
@@ -301,7 +676,7 @@ impl DashboardData for MempoolDashboardData {
             .with_context(|| anyhow!("Unable to get tip header"))?;
         let overview_data = if self.enable_fetch_overview {
             let overview: Overview = client
-                .post("get_overview", ())
+                .get_overview()
                 .with_context(|| anyhow!("Unable to get overview info"))?;
             Some(GetOverviewOfMempoolDashboardData {
                 total_pool_size_in_bytes: overview.pool.total_tx_size.value(),
@@ -317,8 +692,6 @@ impl DashboardData for MempoolDashboardData {
         *self = Self {
             overview_data,
             avg_fee_rate: fee_rate_statistics.map(|x| x.mean.value()),
-            tx_in: 0,
-            tx_out: 0,
             average_block_time,
             enable_fetch_overview: self.enable_fetch_overview,
         };
@@ -332,6 +705,9 @@ impl DashboardData for MempoolDashboardData {
     fn set_enable_overview_data(&mut self, flag: bool) {
         self.enable_fetch_overview = flag;
     }
+    fn panel_name(&self) -> &'static str {
+        "Mempool"
+    }
 }
 
 impl UpdateToView for MempoolDashboardData {
@@ -363,8 +739,6 @@ impl UpdateToView for MempoolDashboardData {
                 Some(v) => format!("{} shannons/KB", v),
             }
         );
-        update_text!(siv, TX_IN, format!("{} tx/s", self.tx_in));
-        update_text!(siv, TX_OUT, format!("{} tx/s", self.tx_out));
         update_text!(
             siv,
             AVG_BLOCK_TIME,
@@ -403,7 +777,7 @@ impl TableViewItem<RejectionColumn> for RejectionItem {
 }
 #[derive(Clone)]
 struct LatestIncomingTxItem {
-    tx_hash: String,
+    tx_hash: H256,
     time: chrono::DateTime<Local>,
     size_in_bytes: u64,
     fee_rate: u64,
@@ -420,7 +794,7 @@ pub enum LatestIncomingTxColumn {
 impl TableViewItem<LatestIncomingTxColumn> for LatestIncomingTxItem {
     fn to_column(&self, column: LatestIncomingTxColumn) -> String {
         match column {
-            LatestIncomingTxColumn::TxHash => self.tx_hash.clone(),
+            LatestIncomingTxColumn::TxHash => shorten_hex(self.tx_hash.to_string(), 10, 11),
             LatestIncomingTxColumn::Time => format!(
                 "{}s ago",
                 chrono::Local::now().timestamp() - self.time.timestamp()
@@ -443,7 +817,94 @@ impl TableViewItem<LatestIncomingTxColumn> for LatestIncomingTxItem {
     }
 }
 
-pub fn mempool_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxedView + use<> {
+#[derive(Clone)]
+struct RejectedTxItem {
+    tx_hash: H256,
+    time: chrono::DateTime<Local>,
+    size_in_bytes: u64,
+    cycles: u64,
+    fee: u64,
+    inputs: usize,
+    outputs: usize,
+    category: &'static str,
+    description: String,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RejectedTxColumn {
+    TxHash,
+    Time,
+    SizeInBytes,
+    Fee,
+    Category,
+    Description,
+}
+
+impl TableViewItem<RejectedTxColumn> for RejectedTxItem {
+    fn to_column(&self, column: RejectedTxColumn) -> String {
+        match column {
+            RejectedTxColumn::TxHash => shorten_hex(self.tx_hash.to_string(), 10, 11),
+            RejectedTxColumn::Time => format!(
+                "{}s ago",
+                chrono::Local::now().timestamp() - self.time.timestamp()
+            ),
+            RejectedTxColumn::SizeInBytes => format!("{}", self.size_in_bytes),
+            RejectedTxColumn::Fee => format!("{}", self.fee),
+            RejectedTxColumn::Category => self.category.to_string(),
+            RejectedTxColumn::Description => self.description.clone(),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: RejectedTxColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            RejectedTxColumn::TxHash => self.tx_hash.cmp(&other.tx_hash),
+            RejectedTxColumn::Time => self.time.cmp(&other.time).reverse(),
+            RejectedTxColumn::SizeInBytes => self.size_in_bytes.cmp(&other.size_in_bytes),
+            RejectedTxColumn::Fee => self.fee.cmp(&other.fee),
+            RejectedTxColumn::Category => self.category.cmp(other.category),
+            RejectedTxColumn::Description => self.description.cmp(&other.description),
+        }
+    }
+}
+
+fn rejected_tx_dialog(item: &RejectedTxItem) -> impl IntoBoxedView + use<> {
+    Dialog::around(
+        ListView::new()
+            .child("Tx Hash:", TextView::new(format!("{:#x}", item.tx_hash)))
+            .child("Time:", TextView::new(item.time.to_rfc2822()))
+            .child(
+                "Size:",
+                TextView::new(format!("{} bytes", item.size_in_bytes)),
+            )
+            .child("Cycles:", TextView::new(format!("{}", item.cycles)))
+            .child(
+                "Fee:",
+                TextView::new(format!(
+                    "{} shannons ({} shannons/KB)",
+                    item.fee,
+                    item.fee * 1000 / item.size_in_bytes.max(1)
+                )),
+            )
+            .child(
+                "Inputs/Outputs:",
+                TextView::new(format!("{} / {}", item.inputs, item.outputs)),
+            )
+            .child("Category:", TextView::new(item.category))
+            .child("Reason:", TextView::new(&item.description))
+            .max_width(100)
+            .scrollable(),
+    )
+    .title("Rejected Transaction")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
+
+pub fn mempool_dashboard(event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxedView + use<> {
+    let filter_sender = event_sender.clone();
     LinearLayout::vertical()
         .child(
             LinearLayout::horizontal()
@@ -485,13 +946,26 @@ pub fn mempool_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxe
                             .child(TextView::new("[Throughput & Trends]"))
                             .child(
                                 LinearLayout::horizontal()
-                                    .child(TextView::new("• Tx In").min_width(20))
-                                    .child(TextView::empty().with_name(TX_IN)),
+                                    .child(TextView::new("• Tx In:").min_width(20))
+                                    .child(TextView::empty().with_name(TX_IN).min_width(16))
+                                    .child(NamedView::new(
+                                        TX_IN_HISTORY,
+                                        SimpleBarChart::new(&[]).unwrap(),
+                                    )),
                             )
                             .child(
                                 LinearLayout::horizontal()
                                     .child(TextView::new("• Tx Out:").min_width(20))
-                                    .child(TextView::empty().with_name(TX_OUT)),
+                                    .child(TextView::empty().with_name(TX_OUT).min_width(16))
+                                    .child(NamedView::new(
+                                        TX_OUT_HISTORY,
+                                        SimpleBarChart::new(&[]).unwrap(),
+                                    )),
+                            )
+                            .child(
+                                LinearLayout::horizontal()
+                                    .child(TextView::new("• Pool Trend:").min_width(20))
+                                    .child(TextView::empty().with_name(POOL_TREND)),
                             )
                             .child(
                                 LinearLayout::horizontal()
@@ -502,6 +976,43 @@ pub fn mempool_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxe
                     .min_width(50),
                 ),
         )
+        .child(
+            Panel::new(
+                LinearLayout::horizontal()
+                    .child(
+                        LinearLayout::vertical()
+                            .child(TextView::new("[Fee Advisor]"))
+                            .child(TextView::empty().with_name(FEE_RATE_WINDOWS))
+                            .child(
+                                LinearLayout::horizontal()
+                                    .child(TextView::new("• Live P25/P50/P90:").min_width(20))
+                                    .child(TextView::empty().with_name(LIVE_FEE_PERCENTILES)),
+                            )
+                            .child(
+                                LinearLayout::horizontal()
+                                    .child(TextView::new("  🟢 Low:").min_width(20))
+                                    .child(TextView::empty().with_name(RECOMMENDED_LOW)),
+                            )
+                            .child(
+                                LinearLayout::horizontal()
+                                    .child(TextView::new("  🟡 Medium:").min_width(20))
+                                    .child(TextView::empty().with_name(RECOMMENDED_MEDIUM)),
+                            )
+                            .child(
+                                LinearLayout::horizontal()
+                                    .child(TextView::new("  🔴 High:").min_width(20))
+                                    .child(TextView::empty().with_name(RECOMMENDED_HIGH)),
+                            )
+                            .min_width(50),
+                    )
+                    .child(
+                        LinearLayout::vertical()
+                            .child(TextView::new("[Live Fee Rates (shannons/KB)]"))
+                            .child(TextView::empty().with_name(FEE_RATE_HISTOGRAM)),
+                    ),
+            )
+            .scrollable(),
+        )
         .child(
             Panel::new(
                 LinearLayout::vertical()
@@ -528,6 +1039,51 @@ pub fn mempool_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxe
             )
             .scrollable(),
         )
+        .child(
+            Panel::new(
+                LinearLayout::vertical()
+                    .child(TextView::new("[Recent Rejected Transactions]"))
+                    .child(
+                        LinearLayout::horizontal()
+                            .child(TextView::new("Category:").min_width(10))
+                            .child(
+                                SelectView::<Option<String>>::new()
+                                    .popup()
+                                    .item("All", None)
+                                    .on_submit(move |_, category: &Option<String>| {
+                                        filter_sender
+                                            .send(TUIEvent::FilterRejectedTxEvent(category.clone()))
+                                            .ok();
+                                    })
+                                    .with_name(REJECTED_TX_FILTER),
+                            ),
+                    )
+                    .child(
+                        TableView::<RejectedTxItem, RejectedTxColumn>::new()
+                            .column(RejectedTxColumn::TxHash, "Tx Hash", |c| c)
+                            .column(RejectedTxColumn::Time, "Time", |c| c.width(10))
+                            .column(RejectedTxColumn::SizeInBytes, "Size", |c| c.width(8))
+                            .column(RejectedTxColumn::Fee, "Fee (shannons)", |c| c.width(15))
+                            .column(RejectedTxColumn::Category, "Category", |c| c)
+                            .column(RejectedTxColumn::Description, "Reason", |c| c)
+                            .default_column(RejectedTxColumn::Time)
+                            .on_submit(|siv, _row, index| {
+                                let item = siv
+                                    .call_on_name(
+                                        REJECTED_TX_TABLE,
+                                        |view: &mut TableView<RejectedTxItem, RejectedTxColumn>| {
+                                            view.borrow_item(index).unwrap().clone()
+                                        },
+                                    )
+                                    .unwrap();
+                                siv.add_layer(rejected_tx_dialog(&item));
+                            })
+                            .with_name(REJECTED_TX_TABLE)
+                            .min_size((50, 8)),
+                    ),
+            )
+            .scrollable(),
+        )
         .child(
             Panel::new(
                 LinearLayout::vertical()
@@ -544,6 +1100,25 @@ pub fn mempool_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxe
                                 |c| c,
                             )
                             .default_column(LatestIncomingTxColumn::Time)
+                            .on_submit(move |siv, _row, index| {
+                                let line = siv
+                                    .call_on_name(
+                                        LATEST_INCOMING_TX_TABLE,
+                                        |view: &mut TableView<
+                                            LatestIncomingTxItem,
+                                            LatestIncomingTxColumn,
+                                        >| {
+                                            view.borrow_item(index).unwrap().clone()
+                                        },
+                                    )
+                                    .unwrap();
+                                event_sender
+                                    .send(TUIEvent::OpenTransactionDetail(
+                                        line.tx_hash,
+                                        siv.cb_sink().clone(),
+                                    ))
+                                    .unwrap();
+                            })
                             .with_name(LATEST_INCOMING_TX_TABLE)
                             .min_size((50, 5)),
                     ),
@@ -551,3 +1126,181 @@ pub fn mempool_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxe
             .scrollable(),
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, atomic::Ordering};
+
+    use ckb_jsonrpc_types::{PoolTransactionEntry, PoolTransactionReject};
+    use serde_json::Value;
+
+    use super::{
+        MAX_REJECTED_TXS, MempoolDashboardState, MempoolDashboardSubscriptionState,
+        update_rejected_tx,
+    };
+    use crate::{
+        components::{
+            DashboardState,
+            dashboard::{
+                TUIEvent,
+                fee_advisor::{FeeRateWindow, TARGET_WINDOWS},
+            },
+        },
+        history::{MetricsHistory, NodeHistory},
+        mock_node::{MockNode, fixtures, wait_until},
+        utils::{rpc_cache::CachedRpcClient, subscription::SubscriptionMultiplexer},
+    };
+
+    fn new_state(
+        node: &MockNode,
+        multiplexer: Option<&SubscriptionMultiplexer>,
+    ) -> MempoolDashboardState {
+        MempoolDashboardState::new(
+            CachedRpcClient::new(&node.rpc_url()),
+            multiplexer,
+            NodeHistory::new(Arc::new(MetricsHistory::in_memory()), "mock"),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn receives_transactions_and_rejections() {
+        let node = MockNode::start();
+        let multiplexer = SubscriptionMultiplexer::new(node.tcp_url());
+        let state = new_state(&node, Some(&multiplexer));
+        let MempoolDashboardSubscriptionState::WithTcpConn(inner) = &state.subscription else {
+            panic!("a state with a pubsub endpoint should subscribe");
+        };
+        node.wait_for_subscriber("rejected_transaction");
+        node.publish("new_transaction", fixtures::new_transaction(1));
+        node.publish("new_transaction", fixtures::new_transaction(2));
+        node.publish(
+            "rejected_transaction",
+            fixtures::rejected_transaction(3, "LowFeeRate"),
+        );
+        wait_until("published messages", || {
+            inner.total_transaction.load(Ordering::SeqCst) == 2
+                && inner.total_rejection.load(Ordering::SeqCst) == 1
+        });
+        assert_eq!(inner.latest_incoming_txs.read().unwrap().len(), 2);
+        assert_eq!(
+            inner.rejection_details.read().unwrap()["Transaction fee lower than config"],
+            1
+        );
+        assert_eq!(inner.rejection_rate(), 0.5);
+        let rejected_txs = inner.rejected_txs.read().unwrap();
+        let rejected = &rejected_txs.vec()[0];
+        assert_eq!(rejected.category, "Transaction fee lower than config");
+        assert_eq!(
+            rejected.description,
+            "Rejected by the mock node: LowFeeRate"
+        );
+        assert_eq!((rejected.size_in_bytes, rejected.fee), (0x200, 0x400));
+        drop(rejected_txs);
+        // 0x400 shannons for 0x200 bytes
+        assert_eq!(
+            inner.fee_rate_samples.read().unwrap().percentile(50),
+            Some(2000)
+        );
+        state.stop();
+
+        let state = new_state(&node, None);
+        assert!(matches!(
+            state.subscription,
+            MempoolDashboardSubscriptionState::WithoutTcpConn
+        ));
+    }
+
+    #[test]
+    fn keeps_latest_rejected_txs_by_category() {
+        let node = MockNode::start();
+        let multiplexer = SubscriptionMultiplexer::new(node.tcp_url());
+        let mut state = new_state(&node, Some(&multiplexer));
+        let MempoolDashboardSubscriptionState::WithTcpConn(inner) = &state.subscription else {
+            panic!("a state with a pubsub endpoint should subscribe");
+        };
+        for byte in 0..=MAX_REJECTED_TXS as u8 {
+            let reason = if byte % 2 == 0 { "LowFeeRate" } else { "Full" };
+            let (tx, rej): (PoolTransactionEntry, PoolTransactionReject) =
+                serde_json::from_value(fixtures::rejected_transaction(byte, reason)).unwrap();
+            update_rejected_tx(
+                &inner.rejection_details,
+                &inner.rejected_txs,
+                &inner.total_rejection,
+                tx,
+                rej,
+            );
+        }
+        assert_eq!(
+            inner.total_rejection.load(Ordering::SeqCst),
+            MAX_REJECTED_TXS + 1
+        );
+        // The oldest rejection is dropped
+        let rejected_txs = inner.filtered_rejected_txs();
+        assert_eq!(rejected_txs.len(), MAX_REJECTED_TXS);
+        assert_eq!(
+            rejected_txs[0].tx_hash,
+            serde_json::from_value(Value::String(fixtures::hash(1))).unwrap()
+        );
+        assert_eq!(
+            rejected_txs[0].description,
+            "Rejected by the mock node: Full"
+        );
+
+        state.accept_event(&TUIEvent::FilterRejectedTxEvent(Some(
+            "Transaction fee lower than config".to_string(),
+        )));
+        let MempoolDashboardSubscriptionState::WithTcpConn(inner) = &state.subscription else {
+            unreachable!();
+        };
+        let rejected_txs = inner.filtered_rejected_txs();
+        assert_eq!(rejected_txs.len(), MAX_REJECTED_TXS / 2);
+        assert!(
+            rejected_txs
+                .iter()
+                .all(|x| x.description == "Rejected by the mock node: LowFeeRate")
+        );
+
+        state.accept_event(&TUIEvent::FilterRejectedTxEvent(None));
+        let MempoolDashboardSubscriptionState::WithTcpConn(inner) = &state.subscription else {
+            unreachable!();
+        };
+        assert_eq!(inner.filtered_rejected_txs().len(), MAX_REJECTED_TXS);
+        state.stop();
+    }
+
+    #[test]
+    fn fetches_fee_rate_windows() {
+        let node = MockNode::start();
+        let mut state = new_state(&node, None);
+        state.update_state().unwrap();
+        assert_eq!(state.fee_rate_windows.len(), TARGET_WINDOWS.len());
+        assert_eq!(state.fee_rate_windows[0].median, 1000);
+        assert_eq!(state.fee_recommendation().high, 1500);
+        // Not fetched again until the interval passed
+        state.update_state().unwrap();
+        assert_eq!(node.calls("get_fee_rate_statistics"), TARGET_WINDOWS.len());
+
+        // Windows without transactions are skipped
+        node.set_result("get_fee_rate_statistics", Value::Null);
+        let mut state = new_state(&node, None);
+        state.update_state().unwrap();
+        assert!(state.fee_rate_windows.is_empty());
+
+        // Errors are reported without failing the update, the last windows are kept
+        state.fee_rate_windows = vec![FeeRateWindow {
+            blocks: TARGET_WINDOWS[1],
+            mean: 2000,
+            median: 1500,
+        }];
+        state.fee_rate_windows_fetched_at = None;
+        // Uncached, the statistics are cached for a short while
+        state.client = CachedRpcClient::new(&node.rpc_url());
+        node.set_error("get_fee_rate_statistics", "statistics unavailable");
+        state.update_state().unwrap();
+        assert_eq!(state.fee_rate_windows.len(), 1);
+        assert!(state.throughput.pool_size().is_some());
+        let notifications = state.notifications.lock().unwrap();
+        assert_eq!(notifications.active()[0].source, "FeeRateStatistics");
+    }
+}
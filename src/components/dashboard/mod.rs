@@ -1,55 +1,79 @@
+pub mod alerts;
 pub mod blockchain;
+pub mod fee_advisor;
+pub mod fleet;
 pub mod logs;
 pub mod mempool;
+pub mod notifications;
 pub mod overview;
 pub mod peers;
+pub mod throughput;
 
-use std::sync::mpsc;
+use std::{collections::BTreeMap, sync::mpsc};
 
 use anyhow::{Context, anyhow};
 use ckb_jsonrpc_types::Overview;
-use ckb_sdk::CkbRpcClient;
 use cursive::{
-    Cursive,
+    Cursive, View,
+    theme::{BaseColor, ColorStyle},
+    utils::markup::StyledString,
     view::{IntoBoxedView, Nameable, Resizable},
     views::{LinearLayout, Panel, RadioGroup, TextView},
 };
 use cursive_aligned_view::Alignable;
 use cursive_spinner_view::SpinnerView;
+use serde::Serialize;
 
 use crate::{
     CURRENT_TAB,
+    alerts::{AlertMetrics, AlertSource},
     components::{
         DashboardData, UpdateToView,
         dashboard::{
-            blockchain::blockchain_dashboard,
+            alerts::alert_banner,
+            blockchain::{BlockchainDashboardState, blockchain_dashboard},
             logs::{FilterLogOption, logs_dashboard},
-            mempool::mempool_dashboard,
-            names::{MAIN_LAYOUT, REFRESHING_SPINNER, TITLE},
-            overview::basic_info_dashboard,
-            peers::peers_dashboard,
+            mempool::{MempoolDashboardState, mempool_dashboard},
+            names::{CHART_RANGE, MAIN_LAYOUT, REFRESHING_SPINNER, STALE_PANELS, TITLE},
+            notifications::notification_indicator,
+            overview::{OverviewDashboardState, basic_info_dashboard},
+            peers::{PeersDashboardData, peers_dashboard},
         },
     },
+    config::{DashboardTab, KeyBindings},
     declare_names,
+    history::HistoryRange,
+    utils::rpc_cache::CachedRpcClient,
 };
 
-declare_names!(names, "dashboard_", TITLE, REFRESHING_SPINNER, MAIN_LAYOUT);
-#[derive(Clone, Default)]
+declare_names!(
+    names,
+    "dashboard_",
+    TITLE,
+    REFRESHING_SPINNER,
+    MAIN_LAYOUT,
+    CHART_RANGE,
+    STALE_PANELS
+);
+#[derive(Clone, Default, Serialize)]
 pub struct GeneralDashboardData {
+    pub node_name: String,
     pub network_name: String,
     pub version: Option<String>,
+    #[serde(skip)]
     enable_fetch_overview_data: bool,
 }
 impl UpdateToView for GeneralDashboardData {
     fn update_to_view(&self, siv: &mut cursive::Cursive) {
         siv.call_on_name(names::TITLE, |view: &mut TextView| {
             view.set_content(format!(
-                "{} CKB Node Monitor {}",
+                "{} CKB Node Monitor {} ({})",
                 self.network_name,
                 match &self.version {
                     Some(v) => v,
                     None => "<unknown version>",
-                }
+                },
+                self.node_name
             ));
         });
     }
@@ -58,7 +82,7 @@ impl UpdateToView for GeneralDashboardData {
 impl DashboardData for GeneralDashboardData {
     fn fetch_data_through_client(
         &mut self,
-        client: &CkbRpcClient,
+        client: &CachedRpcClient,
     ) -> anyhow::Result<Box<dyn DashboardData + Send + Sync>> {
         log::debug!("Updating: GeneralDashboardData");
         let block_chain_info = client
@@ -66,13 +90,14 @@ impl DashboardData for GeneralDashboardData {
             .with_context(|| anyhow!("Unable to get block chain info"))?;
         let version = if self.enable_fetch_overview_data {
             let overview_info: Overview = client
-                .post("get_overview", ())
+                .get_overview()
                 .with_context(|| anyhow!("Unable to get overview info"))?;
             Some(overview_info.version)
         } else {
             None
         };
         *self = Self {
+            node_name: self.node_name.clone(),
             network_name: match block_chain_info.chain.as_str() {
                 "ckb" => "[Meepo Mainnet]".to_string(),
                 "ckb_testnet" => "[Mirana Testnet]".to_string(),
@@ -88,27 +113,72 @@ impl DashboardData for GeneralDashboardData {
     fn set_enable_overview_data(&mut self, flag: bool) {
         self.enable_fetch_overview_data = flag;
     }
+
+    fn panel_name(&self) -> &'static str {
+        "General"
+    }
+}
+
+/// Dashboard states of one node after a refresh, `None` for the ones whose last update failed. These
+/// keep their last values, which aren't used for alerts and metrics anymore
+#[derive(Clone, Copy)]
+pub struct UpdatedSources<'a> {
+    pub overview: Option<&'a OverviewDashboardState>,
+    pub blockchain: Option<&'a BlockchainDashboardState>,
+    pub mempool: Option<&'a MempoolDashboardState>,
+    pub peers: Option<&'a PeersDashboardData>,
+}
+
+impl UpdatedSources<'_> {
+    /// Whether the node answered any of the updates
+    pub fn node_up(&self) -> bool {
+        self.overview.is_some()
+            || self.blockchain.is_some()
+            || self.mempool.is_some()
+            || self.peers.is_some()
+    }
+
+    pub fn alert_metrics(&self) -> AlertMetrics {
+        let mut metrics = AlertMetrics {
+            node_up: Some(self.node_up()),
+            ..Default::default()
+        };
+        let sources: [Option<&dyn AlertSource>; 4] = [
+            self.overview.map(|x| x as _),
+            self.blockchain.map(|x| x as _),
+            self.mempool.map(|x| x as _),
+            self.peers.map(|x| x as _),
+        ];
+        for source in sources.into_iter().flatten() {
+            source.collect_alert_metrics(&mut metrics);
+        }
+        metrics
+    }
 }
 
 pub fn dashboard(
     event_sender: mpsc::Sender<TUIEvent>,
     cursive: &mut Cursive,
+    keys: &KeyBindings,
+    default_tab: DashboardTab,
 ) -> impl IntoBoxedView + use<> {
-    let event_sender_0 = event_sender.clone();
-    let event_sender_1 = event_sender.clone();
-    let event_sender_2 = event_sender.clone();
-    let event_sender_3 = event_sender.clone();
-    let event_sender_4 = event_sender.clone();
+    let event_sender_cloned = event_sender.clone();
     let mut tab_selector = RadioGroup::<usize>::new().on_change(move |siv, value: &usize| {
-        match value {
-            idx @ 0 => switch_panel(siv, basic_info_dashboard(event_sender_0.clone()), *idx),
-            idx @ 1 => switch_panel(siv, blockchain_dashboard(event_sender_1.clone()), *idx),
-            idx @ 2 => switch_panel(siv, mempool_dashboard(event_sender_2.clone()), *idx),
-            idx @ 3 => switch_panel(siv, peers_dashboard(event_sender_3.clone()), *idx),
-            idx @ 4 => switch_panel(siv, logs_dashboard(event_sender_4.clone()), *idx),
-            _ => unreachable!(),
-        };
+        switch_panel(
+            siv,
+            tab_panel(DashboardTab::ALL[*value], event_sender_cloned.clone()),
+            *value,
+        );
     });
+    let mut tab_button = |tab: DashboardTab, label: &str| {
+        let button = tab_selector.button(tab.index(), label);
+        if tab == default_tab {
+            button.selected()
+        } else {
+            button
+        }
+    };
+    CURRENT_TAB.store(default_tab.index(), std::sync::atomic::Ordering::SeqCst);
 
     Panel::new(
         LinearLayout::vertical()
@@ -119,25 +189,54 @@ pub fn dashboard(
                     .child(
                         SpinnerView::new(cursive.cb_sink().clone()).with_name(REFRESHING_SPINNER),
                     )
+                    .child(alert_banner())
+                    .child(TextView::empty().with_name(STALE_PANELS))
                     .align_center(),
             )
             .child(
                 LinearLayout::horizontal()
-                    .child(tab_selector.button(0, "Overview").fixed_width(15))
-                    .child(tab_selector.button(1, "Blockchain").fixed_width(17))
-                    .child(tab_selector.button(2, "Mempool").fixed_width(15))
-                    .child(tab_selector.button(3, "Peers").fixed_width(15))
-                    .child(tab_selector.button(4, "Logs").fixed_width(15))
+                    .child(tab_button(DashboardTab::Overview, "Overview").fixed_width(15))
+                    .child(tab_button(DashboardTab::Blockchain, "Blockchain").fixed_width(17))
+                    .child(tab_button(DashboardTab::Mempool, "Mempool").fixed_width(15))
+                    .child(tab_button(DashboardTab::Peers, "Peers").fixed_width(15))
+                    .child(tab_button(DashboardTab::Logs, "Logs").fixed_width(15))
                     .align_center(),
             )
-            .child(basic_info_dashboard(event_sender.clone()))
-            .child(Panel::new(TextView::new(
-                "Press [Q] to quit, [Tab] to switch panels, [R] to refresh, [M] for menu",
-            )))
+            .child(tab_panel(default_tab, event_sender))
+            .child(Panel::new(
+                LinearLayout::horizontal()
+                    .child(TextView::new(format!(
+                        "Press [{}] to quit, [Tab] to switch panels, [{}] to refresh, [{}] for menu, [{}] to switch node, [{}] for fleet, [{}] for chart range, [{}] for alerts, [{}] for notifications",
+                        keys.quit.to_ascii_uppercase(),
+                        keys.refresh.to_ascii_uppercase(),
+                        keys.menu.to_ascii_uppercase(),
+                        keys.switch_node.to_ascii_uppercase(),
+                        keys.fleet.to_ascii_uppercase(),
+                        keys.chart_range.to_ascii_uppercase(),
+                        keys.alerts.to_ascii_uppercase(),
+                        keys.notifications.to_ascii_uppercase(),
+                    )))
+                    .child(TextView::new(" "))
+                    .child(
+                        TextView::new(chart_range_label(HistoryRange::current()))
+                            .with_name(CHART_RANGE),
+                    )
+                    .child(notification_indicator()),
+            ))
             .with_name(MAIN_LAYOUT),
     )
 }
 
+fn tab_panel(tab: DashboardTab, event_sender: mpsc::Sender<TUIEvent>) -> Box<dyn View> {
+    match tab {
+        DashboardTab::Overview => basic_info_dashboard(event_sender).into_boxed_view(),
+        DashboardTab::Blockchain => blockchain_dashboard(event_sender).into_boxed_view(),
+        DashboardTab::Mempool => mempool_dashboard(event_sender).into_boxed_view(),
+        DashboardTab::Peers => peers_dashboard(event_sender).into_boxed_view(),
+        DashboardTab::Logs => logs_dashboard(event_sender).into_boxed_view(),
+    }
+}
+
 fn switch_panel(siv: &mut Cursive, panel: impl IntoBoxedView + 'static, panel_index: usize) {
     siv.call_on_name(MAIN_LAYOUT, move |view: &mut LinearLayout| {
         view.remove_child(3);
@@ -146,6 +245,39 @@ fn switch_panel(siv: &mut Cursive, panel: impl IntoBoxedView + 'static, panel_in
     CURRENT_TAB.store(panel_index, std::sync::atomic::Ordering::SeqCst);
 }
 
+fn chart_range_label(range: HistoryRange) -> String {
+    format!("(Charts: {})", range.label())
+}
+
+/// Switches history charts to the next range
+pub fn cycle_chart_range(siv: &mut Cursive) {
+    let range = HistoryRange::cycle();
+    siv.call_on_name(CHART_RANGE, |view: &mut TextView| {
+        view.set_content(chart_range_label(range));
+    });
+}
+
+/// Marks the panels whose last fetch failed, they keep showing their last fetched data
+pub fn update_stale_panels(siv: &mut Cursive, stale: &BTreeMap<&'static str, String>) {
+    siv.call_on_name(STALE_PANELS, |view: &mut TextView| {
+        if stale.is_empty() {
+            view.set_content("");
+        } else {
+            view.set_content(StyledString::styled(
+                format!(
+                    " ⚠ Stale: {}",
+                    stale
+                        .iter()
+                        .map(|(panel, error)| format!("{} ({})", panel, error))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                ColorStyle::front(BaseColor::Yellow),
+            ));
+        }
+    });
+}
+
 pub fn set_loading(siv: &mut Cursive, loading: bool) {
     siv.call_on_name(REFRESHING_SPINNER, move |view: &mut SpinnerView| {
         if loading {
@@ -157,5 +289,116 @@ pub fn set_loading(siv: &mut Cursive, loading: bool) {
 }
 pub enum TUIEvent {
     FilterLogEvent(FilterLogOption),
+    /// Show only rejected transactions of the reason category, all of them if `None`
+    FilterRejectedTxEvent(Option<String>),
     OpenConsensusModal(cursive::CbSink),
+    OpenBlockDetail(ckb_fixed_hash_core::H256, cursive::CbSink),
+    OpenTransactionDetail(ckb_fixed_hash_core::H256, cursive::CbSink),
+    /// Switch the dashboard to the node profile with the given index
+    SwitchNode(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, mpsc},
+    };
+
+    use cursive::event::Key;
+
+    use super::{
+        GeneralDashboardData, UpdatedSources, blockchain::BlockchainDashboardState, dashboard,
+        overview::OverviewDashboardState, update_stale_panels,
+    };
+    use crate::{
+        CURRENT_TAB,
+        alerts::{AlertEngine, AlertMetric, AlertRule},
+        components::{DashboardData, DashboardState},
+        config::{DashboardTab, KeyBindings},
+        history::{MetricsHistory, NodeHistory},
+        mock_node::MockNode,
+        utils::{puppet::PuppetScreen, rpc_cache::CachedRpcClient},
+    };
+
+    #[test]
+    fn switches_tabs_and_refreshes_with_keyboard() {
+        let node = MockNode::start();
+        let mut screen = PuppetScreen::new(220, 60);
+        let (event_sender, _event_receiver) = mpsc::channel();
+        let keys = KeyBindings::default();
+        let view = dashboard(event_sender, screen.siv(), &keys, DashboardTab::Overview);
+        screen.siv().add_fullscreen_layer(view);
+        assert!(screen.contains("Press [Q] to quit, [Tab] to switch panels, [R] to refresh"));
+        assert!(!screen.contains("[Consensus]"));
+
+        // Focus moves from the selected tab to the next one
+        screen.press(Key::Tab);
+        screen.press(Key::Enter);
+        assert!(screen.contains("[Consensus]"));
+        assert_eq!(CURRENT_TAB.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let client = CachedRpcClient::new(&node.rpc_url());
+        screen.siv().add_global_callback(keys.refresh, move |siv| {
+            let mut data = GeneralDashboardData {
+                node_name: String::from("mock"),
+                ..Default::default()
+            };
+            data.set_enable_overview_data(true);
+            data.fetch_data_through_client(&client)
+                .unwrap()
+                .update_to_view(siv);
+            update_stale_panels(
+                siv,
+                &BTreeMap::from([("Peers", String::from("connection refused"))]),
+            );
+        });
+        screen.press(keys.refresh);
+        assert!(screen.contains("[Mirana Testnet] CKB Node Monitor 0.203.0 (mock) (mock)"));
+        assert!(screen.contains("⚠ Stale: Peers (connection refused)"));
+    }
+
+    #[test]
+    fn evaluates_alerts_while_a_state_fails() {
+        let node = MockNode::start();
+        node.set_error("get_overview", "database is locked");
+        let client = CachedRpcClient::new(&node.rpc_url());
+        let history = NodeHistory::new(Arc::new(MetricsHistory::in_memory()), "mock");
+        let mut overview_state =
+            OverviewDashboardState::new(client.clone(), false, history.clone()).unwrap();
+        let mut blockchain_state = BlockchainDashboardState::new(client, true, None, history);
+        overview_state.update_state().unwrap();
+        assert!(blockchain_state.update_state().is_err());
+
+        let rule = |name: &str, metric, above, below| AlertRule {
+            name: name.to_string(),
+            metric,
+            above,
+            below,
+            for_secs: 0,
+        };
+        let mut engine = AlertEngine::new(vec![
+            rule("Behind", AlertMetric::SyncLag, Some(50.0), None),
+            rule("Down", AlertMetric::NodeUp, None, Some(1.0)),
+        ]);
+        let sources = UpdatedSources {
+            overview: Some(&overview_state),
+            blockchain: None,
+            mempool: None,
+            peers: None,
+        };
+        // The sync lag of the fixtures is 100 blocks
+        let fired = engine.evaluate("mock", &sources.alert_metrics(), chrono::Local::now());
+        assert_eq!(fired.len(), 1);
+        assert_eq!(engine.firing(), vec!["Behind"]);
+
+        let sources = UpdatedSources {
+            overview: None,
+            ..sources
+        };
+        assert!(!sources.node_up());
+        engine.evaluate("mock", &sources.alert_metrics(), chrono::Local::now());
+        // Rules of unavailable metrics are left as they are
+        assert_eq!(engine.firing(), vec!["Behind", "Down"]);
+    }
 }
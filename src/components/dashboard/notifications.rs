@@ -0,0 +1,161 @@
+use cursive::{
+    theme::{BaseColor, ColorStyle},
+    utils::markup::StyledString,
+    view::{IntoBoxedView, Nameable, Resizable},
+    views::{Dialog, LinearLayout, TextView},
+};
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    components::{
+        UpdateToView,
+        dashboard::notifications::names::{NOTIFICATION_INDICATOR, NOTIFICATIONS_TABLE},
+    },
+    declare_names,
+    notifications::{Notification, NotificationCenter},
+};
+
+declare_names!(
+    names,
+    "notifications_",
+    NOTIFICATION_INDICATOR,
+    NOTIFICATIONS_TABLE
+);
+
+/// Status bar indicator of the sources currently failing, empty if there are none
+pub fn notification_indicator() -> impl IntoBoxedView + use<> {
+    TextView::empty().with_name(NOTIFICATION_INDICATOR)
+}
+
+impl UpdateToView for NotificationCenter {
+    fn update_to_view(&self, siv: &mut cursive::Cursive) {
+        let active = self.active();
+        siv.call_on_name(NOTIFICATION_INDICATOR, |view: &mut TextView| {
+            match active.iter().max_by_key(|x| x.last_seen) {
+                None => view.set_content(""),
+                Some(latest) => view.set_content(StyledString::styled(
+                    format!(
+                        " ✖ {} error(s), latest from {}: {}",
+                        active.len(),
+                        latest.source,
+                        latest.message
+                    ),
+                    ColorStyle::front(BaseColor::Red),
+                )),
+            }
+        });
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum NotificationsColumn {
+    LastSeen,
+    FirstSeen,
+    Source,
+    Count,
+    Status,
+    Message,
+}
+
+impl TableViewItem<NotificationsColumn> for Notification {
+    fn to_column(&self, column: NotificationsColumn) -> String {
+        match column {
+            NotificationsColumn::LastSeen => self.last_seen.format("%H:%M:%S").to_string(),
+            NotificationsColumn::FirstSeen => self.first_seen.format("%H:%M:%S").to_string(),
+            NotificationsColumn::Source => self.source.clone(),
+            NotificationsColumn::Count => self.count.to_string(),
+            NotificationsColumn::Status => {
+                if self.active {
+                    String::from("🔴 Failing")
+                } else {
+                    String::from("🟢 Recovered")
+                }
+            }
+            NotificationsColumn::Message => self.message.clone(),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: NotificationsColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            NotificationsColumn::LastSeen => self.last_seen.cmp(&other.last_seen).reverse(),
+            NotificationsColumn::FirstSeen => self.first_seen.cmp(&other.first_seen).reverse(),
+            NotificationsColumn::Source => self.source.cmp(&other.source),
+            NotificationsColumn::Count => self.count.cmp(&other.count),
+            NotificationsColumn::Status => self.active.cmp(&other.active),
+            NotificationsColumn::Message => self.message.cmp(&other.message),
+        }
+    }
+}
+
+fn notification_detail_dialog(notification: &Notification) -> impl IntoBoxedView + use<> {
+    Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(format!("• Source: {}", notification.source)))
+            .child(TextView::new(format!(
+                "• First seen: {}",
+                notification.first_seen.format("%Y-%m-%d %H:%M:%S")
+            )))
+            .child(TextView::new(format!(
+                "• Last seen: {}",
+                notification.last_seen.format("%Y-%m-%d %H:%M:%S")
+            )))
+            .child(TextView::new(format!(
+                "• Occurrences: {}",
+                notification.count
+            )))
+            .child(TextView::new(" "))
+            .child(TextView::new(notification.message.clone()))
+            .max_width(100),
+    )
+    .title("Details of error")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
+
+/// Errors reported in this session, most recent first
+pub fn notifications_dialog(center: &NotificationCenter) -> impl IntoBoxedView + use<> {
+    let active = center.active().len();
+    Dialog::around(
+        LinearLayout::vertical()
+            .child(TextView::new(if active == 0 {
+                String::from("All sources are healthy")
+            } else {
+                format!("{} source(s) failing", active)
+            }))
+            .child(TextView::new(" "))
+            .child(
+                TableView::<Notification, NotificationsColumn>::new()
+                    .column(NotificationsColumn::LastSeen, "Last Seen", |c| c.width(10))
+                    .column(NotificationsColumn::FirstSeen, "First Seen", |c| {
+                        c.width(11)
+                    })
+                    .column(NotificationsColumn::Source, "Source", |c| c.width(26))
+                    .column(NotificationsColumn::Count, "Count", |c| c.width(7))
+                    .column(NotificationsColumn::Status, "Status", |c| c.width(13))
+                    .column(NotificationsColumn::Message, "Message", |c| c)
+                    .default_column(NotificationsColumn::LastSeen)
+                    .items(center.history().to_vec())
+                    .on_submit(|siv, _row, index| {
+                        let notification = siv
+                            .call_on_name(
+                                NOTIFICATIONS_TABLE,
+                                |view: &mut TableView<Notification, NotificationsColumn>| {
+                                    view.borrow_item(index).unwrap().clone()
+                                },
+                            )
+                            .unwrap();
+                        siv.add_layer(notification_detail_dialog(&notification));
+                    })
+                    .with_name(NOTIFICATIONS_TABLE)
+                    .min_size((120, 15)),
+            ),
+    )
+    .title("Notifications")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
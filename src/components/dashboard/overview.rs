@@ -3,15 +3,16 @@ use std::sync::mpsc;
 use anyhow::{Context, anyhow};
 use chrono::Local;
 use ckb_jsonrpc_types::Overview;
-use ckb_sdk::CkbRpcClient;
 use cursive::{
     Cursive,
     view::{IntoBoxedView, Nameable, Resizable, Scrollable},
     views::{LinearLayout, NamedView, Panel, ProgressBar, TextView},
 };
+use serde::Serialize;
 
 use crate::{
     CURRENT_TAB,
+    alerts::{AlertMetrics, AlertSource},
     components::{
         DashboardData, DashboardState, UpdateToView,
         dashboard::{
@@ -25,8 +26,14 @@ use crate::{
         },
         extract_epoch, get_average_block_time_and_estimated_epoch_time,
     },
-    declare_names, update_text,
-    utils::{bar_chart::SimpleBarChart, difficulty_to_string, hash_rate_to_string},
+    declare_names,
+    exporter::{ExportMetrics, MetricsWriter},
+    history::{self, NodeHistory},
+    update_text,
+    utils::{
+        bar_chart::SimpleBarChart, difficulty_to_string, hash_rate_to_string,
+        rpc_cache::CachedRpcClient,
+    },
 };
 
 declare_names!(
@@ -81,19 +88,26 @@ struct GetOverviewOfOverviewDashboardState {
 
     pub difficulty: f64,
     pub hash_rate: f64,
+
+    pub tx_pending: u64,
+    pub tx_proposed: u64,
+    pub tx_committing: u64,
+    // in bytes
+    pub total_pool_size: u64,
 }
 
 #[derive(Clone)]
 pub struct OverviewDashboardState {
     pub last_update: chrono::DateTime<Local>,
 
-    pub client: CkbRpcClient,
+    pub client: CachedRpcClient,
     pub current_block: u64,
     pub total_block: u64,
     // In seconds
     pub estimated_time_left: u64,
 
     overview_data: Option<GetOverviewOfOverviewDashboardState>,
+    history: NodeHistory,
 }
 
 impl OverviewDashboardState {
@@ -131,9 +145,13 @@ impl OverviewDashboardState {
         (cpu_percent, disk_total, disk_used, ram_total, ram_used)
     }
 
-    pub fn new(client: CkbRpcClient, enable_overview_data: bool) -> anyhow::Result<Self> {
+    pub fn new(
+        client: CachedRpcClient,
+        enable_overview_data: bool,
+        history: NodeHistory,
+    ) -> anyhow::Result<Self> {
         let overview_data = if enable_overview_data {
-            let overview = client.post::<(), Overview>("get_overview", ())?;
+            let overview = client.get_overview()?;
 
             let (read, write) = Self::get_total_read_and_total_write_bytes_for_disk(&overview);
             let (send, receive) =
@@ -157,6 +175,10 @@ impl OverviewDashboardState {
                 ram_used,
                 difficulty: 0.0,
                 hash_rate: 0.0,
+                tx_pending: overview.pool.pending.value(),
+                tx_proposed: overview.pool.proposed.value(),
+                tx_committing: overview.pool.committing.value(),
+                total_pool_size: overview.pool.total_tx_size.value(),
             })
         } else {
             None
@@ -168,6 +190,7 @@ impl OverviewDashboardState {
             current_block: 0,
             estimated_time_left: 100,
             total_block: 1,
+            history,
         })
     }
 }
@@ -178,14 +201,25 @@ impl DashboardState for OverviewDashboardState {
         let now = chrono::Local::now();
         let diff_secs = ((now - self.last_update).num_milliseconds() as f64) / 1e3;
         if let Some(data) = &mut self.overview_data {
-            let overview_data = self.client.post::<(), Overview>("get_overview", ())?;
+            let overview_data = self.client.get_overview()?;
 
-            data.cpu_history
-                .queue(overview_data.sys.global.global_cpu_usage as f64 / 100.0)
-                .unwrap();
+            let cpu_usage = overview_data.sys.global.global_cpu_usage as f64 / 100.0;
+            data.cpu_history.queue(cpu_usage).unwrap();
+            self.history.record(history::CPU, cpu_usage);
             if data.cpu_history.len() > 20 {
                 data.cpu_history.dequeue();
             }
+            (
+                data.cpu_percent,
+                data.disk_total,
+                data.disk_used,
+                data.ram_total,
+                data.ram_used,
+            ) = Self::extract_cpu_percent_and_disk_total_and_disk_used_and_ram_total_and_ram_used_from_overview(&overview_data);
+            data.tx_pending = overview_data.pool.pending.value();
+            data.tx_proposed = overview_data.pool.proposed.value();
+            data.tx_committing = overview_data.pool.committing.value();
+            data.total_pool_size = overview_data.pool.total_tx_size.value();
             data.hash_rate = overview_data
                 .mining
                 .hash_rate
@@ -238,6 +272,10 @@ impl DashboardState for OverviewDashboardState {
             };
             self.current_block = current_block;
             self.total_block = total_block;
+            self.history
+                .record(history::TIP_NUMBER, current_block as f64);
+            self.history
+                .record(history::SYNC_HEIGHT, total_block as f64);
             self.estimated_time_left = estimated_seconds.ceil() as u64;
         }
 
@@ -247,11 +285,111 @@ impl DashboardState for OverviewDashboardState {
     }
 }
 
+impl AlertSource for OverviewDashboardState {
+    fn collect_alert_metrics(&self, metrics: &mut AlertMetrics) {
+        metrics.observe_tip(self.current_block);
+        metrics.sync_lag = Some(self.total_block.saturating_sub(self.current_block));
+        if let Some(data) = &self.overview_data {
+            metrics.cpu = Some(data.cpu_percent);
+        }
+    }
+}
+
+impl ExportMetrics for OverviewDashboardState {
+    fn export_metrics(&self, metrics: &mut MetricsWriter) {
+        metrics.gauge(
+            "ckb_tip_block_number",
+            "Number of the tip block",
+            self.current_block as f64,
+        );
+        metrics.gauge(
+            "ckb_best_known_block_number",
+            "Number of the best known block",
+            self.total_block as f64,
+        );
+        metrics.gauge(
+            "ckb_sync_progress_ratio",
+            "Tip block number per best known block number",
+            (self.current_block as f64 / self.total_block.max(1) as f64).min(1.0),
+        );
+        metrics.gauge(
+            "ckb_sync_eta_seconds",
+            "Estimated time left to catch up with the best known block",
+            self.estimated_time_left as f64,
+        );
+        if let Some(data) = &self.overview_data {
+            metrics.gauge(
+                "ckb_cpu_usage_ratio",
+                "CPU usage of the node",
+                data.cpu_percent / 100.0,
+            );
+            metrics.gauge("ckb_memory_used_bytes", "Used memory", data.ram_used as f64);
+            metrics.gauge(
+                "ckb_memory_total_bytes",
+                "Total memory",
+                data.ram_total as f64,
+            );
+            metrics.gauge(
+                "ckb_disk_used_bytes",
+                "Used disk space",
+                data.disk_used as f64,
+            );
+            metrics.gauge(
+                "ckb_disk_total_bytes",
+                "Total disk space",
+                data.disk_total as f64,
+            );
+            metrics.gauge(
+                "ckb_disk_read_bytes_per_second",
+                "Disk read speed",
+                data.disk_read_speed,
+            );
+            metrics.gauge(
+                "ckb_disk_write_bytes_per_second",
+                "Disk write speed",
+                data.disk_write_speed,
+            );
+            metrics.gauge(
+                "ckb_network_receive_bytes_per_second",
+                "Network receive speed",
+                data.network_receive_speed,
+            );
+            metrics.gauge(
+                "ckb_network_send_bytes_per_second",
+                "Network send speed",
+                data.network_send_speed,
+            );
+            for (state, count) in [
+                ("pending", data.tx_pending),
+                ("proposed", data.tx_proposed),
+                ("committing", data.tx_committing),
+            ] {
+                metrics.gauge_with_labels(
+                    "ckb_pool_transactions",
+                    "Transactions in the pool",
+                    vec![("state", state.to_string())],
+                    count as f64,
+                );
+            }
+            metrics.gauge(
+                "ckb_pool_size_bytes",
+                "Total size of transactions in the pool",
+                data.total_pool_size as f64,
+            );
+            metrics.gauge("ckb_difficulty", "Mining difficulty", data.difficulty);
+            metrics.gauge("ckb_hash_rate", "Network hash rate", data.hash_rate);
+        }
+    }
+}
+
 impl UpdateToView for OverviewDashboardState {
     fn update_to_view(&self, siv: &mut Cursive) {
         if let Some(data) = &self.overview_data {
             siv.call_on_name(CPU_HISTORY, |view: &mut SimpleBarChart| {
-                view.set_data(data.cpu_history.vec()).unwrap();
+                match self.history.current_series(history::CPU) {
+                    Some(series) => view.set_data(&series).unwrap(),
+                    None => view.set_data(data.cpu_history.vec()).unwrap(),
+                }
             });
             update_text!(
                 siv,
@@ -329,7 +467,7 @@ impl UpdateToView for OverviewDashboardState {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 struct GetOverviewOfOverviewDashboardData {
     pub tx_pending: u64,
     pub tx_proposed: u64,
@@ -341,8 +479,9 @@ struct GetOverviewOfOverviewDashboardData {
     pub average_latency: Option<u64>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct OverviewDashboardData {
+    pub tip_block_number: u64,
     pub inbound_peers: usize,
     pub outbound_peers: usize,
     overview_data: Option<GetOverviewOfOverviewDashboardData>,
@@ -356,9 +495,19 @@ pub struct OverviewDashboardData {
     pub estimated_epoch_time: f64,
     pub average_block_time: f64,
 
+    #[serde(skip)]
     enable_fetch_overview_data: bool,
 }
 
+impl OverviewDashboardData {
+    /// Number of transactions in the pool, `None` if overview data is unavailable
+    pub fn total_pool_txs(&self) -> Option<u64> {
+        self.overview_data
+            .as_ref()
+            .map(|x| x.tx_pending + x.tx_proposed + x.tx_committing)
+    }
+}
+
 impl UpdateToView for OverviewDashboardData {
     fn update_to_view(&self, siv: &mut Cursive) {
         update_text!(
@@ -437,7 +586,7 @@ impl DashboardData for OverviewDashboardData {
     }
     fn fetch_data_through_client(
         &mut self,
-        client: &CkbRpcClient,
+        client: &CachedRpcClient,
     ) -> anyhow::Result<Box<dyn DashboardData + Send + Sync>> {
         log::debug!("Updating: OverviewDashboardData");
         let peers = client
@@ -463,7 +612,7 @@ impl DashboardData for OverviewDashboardData {
             get_average_block_time_and_estimated_epoch_time(&tip_header, client)?;
         let overview_data = if self.enable_fetch_overview_data {
             let overview_data: Overview = client
-                .post("get_overview", ())
+                .get_overview()
                 .with_context(|| anyhow!("Unable to get overview info"))?;
             Some(GetOverviewOfOverviewDashboardData {
                 tx_pending: overview_data.pool.pending.value(),
@@ -487,6 +636,7 @@ impl DashboardData for OverviewDashboardData {
             None
         };
         *self = OverviewDashboardData {
+            tip_block_number: tip_header.inner.number.value(),
             inbound_peers,
             outbound_peers,
             average_fee_rate: fee_rate_statistics.map(|x| x.mean.value()),
@@ -505,6 +655,9 @@ impl DashboardData for OverviewDashboardData {
     fn set_enable_overview_data(&mut self, flag: bool) {
         self.enable_fetch_overview_data = flag;
     }
+    fn panel_name(&self) -> &'static str {
+        "Overview"
+    }
 }
 
 pub fn basic_info_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxedView + use<> {
@@ -674,3 +827,98 @@ pub fn basic_info_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoB
                 .scrollable(),
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{OverviewDashboardData, OverviewDashboardState, basic_info_dashboard};
+    use crate::{
+        components::{DashboardData, DashboardState, UpdateToView, is_overview_supported},
+        history::{self, MetricsHistory, NodeHistory},
+        mock_node::{MockNode, fixtures},
+        utils::{puppet::PuppetScreen, rpc_cache::CachedRpcClient},
+    };
+
+    #[test]
+    fn fetches_with_and_without_terminal_module() {
+        let node = MockNode::start();
+        let client = CachedRpcClient::new(&node.rpc_url());
+        assert!(is_overview_supported(&client));
+        let mut data = OverviewDashboardData::default();
+        data.set_enable_overview_data(true);
+        data.fetch_data_through_client(&client).unwrap();
+        assert_eq!(data.tip_block_number, fixtures::TIP_NUMBER);
+        assert_eq!((data.inbound_peers, data.outbound_peers), (1, 1));
+        assert_eq!((data.epoch, data.epoch_block), (10, 100));
+        assert_eq!(
+            data.average_block_time,
+            fixtures::BLOCK_INTERVAL_SECS as f64
+        );
+        assert_eq!(data.average_fee_rate, Some(1500));
+        assert_eq!(data.total_pool_txs(), Some(10));
+        let overview = data.overview_data.as_ref().unwrap();
+        assert_eq!(overview.average_latency, Some(48));
+
+        node.remove("get_overview");
+        let client = CachedRpcClient::new(&node.rpc_url());
+        assert!(!is_overview_supported(&client));
+        data.set_enable_overview_data(false);
+        data.fetch_data_through_client(&client).unwrap();
+        assert!(data.overview_data.is_none());
+        assert_eq!(data.total_pool_txs(), None);
+
+        node.set_error("get_tip_header", "database is locked");
+        let error = data
+            .fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .err()
+            .unwrap();
+        assert!(format!("{:#}", error).starts_with("Unable to get tip header"));
+    }
+
+    #[test]
+    fn state_tracks_sync_progress() {
+        let node = MockNode::start();
+        let store = Arc::new(MetricsHistory::in_memory());
+        let history = NodeHistory::new(Arc::clone(&store), "mock");
+        let mut state =
+            OverviewDashboardState::new(CachedRpcClient::new(&node.rpc_url()), true, history)
+                .unwrap();
+        state.update_state().unwrap();
+        assert_eq!(state.current_block, fixtures::TIP_NUMBER);
+        assert_eq!(state.total_block, fixtures::TIP_NUMBER + 100);
+        assert_eq!(
+            store.series("mock", history::SYNC_HEIGHT, 3600 * 1000, 1),
+            vec![(fixtures::TIP_NUMBER + 100) as f64]
+        );
+        let overview = state.overview_data.as_ref().unwrap();
+        assert_eq!(overview.cpu_percent, 25.0);
+        assert_eq!(overview.tx_pending, 5);
+    }
+
+    #[test]
+    fn renders_fetched_data() {
+        let node = MockNode::start();
+        let mut data = OverviewDashboardData::default();
+        data.set_enable_overview_data(true);
+        data.fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .unwrap();
+        let mut screen = PuppetScreen::new(200, 60);
+        let (event_sender, _event_receiver) = std::sync::mpsc::channel();
+        screen
+            .siv()
+            .add_fullscreen_layer(basic_info_dashboard(event_sender));
+        data.update_to_view(screen.siv());
+        assert!(screen.contains("2 (1 outbound / 1 inbound)"));
+        assert!(screen.contains("10 txs"));
+        assert!(screen.contains("48ms"));
+
+        data.set_enable_overview_data(false);
+        node.remove("get_overview");
+        data.fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .unwrap();
+        data.update_to_view(screen.siv());
+        assert!(!screen.contains("10 txs"));
+        assert!(screen.contains("N/A"));
+    }
+}
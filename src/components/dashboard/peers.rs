@@ -1,8 +1,6 @@
 use std::{collections::HashMap, str::FromStr, sync::mpsc};
 
 use anyhow::{Context, anyhow};
-use ckb_jsonrpc_types::Overview;
-use ckb_sdk::CkbRpcClient;
 use cursive::{
     theme::{BaseColor, ColorStyle},
     utils::markup::StyledString,
@@ -10,6 +8,7 @@ use cursive::{
     views::{LinearLayout, Panel, TextView},
 };
 use cursive_table_view::{TableView, TableViewItem};
+use serde::Serialize;
 use tentacle_multiaddr::{Multiaddr, Protocol};
 
 /// Get peer id from multiaddr
@@ -27,6 +26,7 @@ pub fn extract_peer_id_base58(addr: &Multiaddr) -> Option<String> {
 
 use crate::{
     CURRENT_TAB,
+    alerts::{AlertMetrics, AlertSource},
     components::{
         DashboardData, UpdateToView,
         dashboard::{
@@ -34,7 +34,10 @@ use crate::{
             peers::names::{AVG_LATENCY, CONNECTIONS, PEERS_TABLE, PUBLICLY_REACHABLE},
         },
     },
-    declare_names, update_text,
+    declare_names,
+    exporter::{ExportMetrics, MetricsWriter},
+    update_text,
+    utils::rpc_cache::CachedRpcClient,
 };
 declare_names!(
     names,
@@ -45,12 +48,12 @@ declare_names!(
     PEERS_TABLE
 );
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 enum PeerDirection {
     In,
     Out,
 }
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 struct PeersItem {
     peer_id: String,
     direction: PeerDirection,
@@ -103,12 +106,13 @@ impl TableViewItem<PeersColumn> for PeersItem {
         }
     }
 }
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize)]
 pub struct PeersDashboardData {
     connections_in: usize,
     connections_out: usize,
     peers: Vec<PeersItem>,
 
+    #[serde(skip)]
     enable_fetch_overview_data: bool,
 }
 
@@ -172,19 +176,58 @@ impl UpdateToView for PeersDashboardData {
     }
 }
 
+impl PeersDashboardData {
+    /// Connected peers, inbound and outbound
+    pub fn connections(&self) -> usize {
+        self.connections_in + self.connections_out
+    }
+}
+
+impl AlertSource for PeersDashboardData {
+    fn collect_alert_metrics(&self, metrics: &mut AlertMetrics) {
+        metrics.peers = Some(self.connections());
+    }
+}
+
+impl ExportMetrics for PeersDashboardData {
+    fn export_metrics(&self, metrics: &mut MetricsWriter) {
+        for (direction, count) in [
+            ("inbound", self.connections_in),
+            ("outbound", self.connections_out),
+        ] {
+            metrics.gauge_with_labels(
+                "ckb_peers",
+                "Connected peers",
+                vec![("direction", direction.to_string())],
+                count as f64,
+            );
+        }
+        for peer in self.peers.iter() {
+            if let Some(latency) = peer.latency {
+                metrics.gauge_with_labels(
+                    "ckb_peer_latency_milliseconds",
+                    "Latency of connected peers",
+                    vec![("peer_id", peer.peer_id.clone())],
+                    latency as f64,
+                );
+            }
+        }
+    }
+}
+
 impl DashboardData for PeersDashboardData {
     fn should_update(&self) -> bool {
         CURRENT_TAB.load(std::sync::atomic::Ordering::SeqCst) == 3
     }
     fn fetch_data_through_client(
         &mut self,
-        client: &CkbRpcClient,
+        client: &CachedRpcClient,
     ) -> anyhow::Result<Box<dyn DashboardData + Send + Sync>> {
         log::debug!("Updating: PeersDashboardData");
         let peers_from_network = if self.enable_fetch_overview_data {
             Some(
                 client
-                    .post::<(), Overview>("get_overview", ())
+                    .get_overview()
                     .with_context(|| anyhow!("Unable to get peers"))?
                     .network
                     .peers
@@ -247,6 +290,9 @@ impl DashboardData for PeersDashboardData {
     fn set_enable_overview_data(&mut self, flag: bool) {
         self.enable_fetch_overview_data = flag;
     }
+    fn panel_name(&self) -> &'static str {
+        "Peers"
+    }
 }
 
 pub fn peers_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxedView + use<> {
@@ -286,3 +332,54 @@ pub fn peers_dashboard(_event_sender: mpsc::Sender<TUIEvent>) -> impl IntoBoxedV
                 ),
         ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PeerDirection, PeersDashboardData, peers_dashboard};
+    use crate::{
+        components::{DashboardData, UpdateToView},
+        mock_node::{MockNode, fixtures},
+        utils::{puppet::PuppetScreen, rpc_cache::CachedRpcClient},
+    };
+
+    #[test]
+    fn joins_latency_of_terminal_peers() {
+        let node = MockNode::start();
+        let mut data = PeersDashboardData::default();
+        data.set_enable_overview_data(true);
+        data.fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .unwrap();
+        assert_eq!((data.connections_in, data.connections_out), (1, 1));
+        let peer = &data.peers[1];
+        assert_eq!(peer.peer_id, fixtures::PEER_IDS[1]);
+        assert!(peer.direction == PeerDirection::In);
+        assert_eq!(peer.block_height, Some(fixtures::TIP_NUMBER - 5));
+        assert_eq!(peer.latency, Some(64));
+
+        // Without the Terminal module latencies are unknown
+        node.remove("get_overview");
+        data.set_enable_overview_data(false);
+        data.fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .unwrap();
+        assert!(data.peers.iter().all(|x| x.latency.is_none()));
+    }
+
+    #[test]
+    fn renders_peers_table() {
+        let node = MockNode::start();
+        let mut data = PeersDashboardData::default();
+        data.set_enable_overview_data(true);
+        data.fetch_data_through_client(&CachedRpcClient::new(&node.rpc_url()))
+            .unwrap();
+        let mut screen = PuppetScreen::new(200, 40);
+        let (event_sender, _event_receiver) = std::sync::mpsc::channel();
+        screen
+            .siv()
+            .add_fullscreen_layer(peers_dashboard(event_sender));
+        data.update_to_view(screen.siv());
+        assert!(screen.contains("✓ Yes"));
+        assert!(screen.contains("2 (1 outbound / 1 inbound)"));
+        assert!(screen.contains("48 ms"));
+        assert!(screen.contains(&(fixtures::TIP_NUMBER - 5).to_string()));
+    }
+}
@@ -0,0 +1,274 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, anyhow};
+use log::warn;
+use queue::Queue;
+
+use crate::utils::rpc_cache::CachedRpcClient;
+
+/// Counts are accumulated over this window, each window gives one point of the history
+const WINDOW: Duration = Duration::from_secs(60);
+/// Rates kept for the sparklines, i.e. the last 30 minutes
+const HISTORY_LEN: usize = 30;
+/// Blocks fetched at most per update, larger gaps (e.g. while syncing) restart the measurement
+const MAX_NEW_BLOCKS: u64 = 20;
+
+/// Counters observed at one update
+#[derive(Clone)]
+struct Counters {
+    at: Instant,
+    tip_number: u64,
+    pool_size: u64,
+    received: Option<usize>,
+}
+
+/// Transactions counted since the start of the current window
+#[derive(Clone)]
+struct Window {
+    started_at: Instant,
+    tx_in: u64,
+    tx_out: u64,
+}
+
+/// Transactions entering and leaving the pool between two observations. Without the
+/// `new_transaction` subscription the inflow is derived from the growth of the pool, assuming
+/// nothing was dropped
+pub fn count_flows(
+    pool_size_before: u64,
+    pool_size_after: u64,
+    committed: u64,
+    received: Option<u64>,
+) -> (u64, u64) {
+    let grown = pool_size_after as i64 - pool_size_before as i64;
+    match received {
+        // Received transactions that neither stayed in the pool nor got committed were dropped
+        Some(received) => (
+            received,
+            (received as i64 - grown).max(committed as i64) as u64,
+        ),
+        None => ((grown + committed as i64).max(0) as u64, committed),
+    }
+}
+
+/// Inflow and outflow of the pool in transactions per minute, counted over one-minute windows
+#[derive(Clone, Default)]
+pub struct Throughput {
+    last: Option<Counters>,
+    window: Option<Window>,
+    tx_in: Queue<f64>,
+    tx_out: Queue<f64>,
+}
+
+impl Throughput {
+    /// Counts the transactions committed in the blocks after the last observed tip and the change
+    /// of the pool size. `received` is the number of transactions received through the
+    /// subscription so far, if subscribed
+    pub fn update(
+        &mut self,
+        client: &CachedRpcClient,
+        received: Option<usize>,
+    ) -> anyhow::Result<()> {
+        self.update_at(client, received, Instant::now())
+    }
+    fn update_at(
+        &mut self,
+        client: &CachedRpcClient,
+        received: Option<usize>,
+        now: Instant,
+    ) -> anyhow::Result<()> {
+        let tip_number = client
+            .get_tip_header()
+            .with_context(|| anyhow!("Unable to get tip header"))?
+            .inner
+            .number
+            .value();
+        let pool_info = client
+            .inner()
+            .tx_pool_info()
+            .with_context(|| anyhow!("Unable to get tx pool info"))?;
+        let pool_size =
+            pool_info.pending.value() + pool_info.proposed.value() + pool_info.orphan.value();
+        let counted = match self.last.take() {
+            Some(last)
+                if tip_number >= last.tip_number
+                    && tip_number - last.tip_number <= MAX_NEW_BLOCKS =>
+            {
+                match count_committed(client, last.tip_number + 1..=tip_number) {
+                    Ok(committed) => {
+                        let received = received
+                            .zip(last.received)
+                            .map(|(now, before)| now.saturating_sub(before) as u64);
+                        Some((
+                            last.at,
+                            count_flows(last.pool_size, pool_size, committed, received),
+                        ))
+                    }
+                    Err(e) => {
+                        warn!("Restarting the pool throughput measurement: {:?}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        match counted {
+            Some((since, (tx_in, tx_out))) => self.count(since, now, tx_in, tx_out),
+            None => self.window = None,
+        }
+        self.last = Some(Counters {
+            at: now,
+            tip_number,
+            pool_size,
+            received,
+        });
+        Ok(())
+    }
+    /// Adds the flows observed since `since` to the current window, which is closed into a point
+    /// of the history once it spans `WINDOW`
+    fn count(&mut self, since: Instant, now: Instant, tx_in: u64, tx_out: u64) {
+        let window = self.window.get_or_insert(Window {
+            started_at: since,
+            tx_in: 0,
+            tx_out: 0,
+        });
+        window.tx_in += tx_in;
+        window.tx_out += tx_out;
+        let elapsed = now.duration_since(window.started_at);
+        if elapsed >= WINDOW {
+            let minutes = elapsed.as_secs_f64() / 60.0;
+            let (tx_in, tx_out) = (
+                window.tx_in as f64 / minutes,
+                window.tx_out as f64 / minutes,
+            );
+            self.window = Some(Window {
+                started_at: now,
+                tx_in: 0,
+                tx_out: 0,
+            });
+            self.push(tx_in, tx_out);
+        }
+    }
+    fn push(&mut self, tx_in: f64, tx_out: f64) {
+        self.tx_in.queue(tx_in).unwrap();
+        self.tx_out.queue(tx_out).unwrap();
+        if self.tx_in.len() > HISTORY_LEN {
+            self.tx_in.dequeue();
+            self.tx_out.dequeue();
+        }
+    }
+    /// Transactions in the pool at the last update
+    pub fn pool_size(&self) -> Option<u64> {
+        self.last.as_ref().map(|x| x.pool_size)
+    }
+    pub fn tx_in_history(&self) -> &[f64] {
+        self.tx_in.vec()
+    }
+    pub fn tx_out_history(&self) -> &[f64] {
+        self.tx_out.vec()
+    }
+    /// Inflow and outflow of the last complete window, `None` during the first minute
+    pub fn latest(&self) -> Option<(f64, f64)> {
+        Some((*self.tx_in.vec().last()?, *self.tx_out.vec().last()?))
+    }
+    /// Mean outflow minus mean inflow of the history, positive if the pool is draining
+    pub fn net_outflow(&self) -> Option<f64> {
+        if self.tx_in.vec().is_empty() {
+            return None;
+        }
+        let mean = |x: &[f64]| x.iter().sum::<f64>() / x.len() as f64;
+        Some(mean(self.tx_out_history()) - mean(self.tx_in_history()))
+    }
+}
+
+/// Transactions committed in the given blocks, excluding the cellbases
+fn count_committed(
+    client: &CachedRpcClient,
+    numbers: std::ops::RangeInclusive<u64>,
+) -> anyhow::Result<u64> {
+    let mut committed = 0;
+    for number in numbers {
+        let block = client
+            .inner()
+            .get_block_by_number(number.into())
+            .with_context(|| anyhow!("Unable to get block {}", number))?
+            .with_context(|| anyhow!("Block {} not found", number))?;
+        // Excluding the cellbase, which never enters the pool
+        committed += block.transactions.len().saturating_sub(1) as u64;
+    }
+    Ok(committed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+
+    use super::{Throughput, count_flows};
+    use crate::{
+        mock_node::{MockNode, fixtures},
+        utils::rpc_cache::CachedRpcClient,
+    };
+
+    #[test]
+    fn counts_flows_with_and_without_subscription() {
+        // 5 received, 3 committed and the pool grew by 1, so 1 was dropped
+        assert_eq!(count_flows(10, 11, 3, Some(5)), (5, 4));
+        // Missed messages don't hide committed transactions
+        assert_eq!(count_flows(10, 10, 3, Some(0)), (0, 3));
+        assert_eq!(count_flows(10, 12, 3, None), (5, 3));
+        assert_eq!(count_flows(10, 4, 3, None), (0, 3));
+    }
+
+    #[test]
+    fn measures_rates_over_windows() {
+        let node = MockNode::start();
+        let client = || CachedRpcClient::new(&node.rpc_url());
+        let start = Instant::now();
+        let mut throughput = Throughput::default();
+        throughput.update_at(&client(), Some(0), start).unwrap();
+        assert_eq!(throughput.latest(), None);
+        assert_eq!(node.calls("get_block_by_number"), 0);
+
+        // Two new blocks with two committed transactions each, the pool size is unchanged
+        node.set_result(
+            "get_tip_header",
+            fixtures::header(fixtures::TIP_NUMBER + 2, fixtures::TIP_TIMESTAMP),
+        );
+        let mut block = fixtures::new_tip_block(fixtures::TIP_NUMBER + 1);
+        block["transactions"] = json!([
+            fixtures::transaction(1),
+            fixtures::transaction(2),
+            fixtures::transaction(3)
+        ]);
+        node.set_result("get_block_by_number", block);
+        // Uncached, as the tip header changed
+        throughput
+            .update_at(&client(), Some(1), start + Duration::from_secs(20))
+            .unwrap();
+        assert_eq!(node.calls("get_block_by_number"), 2);
+        // Not extrapolated before the window is complete
+        assert_eq!(throughput.latest(), None);
+
+        throughput
+            .update_at(&client(), Some(3), start + Duration::from_secs(60))
+            .unwrap();
+        // 3 received, 4 committed and 2 dropped within one minute
+        assert_eq!(throughput.latest(), Some((3.0, 6.0)));
+        assert_eq!(throughput.tx_in_history().len(), 1);
+        assert!(throughput.net_outflow().unwrap() > 0.0);
+
+        // A failing block restarts the measurement without failing the update
+        node.set_result(
+            "get_tip_header",
+            fixtures::header(fixtures::TIP_NUMBER + 3, fixtures::TIP_TIMESTAMP),
+        );
+        node.set_error("get_block_by_number", "block unavailable");
+        throughput
+            .update_at(&client(), Some(3), start + Duration::from_secs(70))
+            .unwrap();
+        assert!(throughput.window.is_none());
+        assert_eq!(throughput.latest(), Some((3.0, 6.0)));
+        assert!(throughput.pool_size().is_some());
+    }
+}
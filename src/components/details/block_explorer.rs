@@ -0,0 +1,449 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, anyhow, bail};
+use chrono::{Local, TimeZone, Utc};
+use ckb_fixed_hash_core::H256;
+use ckb_jsonrpc_types::{BlockEconomicState, BlockView, TransactionView};
+use ckb_sdk::CkbRpcClient;
+use cursive::{
+    Cursive,
+    view::{IntoBoxedView, Nameable, Resizable, Scrollable},
+    views::{Dialog, EditView, LinearLayout, ListView, Panel, ScrollView, TextView},
+};
+use cursive_async_view::AsyncView;
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    components::{
+        details::block_explorer::names::{BLOCK_QUERY, BLOCK_TXS_TABLE},
+        details::transaction_inspector::transaction_detail_dialog,
+        extract_epoch,
+    },
+    declare_names,
+    utils::{shannons_to_ckb_string, shorten_hex},
+};
+
+declare_names!(names, "block_explorer_", BLOCK_QUERY, BLOCK_TXS_TABLE);
+
+/// A block to look up, either by its height or by its hash
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockQuery {
+    Number(u64),
+    Hash(H256),
+}
+
+impl FromStr for BlockQuery {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hash) = s.strip_prefix("0x") {
+            Ok(Self::Hash(
+                H256::from_str(hash).with_context(|| anyhow!("Bad block hash: {}", s))?,
+            ))
+        } else if s.is_empty() {
+            bail!("Please input a block number or a block hash");
+        } else {
+            Ok(Self::Number(
+                s.parse::<u64>()
+                    .with_context(|| anyhow!("Bad block number: {}", s))?,
+            ))
+        }
+    }
+}
+
+struct BlockDetail {
+    block: BlockView,
+    // `None` if the block is not finalized yet
+    economic_state: Option<BlockEconomicState>,
+}
+
+fn fetch_block_detail(client: &CkbRpcClient, query: &BlockQuery) -> anyhow::Result<BlockDetail> {
+    let block = match query {
+        BlockQuery::Number(number) => client
+            .get_block_by_number((*number).into())
+            .with_context(|| anyhow!("Unable to get block {}", number))?
+            .ok_or_else(|| anyhow!("Block {} not found", number))?,
+        BlockQuery::Hash(hash) => client
+            .get_block(hash.clone())
+            .with_context(|| anyhow!("Unable to get block {:#x}", hash))?
+            .ok_or_else(|| anyhow!("Block {:#x} not found", hash))?,
+    };
+    let economic_state = client
+        .get_block_economic_state(block.header.hash.clone())
+        .with_context(|| anyhow!("Unable to get block economic state"))?;
+    Ok(BlockDetail {
+        block,
+        economic_state,
+    })
+}
+
+#[derive(Clone)]
+struct BlockTxItem {
+    index: usize,
+    tx_hash: H256,
+    inputs: usize,
+    outputs: usize,
+    // In shannons
+    output_capacity: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum BlockTxColumn {
+    Index,
+    TxHash,
+    Inputs,
+    Outputs,
+    OutputCapacity,
+}
+
+impl From<(usize, &TransactionView)> for BlockTxItem {
+    fn from((index, tx): (usize, &TransactionView)) -> Self {
+        Self {
+            index,
+            tx_hash: tx.hash.clone(),
+            inputs: tx.inner.inputs.len(),
+            outputs: tx.inner.outputs.len(),
+            output_capacity: tx.inner.outputs.iter().map(|x| x.capacity.value()).sum(),
+        }
+    }
+}
+
+impl TableViewItem<BlockTxColumn> for BlockTxItem {
+    fn to_column(&self, column: BlockTxColumn) -> String {
+        match column {
+            BlockTxColumn::Index => {
+                if self.index == 0 {
+                    String::from("0 (cellbase)")
+                } else {
+                    self.index.to_string()
+                }
+            }
+            BlockTxColumn::TxHash => shorten_hex(self.tx_hash.to_string(), 10, 10),
+            BlockTxColumn::Inputs => self.inputs.to_string(),
+            BlockTxColumn::Outputs => self.outputs.to_string(),
+            BlockTxColumn::OutputCapacity => format!("{}", self.output_capacity as f64 / 1e8),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: BlockTxColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            BlockTxColumn::Index => self.index.cmp(&other.index),
+            BlockTxColumn::TxHash => self.tx_hash.cmp(&other.tx_hash),
+            BlockTxColumn::Inputs => self.inputs.cmp(&other.inputs),
+            BlockTxColumn::Outputs => self.outputs.cmp(&other.outputs),
+            BlockTxColumn::OutputCapacity => self.output_capacity.cmp(&other.output_capacity),
+        }
+    }
+}
+
+fn block_detail_view(detail: BlockDetail, client: CkbRpcClient) -> ScrollView<LinearLayout> {
+    let header = &detail.block.header;
+    let (epoch, epoch_block, epoch_block_count) = extract_epoch(header.inner.epoch.value());
+    let header_view = ListView::new()
+        .child("Block Hash:", TextView::new(format!("{:#x}", header.hash)))
+        .child(
+            "Block Number:",
+            TextView::new(header.inner.number.value().to_string()),
+        )
+        .child(
+            "Time:",
+            TextView::new(
+                Utc.timestamp_millis_opt(header.inner.timestamp.value() as i64)
+                    .unwrap()
+                    .with_timezone(&Local)
+                    .to_rfc2822(),
+            ),
+        )
+        .child(
+            "Epoch:",
+            TextView::new(format!("{} ({}/{})", epoch, epoch_block, epoch_block_count)),
+        )
+        .child(
+            "Parent Hash:",
+            TextView::new(format!("{:#x}", header.inner.parent_hash)),
+        )
+        .child(
+            "Compact Target:",
+            TextView::new(format!("{:#x}", header.inner.compact_target.value())),
+        )
+        .child(
+            "Nonce:",
+            TextView::new(format!("{:#x}", header.inner.nonce.value())),
+        )
+        .child(
+            "Version:",
+            TextView::new(header.inner.version.value().to_string()),
+        )
+        .child(
+            "Transactions Root:",
+            TextView::new(format!("{:#x}", header.inner.transactions_root)),
+        )
+        .child(
+            "Proposals Hash:",
+            TextView::new(format!("{:#x}", header.inner.proposals_hash)),
+        )
+        .child(
+            "Extra Hash:",
+            TextView::new(format!("{:#x}", header.inner.extra_hash)),
+        )
+        .child(
+            "DAO:",
+            TextView::new(format!(
+                "0x{}",
+                byteutils::bytes_to_hex(&header.inner.dao.0)
+            )),
+        );
+    let reward_view = match &detail.economic_state {
+        Some(state) => {
+            let miner_reward = &state.miner_reward;
+            ListView::new()
+                .child(
+                    "Base Issuance:",
+                    TextView::new(format!(
+                        "{} (primary) + {} (secondary)",
+                        shannons_to_ckb_string(state.issuance.primary.value()),
+                        shannons_to_ckb_string(state.issuance.secondary.value())
+                    )),
+                )
+                .child(
+                    "Miner Reward:",
+                    TextView::new(shannons_to_ckb_string(
+                        miner_reward.primary.value()
+                            + miner_reward.secondary.value()
+                            + miner_reward.committed.value()
+                            + miner_reward.proposal.value(),
+                    )),
+                )
+                .child(
+                    "  Primary:",
+                    TextView::new(shannons_to_ckb_string(miner_reward.primary.value())),
+                )
+                .child(
+                    "  Secondary:",
+                    TextView::new(shannons_to_ckb_string(miner_reward.secondary.value())),
+                )
+                .child(
+                    "  Committed:",
+                    TextView::new(shannons_to_ckb_string(miner_reward.committed.value())),
+                )
+                .child(
+                    "  Proposal:",
+                    TextView::new(shannons_to_ckb_string(miner_reward.proposal.value())),
+                )
+                .child(
+                    "Transactions Fee:",
+                    TextView::new(shannons_to_ckb_string(state.txs_fee.value())),
+                )
+                .child(
+                    "Finalized At:",
+                    TextView::new(format!("{:#x}", state.finalized_at)),
+                )
+        }
+        None => ListView::new().child(
+            "Block Reward:",
+            TextView::new("N/A (the block is not finalized yet)"),
+        ),
+    };
+    let proposals = if detail.block.proposals.is_empty() {
+        String::from("None")
+    } else {
+        detail
+            .block
+            .proposals
+            .iter()
+            .map(|x| format!("0x{}", byteutils::bytes_to_hex(&x.0)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let uncles = if detail.block.uncles.is_empty() {
+        String::from("None")
+    } else {
+        detail
+            .block
+            .uncles
+            .iter()
+            .map(|x| format!("#{} {:#x}", x.header.inner.number.value(), x.header.hash))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    LinearLayout::vertical()
+        .child(Panel::new(header_view).title("Header"))
+        .child(Panel::new(reward_view).title("Block Reward"))
+        .child(
+            Panel::new(
+                TableView::<BlockTxItem, BlockTxColumn>::new()
+                    .column(BlockTxColumn::Index, "Index", |c| c.width(14))
+                    .column(BlockTxColumn::TxHash, "Tx Hash", |c| c.width(28))
+                    .column(BlockTxColumn::Inputs, "Inputs", |c| c.width(8))
+                    .column(BlockTxColumn::Outputs, "Outputs", |c| c.width(8))
+                    .column(
+                        BlockTxColumn::OutputCapacity,
+                        "Output Capacity (CKB)",
+                        |c| c,
+                    )
+                    .items(
+                        detail
+                            .block
+                            .transactions
+                            .iter()
+                            .enumerate()
+                            .map(BlockTxItem::from)
+                            .collect(),
+                    )
+                    .default_column(BlockTxColumn::Index)
+                    .on_submit(move |siv, _row, index| {
+                        let line = siv
+                            .call_on_name(
+                                BLOCK_TXS_TABLE,
+                                |view: &mut TableView<BlockTxItem, BlockTxColumn>| {
+                                    view.borrow_item(index).unwrap().clone()
+                                },
+                            )
+                            .unwrap();
+                        let dialog = transaction_detail_dialog(siv, &client, line.tx_hash);
+                        siv.add_layer(dialog);
+                    })
+                    .with_name(BLOCK_TXS_TABLE)
+                    .min_size((100, 8)),
+            )
+            .title(format!(
+                "Transactions ({}), press Enter to inspect",
+                detail.block.transactions.len()
+            )),
+        )
+        .child(Panel::new(TextView::new(proposals)).title("Proposals"))
+        .child(Panel::new(TextView::new(uncles)).title("Uncles"))
+        .scrollable()
+}
+
+/// Full detail of one block, with navigation to its neighbours
+pub fn block_detail_dialog(
+    siv: &mut Cursive,
+    client: &CkbRpcClient,
+    query: BlockQuery,
+) -> impl IntoBoxedView + use<> {
+    let loaded_number = Arc::new(Mutex::new(match &query {
+        BlockQuery::Number(number) => Some(*number),
+        BlockQuery::Hash(_) => None,
+    }));
+    let loaded_number_cloned = Arc::clone(&loaded_number);
+    let client_cloned = client.clone();
+    let client_for_view = client.clone();
+    let client_prev = client.clone();
+    let client_next = client.clone();
+    let loaded_number_prev = Arc::clone(&loaded_number);
+    let loaded_number_next = Arc::clone(&loaded_number);
+    Dialog::around(
+        AsyncView::new_with_bg_creator(
+            siv,
+            move || {
+                let detail =
+                    fetch_block_detail(&client_cloned, &query).map_err(|e| format!("{:#}", e))?;
+                *loaded_number_cloned.lock().unwrap() =
+                    Some(detail.block.header.inner.number.value());
+                Ok(detail)
+            },
+            move |detail| block_detail_view(detail, client_for_view.clone()),
+        )
+        .with_width(110),
+    )
+    .title("Block Explorer")
+    .button("Prev", move |siv| {
+        let Some(number) = *loaded_number_prev.lock().unwrap() else {
+            return;
+        };
+        if number == 0 {
+            siv.add_layer(
+                Dialog::around(TextView::new("This is the genesis block")).button("Close", |siv| {
+                    siv.pop_layer();
+                }),
+            );
+            return;
+        }
+        siv.pop_layer();
+        let dialog = block_detail_dialog(siv, &client_prev, BlockQuery::Number(number - 1));
+        siv.add_layer(dialog);
+    })
+    .button("Next", move |siv| {
+        let Some(number) = *loaded_number_next.lock().unwrap() else {
+            return;
+        };
+        siv.pop_layer();
+        let dialog = block_detail_dialog(siv, &client_next, BlockQuery::Number(number + 1));
+        siv.add_layer(dialog);
+    })
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
+
+pub fn block_explorer(client: &CkbRpcClient) -> impl IntoBoxedView + use<> {
+    let client = client.clone();
+    Dialog::new()
+        .title("Block Explorer")
+        .content(ListView::new().child(
+            "Block Number or Hash:",
+            EditView::new().with_name(BLOCK_QUERY).min_width(70),
+        ))
+        .button("Open", move |siv| {
+            let query = siv
+                .call_on_name(BLOCK_QUERY, |view: &mut EditView| {
+                    view.get_content().to_string()
+                })
+                .unwrap();
+            match BlockQuery::from_str(&query) {
+                Ok(query) => {
+                    let dialog = block_detail_dialog(siv, &client, query);
+                    siv.add_layer(dialog);
+                }
+                Err(e) => {
+                    siv.add_layer(
+                        Dialog::around(TextView::new(format!("{:?}", e)))
+                            .title("Error")
+                            .button("Close", |siv| {
+                                siv.pop_layer();
+                            }),
+                    );
+                }
+            }
+        })
+        .button("Close", |siv| {
+            siv.pop_layer();
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ckb_fixed_hash_core::H256;
+
+    use super::BlockQuery;
+
+    #[test]
+    fn parses_block_number_and_hash() {
+        assert_eq!(
+            BlockQuery::from_str(" 12345 ").unwrap(),
+            BlockQuery::Number(12345)
+        );
+        let hash = "0x92b197aa1fba0f63633922c61c92375c9c074a93e85963554f5499fe1450d0e5";
+        assert_eq!(
+            BlockQuery::from_str(hash).unwrap(),
+            BlockQuery::Hash(H256::from_str(&hash[2..]).unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_block_query() {
+        assert!(BlockQuery::from_str("").is_err());
+        assert!(BlockQuery::from_str("0x1234").is_err());
+        assert!(BlockQuery::from_str("tip").is_err());
+    }
+}
@@ -0,0 +1,569 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, anyhow};
+use ckb_gen_types::core::ScriptHashType;
+use ckb_jsonrpc_types::{HeaderView, JsonBytes, OutPoint, Script};
+use ckb_sdk::{
+    CkbRpcClient,
+    rpc::ckb_indexer::{Order, ScriptType, SearchKey, SearchKeyFilter, SearchMode},
+};
+use cursive::{
+    Cursive,
+    view::{IntoBoxedView, Nameable, Resizable},
+    views::{
+        Button, Dialog, DummyView, EditView, LinearLayout, ListView, RadioButton, RadioGroup,
+        TextView,
+    },
+};
+use cursive_aligned_view::Alignable;
+use cursive_async_view::AsyncView;
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    components::{
+        details::{
+            dao_tracker::names::{
+                ARGS, CODE_HASH, HASH_TYPE_RADIO_DATA, HASH_TYPE_RADIO_DATA1,
+                HASH_TYPE_RADIO_DATA2, HASH_TYPE_RADIO_TYPE,
+            },
+            live_cells_searcher::{
+                balance_summary::occupied_capacity,
+                derive_from_ckb_address_dialog::derive_from_address_dialog, hash_type_radios,
+                search_key::ScriptInput,
+            },
+        },
+        extract_epoch,
+    },
+    declare_names,
+    utils::{shannons_to_ckb_string, shorten_hex},
+};
+
+declare_names!(
+    names,
+    "dao_tracker_",
+    CODE_HASH,
+    ARGS,
+    HASH_TYPE_RADIO_TYPE,
+    HASH_TYPE_RADIO_DATA,
+    HASH_TYPE_RADIO_DATA1,
+    HASH_TYPE_RADIO_DATA2
+);
+
+/// Deposits are locked in cycles of this many epochs
+const LOCK_PERIOD_EPOCHS: u64 = 180;
+/// Target duration of an epoch in hours
+const EPOCH_DURATION_HOURS: f64 = 4.0;
+/// Cells fetched by each `get_cells` call
+const PAGE_SIZE: u32 = 100;
+
+/// Accumulated rate of the DAO, the second u64 of the `dao` field of a header
+fn accumulated_rate(header: &HeaderView) -> u64 {
+    u64::from_le_bytes(header.inner.dao.0[8..16].try_into().unwrap())
+}
+
+/// Capacity a cell deposited with `deposit_ar` can withdraw at `withdraw_ar`. Only the capacity
+/// not occupied by the cell itself earns compensation
+pub fn maximum_withdraw(capacity: u64, occupied: u64, deposit_ar: u64, withdraw_ar: u64) -> u64 {
+    let counted = capacity.saturating_sub(occupied) as u128;
+    (counted * withdraw_ar as u128 / deposit_ar.max(1) as u128) as u64 + occupied
+}
+
+/// Epoch since which phase 2 can withdraw a cell deposited at `deposit_epoch`, whose phase 1
+/// happened at `withdraw_epoch`. Both are `epoch` fields of headers, the result is the number,
+/// index and length of the epoch
+pub fn minimal_withdraw_epoch(deposit_epoch: u64, withdraw_epoch: u64) -> (u64, u64, u64) {
+    let (deposit_number, deposit_index, deposit_length) = extract_epoch(deposit_epoch);
+    let (withdraw_number, withdraw_index, withdraw_length) = extract_epoch(withdraw_epoch);
+    let mut deposited_epochs = withdraw_number.saturating_sub(deposit_number);
+    if withdraw_index * deposit_length > deposit_index * withdraw_length {
+        deposited_epochs += 1;
+    }
+    let lock_epochs = deposited_epochs.div_ceil(LOCK_PERIOD_EPOCHS) * LOCK_PERIOD_EPOCHS;
+    (deposit_number + lock_epochs, deposit_index, deposit_length)
+}
+
+fn epoch_to_f64((number, index, length): (u64, u64, u64)) -> f64 {
+    number as f64 + index as f64 / length.max(1) as f64
+}
+
+/// Time left until `target` from the `epoch` field of the tip
+fn countdown(target: (u64, u64, u64), tip_epoch: u64) -> String {
+    let remaining = epoch_to_f64(target) - epoch_to_f64(extract_epoch(tip_epoch));
+    if remaining <= 0.0 {
+        return String::from("Now");
+    }
+    let hours = (remaining * EPOCH_DURATION_HOURS).round() as u64;
+    format!("{:.1} epochs (~{}d {}h)", remaining, hours / 24, hours % 24)
+}
+
+#[derive(Clone, Debug)]
+pub struct DaoPosition {
+    pub out_point: OutPoint,
+    pub capacity: u64,
+    pub deposit_block: u64,
+    /// Block of the phase 1 transaction, `None` if the cell is still deposited
+    pub withdrawing_block: Option<u64>,
+    /// Accrued until the tip for deposits, or until phase 1 for withdrawing cells
+    pub compensation: u64,
+    /// Number, index and length of the epoch since which the cell can be withdrawn, assuming
+    /// phase 1 happens at the tip for deposits
+    pub withdrawable_epoch: (u64, u64, u64),
+    /// `epoch` field of the tip
+    pub tip_epoch: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum DaoColumn {
+    OutPoint,
+    Capacity,
+    Status,
+    Compensation,
+    WithdrawableEpoch,
+    Countdown,
+}
+
+impl TableViewItem<DaoColumn> for DaoPosition {
+    fn to_column(&self, column: DaoColumn) -> String {
+        match column {
+            DaoColumn::OutPoint => format!(
+                "{}#{}",
+                shorten_hex(format!("{:#x}", self.out_point.tx_hash), 6, 4),
+                self.out_point.index.value()
+            ),
+            DaoColumn::Capacity => shannons_to_ckb_string(self.capacity),
+            DaoColumn::Status => match self.withdrawing_block {
+                Some(block) => format!("Withdrawing (#{})", block),
+                None => format!("Deposited (#{})", self.deposit_block),
+            },
+            DaoColumn::Compensation => shannons_to_ckb_string(self.compensation),
+            DaoColumn::WithdrawableEpoch => {
+                let (number, index, length) = self.withdrawable_epoch;
+                format!("{} ({}/{})", number, index, length)
+            }
+            DaoColumn::Countdown => countdown(self.withdrawable_epoch, self.tip_epoch),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: DaoColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            DaoColumn::OutPoint => self.out_point.tx_hash.cmp(&other.out_point.tx_hash),
+            DaoColumn::Capacity => self.capacity.cmp(&other.capacity),
+            DaoColumn::Status => self.withdrawing_block.cmp(&other.withdrawing_block),
+            DaoColumn::Compensation => self.compensation.cmp(&other.compensation),
+            DaoColumn::WithdrawableEpoch | DaoColumn::Countdown => {
+                epoch_to_f64(self.withdrawable_epoch)
+                    .total_cmp(&epoch_to_f64(other.withdrawable_epoch))
+            }
+        }
+    }
+}
+
+/// DAO cells of a lock script
+#[derive(Clone, Debug, Default)]
+pub struct DaoPositions {
+    pub positions: Vec<DaoPosition>,
+    /// Cells typed by the DAO whose data isn't the 8 bytes of a deposit or withdrawal
+    pub malformed: Vec<OutPoint>,
+}
+
+/// Fetches headers by number, each at most once
+struct HeaderCache<'a> {
+    client: &'a CkbRpcClient,
+    headers: HashMap<u64, HeaderView>,
+}
+
+impl HeaderCache<'_> {
+    fn get(&mut self, number: u64) -> anyhow::Result<&HeaderView> {
+        if !self.headers.contains_key(&number) {
+            let header = self
+                .client
+                .get_header_by_number(number.into())
+                .with_context(|| anyhow!("Unable to get header of block {}", number))?
+                .ok_or_else(|| anyhow!("Block {} not found", number))?;
+            self.headers.insert(number, header);
+        }
+        Ok(&self.headers[&number])
+    }
+}
+
+/// Finds the DAO cells of `lock` and computes their compensation and withdrawable epoch. Cells
+/// with malformed data are reported instead of failing the whole scan
+pub fn fetch_dao_positions(client: &CkbRpcClient, lock: Script) -> anyhow::Result<DaoPositions> {
+    let dao_type_hash = client
+        .get_consensus()
+        .with_context(|| anyhow!("Unable to get consensus"))?
+        .dao_type_hash;
+    let tip = client
+        .get_tip_header()
+        .with_context(|| anyhow!("Unable to get tip header"))?;
+    let search_key = SearchKey {
+        script: lock,
+        script_type: ScriptType::Lock,
+        script_search_mode: Some(SearchMode::Exact),
+        filter: Some(SearchKeyFilter {
+            script: Some(Script {
+                code_hash: dao_type_hash,
+                hash_type: ScriptHashType::Type.into(),
+                args: JsonBytes::default(),
+            }),
+            ..Default::default()
+        }),
+        with_data: Some(true),
+        group_by_transaction: None,
+    };
+    let mut headers = HeaderCache {
+        client,
+        headers: HashMap::new(),
+    };
+    let mut result = DaoPositions::default();
+    let mut cursor = None;
+    loop {
+        let page = client
+            .get_cells(search_key.clone(), Order::Asc, PAGE_SIZE.into(), cursor)
+            .with_context(|| anyhow!("Unable to get DAO cells"))?;
+        for cell in page.objects.iter() {
+            let data = cell
+                .output_data
+                .as_ref()
+                .map(|x| x.as_bytes())
+                .unwrap_or_default();
+            let Ok(data) = <[u8; 8]>::try_from(data) else {
+                result.malformed.push(cell.out_point.clone());
+                continue;
+            };
+            let capacity = cell.output.capacity.value();
+            let occupied = occupied_capacity(cell);
+            let cell_block = cell.block_number.value();
+            let position = match u64::from_le_bytes(data) {
+                // Deposited, accruing until the tip
+                0 => {
+                    let deposit = headers.get(cell_block)?;
+                    DaoPosition {
+                        out_point: cell.out_point.clone(),
+                        capacity,
+                        deposit_block: cell_block,
+                        withdrawing_block: None,
+                        compensation: maximum_withdraw(
+                            capacity,
+                            occupied,
+                            accumulated_rate(deposit),
+                            accumulated_rate(&tip),
+                        ) - capacity,
+                        withdrawable_epoch: minimal_withdraw_epoch(
+                            deposit.inner.epoch.value(),
+                            tip.inner.epoch.value(),
+                        ),
+                        tip_epoch: tip.inner.epoch.value(),
+                    }
+                }
+                // Phase 1 done, the data is the number of the deposit block
+                deposit_block => {
+                    let (deposit_ar, deposit_epoch) = {
+                        let deposit = headers.get(deposit_block)?;
+                        (accumulated_rate(deposit), deposit.inner.epoch.value())
+                    };
+                    let withdrawing = headers.get(cell_block)?;
+                    DaoPosition {
+                        out_point: cell.out_point.clone(),
+                        capacity,
+                        deposit_block,
+                        withdrawing_block: Some(cell_block),
+                        compensation: maximum_withdraw(
+                            capacity,
+                            occupied,
+                            deposit_ar,
+                            accumulated_rate(withdrawing),
+                        ) - capacity,
+                        withdrawable_epoch: minimal_withdraw_epoch(
+                            deposit_epoch,
+                            withdrawing.inner.epoch.value(),
+                        ),
+                        tip_epoch: tip.inner.epoch.value(),
+                    }
+                }
+            };
+            result.positions.push(position);
+        }
+        if page.objects.len() < PAGE_SIZE as usize {
+            break;
+        }
+        cursor = Some(page.last_cursor);
+    }
+    Ok(result)
+}
+
+fn dao_positions_view(
+    DaoPositions {
+        positions,
+        malformed,
+    }: DaoPositions,
+) -> LinearLayout {
+    let total_capacity = positions.iter().map(|x| x.capacity).sum::<u64>();
+    let total_compensation = positions.iter().map(|x| x.compensation).sum::<u64>();
+    let mut summary = ListView::new()
+        .child("DAO Cells:", TextView::new(positions.len().to_string()))
+        .child(
+            "Total Capacity:",
+            TextView::new(shannons_to_ckb_string(total_capacity)),
+        )
+        .child(
+            "Total Compensation:",
+            TextView::new(shannons_to_ckb_string(total_compensation)),
+        );
+    if !malformed.is_empty() {
+        let out_points = malformed
+            .iter()
+            .map(|x| format!("{:#x}#{}", x.tx_hash, x.index.value()))
+            .collect::<Vec<_>>();
+        summary.add_child("Malformed Cells:", TextView::new(out_points.join("\n")));
+    }
+    LinearLayout::vertical()
+        .child(summary)
+        .child(
+            TableView::<DaoPosition, DaoColumn>::new()
+                .column(DaoColumn::OutPoint, "Out Point", |c| c.width(18))
+                .column(DaoColumn::Capacity, "Capacity", |c| c.width(18))
+                .column(DaoColumn::Status, "Status", |c| c.width(22))
+                .column(DaoColumn::Compensation, "Compensation", |c| c.width(18))
+                .column(DaoColumn::WithdrawableEpoch, "Withdrawable At", |c| {
+                    c.width(18)
+                })
+                .column(DaoColumn::Countdown, "Countdown", |c| c.width(24))
+                .items(positions)
+                .min_size((120, 15)),
+        )
+        .child(TextView::new(
+            "Deposits are assumed to start withdrawing at the tip, the countdown assumes 4 hours per epoch",
+        ))
+}
+
+fn dao_positions_dialog(siv: &mut Cursive, client: &CkbRpcClient, lock: Script) -> Dialog {
+    let client = client.clone();
+    Dialog::around(AsyncView::new_with_bg_creator(
+        siv,
+        move || fetch_dao_positions(&client, lock).map_err(|e| format!("{:#}", e)),
+        dao_positions_view,
+    ))
+    .title("Nervos DAO Positions")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
+
+/// Tracks the Nervos DAO deposits and withdrawals of a lock script
+pub fn dao_tracker(client: &CkbRpcClient) -> impl IntoBoxedView + use<> {
+    let client = client.clone();
+    let mut hash_type_group = RadioGroup::<ScriptHashType>::new();
+    Dialog::new()
+        .title("Nervos DAO Tracker")
+        .content(
+            LinearLayout::vertical()
+                .child(
+                    ListView::new()
+                        .child(
+                            "Lock Code Hash:",
+                            EditView::new().with_name(CODE_HASH).min_width(70),
+                        )
+                        .child("Lock Args:", EditView::new().with_name(ARGS).min_width(70))
+                        .child(
+                            "Lock Hash Type:",
+                            hash_type_radios(
+                                &mut hash_type_group,
+                                Some([
+                                    HASH_TYPE_RADIO_TYPE,
+                                    HASH_TYPE_RADIO_DATA,
+                                    HASH_TYPE_RADIO_DATA1,
+                                    HASH_TYPE_RADIO_DATA2,
+                                ]),
+                            ),
+                        ),
+                )
+                .child(DummyView::new())
+                .child(
+                    Button::new("Derive from CKB address", |siv| {
+                        let cb_sink = siv.cb_sink().clone();
+                        let cb_sink_2 = siv.cb_sink().clone();
+                        siv.add_layer(derive_from_address_dialog(
+                            move |args, code_hash, hash_type| {
+                                cb_sink
+                                    .send(Box::new(move |siv| {
+                                        siv.call_on_name(ARGS, |view: &mut EditView| {
+                                            view.set_content(args)
+                                        });
+                                        siv.call_on_name(CODE_HASH, |view: &mut EditView| {
+                                            view.set_content(code_hash)
+                                        });
+                                        siv.call_on_name(
+                                            match hash_type {
+                                                ScriptHashType::Type => HASH_TYPE_RADIO_TYPE,
+                                                ScriptHashType::Data => HASH_TYPE_RADIO_DATA,
+                                                ScriptHashType::Data1 => HASH_TYPE_RADIO_DATA1,
+                                                ScriptHashType::Data2 => HASH_TYPE_RADIO_DATA2,
+                                                _ => unreachable!(),
+                                            },
+                                            |view: &mut RadioButton<ScriptHashType>| {
+                                                view.select();
+                                            },
+                                        );
+                                    }))
+                                    .unwrap();
+                            },
+                            cb_sink_2,
+                        ));
+                    })
+                    .align_center(),
+                ),
+        )
+        .button("Track", move |siv| {
+            let input = ScriptInput {
+                code_hash: siv
+                    .call_on_name(CODE_HASH, |view: &mut EditView| {
+                        view.get_content().to_string()
+                    })
+                    .unwrap(),
+                args: siv
+                    .call_on_name(ARGS, |view: &mut EditView| view.get_content().to_string())
+                    .unwrap(),
+                hash_type: ScriptHashType::clone(&hash_type_group.selection()),
+            };
+            match input.parse() {
+                Ok(lock) => {
+                    let dialog = dao_positions_dialog(siv, &client, lock);
+                    siv.add_layer(dialog);
+                }
+                Err(e) => siv.add_layer(
+                    Dialog::around(TextView::new(format!("{:?}", e)))
+                        .title("Error")
+                        .button("Close", |siv| {
+                            siv.pop_layer();
+                        }),
+                ),
+            }
+        })
+        .button("Close", |siv| {
+            siv.pop_layer();
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_sdk::CkbRpcClient;
+    use serde_json::{Value, json};
+
+    use super::{countdown, fetch_dao_positions, maximum_withdraw, minimal_withdraw_epoch};
+    use crate::mock_node::{MockNode, fixtures};
+
+    fn epoch(number: u64, index: u64, length: u64) -> u64 {
+        number | (index << 24) | (length << 40)
+    }
+
+    #[test]
+    fn computes_compensation_of_counted_capacity() {
+        // 102 CKB occupied by a secp256k1 DAO cell earns nothing
+        let capacity = 1000 * 100_000_000;
+        let occupied = 102 * 100_000_000;
+        let deposit_ar = 10_000_000_000_000_000;
+        let withdraw_ar = 10_100_000_000_000_000;
+        assert_eq!(
+            maximum_withdraw(capacity, occupied, deposit_ar, withdraw_ar) - capacity,
+            898 * 1_000_000
+        );
+        assert_eq!(
+            maximum_withdraw(capacity, occupied, deposit_ar, deposit_ar),
+            capacity
+        );
+    }
+
+    #[test]
+    fn rounds_lock_period_up_to_cycles_of_180_epochs() {
+        let deposit = epoch(100, 300, 1800);
+        // Within the first cycle
+        assert_eq!(
+            minimal_withdraw_epoch(deposit, epoch(150, 0, 1800)),
+            (280, 300, 1800)
+        );
+        // Exactly at the end of the first cycle
+        assert_eq!(
+            minimal_withdraw_epoch(deposit, epoch(280, 300, 1800)),
+            (280, 300, 1800)
+        );
+        // Just after it, waiting for the second cycle
+        assert_eq!(
+            minimal_withdraw_epoch(deposit, epoch(280, 301, 1800)),
+            (460, 300, 1800)
+        );
+    }
+
+    #[test]
+    fn counts_down_in_epochs() {
+        assert_eq!(
+            countdown((280, 900, 1800), epoch(279, 0, 1800)),
+            "1.5 epochs (~0d 6h)"
+        );
+        assert_eq!(countdown((280, 0, 1800), epoch(280, 1, 1800)), "Now");
+    }
+
+    fn dao_cell(tx_byte: u8, block_number: u64, data: &[u8]) -> Value {
+        json!({
+            "output": {
+                "capacity": format!("{:#x}", 1000 * 100_000_000u64),
+                "lock": {"code_hash": fixtures::hash(0x9b), "hash_type": "type", "args": "0x"},
+                "type": {"code_hash": fixtures::hash(0x82), "hash_type": "type", "args": "0x"},
+            },
+            "output_data": format!(
+                "0x{}",
+                data.iter().map(|x| format!("{:02x}", x)).collect::<String>()
+            ),
+            "out_point": {"tx_hash": fixtures::hash(tx_byte), "index": "0x0"},
+            "block_number": format!("{:#x}", block_number),
+            "tx_index": "0x1",
+        })
+    }
+
+    #[test]
+    fn classifies_deposits_and_withdrawals() {
+        let node = MockNode::start();
+        let deposit_block = fixtures::TIP_NUMBER - 100;
+        node.set_result(
+            "get_cells",
+            json!({
+                "objects": [
+                    dao_cell(0x41, deposit_block, &0u64.to_le_bytes()),
+                    dao_cell(0x42, fixtures::TIP_NUMBER, &deposit_block.to_le_bytes()),
+                    dao_cell(0x43, deposit_block, &[0; 16]),
+                ],
+                "last_cursor": "0x",
+            }),
+        );
+        // Deposited at half the accumulated rate of the tip, at the start of the tip's epoch
+        let mut deposit = fixtures::first_header_in_epoch();
+        deposit["dao"] = json!(format!("0x{}6d{}", "da".repeat(15), "da".repeat(16)));
+        node.set_result("get_header_by_number", deposit);
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let result = fetch_dao_positions(&client, Default::default()).unwrap();
+
+        assert_eq!(result.positions.len(), 2);
+        let deposited = &result.positions[0];
+        assert_eq!(deposited.withdrawing_block, None);
+        assert_eq!(deposited.deposit_block, deposit_block);
+        assert!(deposited.compensation > 0);
+        // Phase 1 at the tip would fall into the first cycle
+        assert_eq!(deposited.withdrawable_epoch, (190, 0, 1800));
+        let withdrawing = &result.positions[1];
+        assert_eq!(withdrawing.withdrawing_block, Some(fixtures::TIP_NUMBER));
+        assert_eq!(withdrawing.deposit_block, deposit_block);
+        // The mock serves the deposit header for the phase 1 block too
+        assert_eq!(withdrawing.compensation, 0);
+        // The malformed cell is reported without failing the scan
+        assert_eq!(result.malformed.len(), 1);
+        assert_eq!(
+            format!("{:#x}", result.malformed[0].tx_hash),
+            fixtures::hash(0x43)
+        );
+        // Each of the deposit and phase 1 blocks is fetched once
+        assert_eq!(node.calls("get_header_by_number"), 2);
+    }
+}
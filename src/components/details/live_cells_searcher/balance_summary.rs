@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, anyhow};
+use ckb_fixed_hash_core::H256;
+use ckb_jsonrpc_types::Script;
+use ckb_sdk::{
+    CkbRpcClient,
+    rpc::ckb_indexer::{Cell, Order, SearchKey},
+};
+use cursive::{
+    Cursive,
+    view::{IntoBoxedView, Resizable, Scrollable},
+    views::{Dialog, LinearLayout, ListView, Panel, TextView},
+};
+use cursive_async_view::AsyncView;
+
+use crate::{
+    components::details::live_cells_searcher::display_cells_dialog::live_cells_error_message,
+    utils::{shannons_to_ckb_string, shorten_hex},
+};
+
+/// Cells fetched by each `get_cells` call while scanning
+const SCAN_PAGE_SIZE: u32 = 1000;
+const SHANNONS_PER_BYTE: u64 = 100_000_000;
+
+/// Bytes taken by a script in a cell: code hash, hash type and args
+fn script_occupied_bytes(script: &Script) -> u64 {
+    32 + 1 + script.args.len() as u64
+}
+
+/// Capacity in shannons the cell needs to hold its capacity field, scripts and data
+pub fn occupied_capacity(cell: &Cell) -> u64 {
+    let bytes = 8
+        + script_occupied_bytes(&cell.output.lock)
+        + cell
+            .output
+            .type_
+            .as_ref()
+            .map(script_occupied_bytes)
+            .unwrap_or_default()
+        + cell
+            .output_data
+            .as_ref()
+            .map(|x| x.len() as u64)
+            .unwrap_or_default();
+    bytes * SHANNONS_PER_BYTE
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TypedCells {
+    pub count: u64,
+    pub capacity: u64,
+}
+
+/// Balance of the live cells matched by a search key
+#[derive(Default, Debug)]
+pub struct BalanceSummary {
+    /// Reported by `get_cells_capacity`
+    pub total_capacity: u64,
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub cells_count: u64,
+    /// Sum of the capacities of the scanned cells
+    pub scanned_capacity: u64,
+    pub occupied_capacity: u64,
+    /// Cells carrying a type script, by the code hash and hash type of the script
+    pub typed_cells: BTreeMap<(String, String), TypedCells>,
+}
+
+impl BalanceSummary {
+    pub fn add_cell(&mut self, cell: &Cell) {
+        let capacity = cell.output.capacity.value();
+        self.cells_count += 1;
+        self.scanned_capacity += capacity;
+        self.occupied_capacity += occupied_capacity(cell);
+        if let Some(type_script) = &cell.output.type_ {
+            let entry = self
+                .typed_cells
+                .entry((
+                    format!("{:#x}", type_script.code_hash),
+                    format!("{:?}", type_script.hash_type),
+                ))
+                .or_default();
+            entry.count += 1;
+            entry.capacity += capacity;
+        }
+    }
+
+    pub fn free_capacity(&self) -> u64 {
+        self.scanned_capacity.saturating_sub(self.occupied_capacity)
+    }
+}
+
+/// Fetches the total capacity, then scans all matched cells to count and classify them
+pub fn fetch_balance_summary(
+    client: &CkbRpcClient,
+    search_key: SearchKey,
+) -> anyhow::Result<BalanceSummary> {
+    let capacity = client
+        .get_cells_capacity(search_key.clone())
+        .with_context(|| anyhow!("Unable to get cells capacity"))?;
+    let mut summary = BalanceSummary::default();
+    if let Some(capacity) = capacity {
+        summary.total_capacity = capacity.capacity.value();
+        summary.block_number = capacity.block_number.value();
+        summary.block_hash = capacity.block_hash;
+    }
+    // Data is needed to compute occupied capacities
+    let search_key = SearchKey {
+        with_data: Some(true),
+        ..search_key
+    };
+    let mut cursor = None;
+    loop {
+        let page = client
+            .get_cells(
+                search_key.clone(),
+                Order::Asc,
+                SCAN_PAGE_SIZE.into(),
+                cursor,
+            )
+            .with_context(|| anyhow!("Unable to get cells"))?;
+        for cell in page.objects.iter() {
+            summary.add_cell(cell);
+        }
+        if page.objects.len() < SCAN_PAGE_SIZE as usize {
+            break;
+        }
+        cursor = Some(page.last_cursor);
+    }
+    Ok(summary)
+}
+
+fn balance_summary_view(summary: BalanceSummary) -> LinearLayout {
+    let mut typed_cells_view = ListView::new();
+    let mut typed_count = 0;
+    let mut typed_capacity = 0;
+    for ((code_hash, hash_type), cells) in summary.typed_cells.iter() {
+        typed_count += cells.count;
+        typed_capacity += cells.capacity;
+        typed_cells_view.add_child(
+            format!("{} ({}):", shorten_hex(code_hash, 10, 8), hash_type),
+            TextView::new(format!(
+                "{} cells, {}",
+                cells.count,
+                shannons_to_ckb_string(cells.capacity)
+            )),
+        );
+    }
+    typed_cells_view.add_child(
+        "Without Type Script:",
+        TextView::new(format!(
+            "{} cells, {}",
+            summary.cells_count - typed_count,
+            shannons_to_ckb_string(summary.scanned_capacity - typed_capacity)
+        )),
+    );
+    let mut layout = LinearLayout::vertical().child(
+        ListView::new()
+            .child(
+                "Total Capacity:",
+                TextView::new(shannons_to_ckb_string(summary.total_capacity)),
+            )
+            .child(
+                "Live Cells:",
+                TextView::new(summary.cells_count.to_string()),
+            )
+            .child(
+                "Free:",
+                TextView::new(shannons_to_ckb_string(summary.free_capacity())),
+            )
+            .child(
+                "Occupied:",
+                TextView::new(shannons_to_ckb_string(summary.occupied_capacity)),
+            )
+            .child(
+                "At Block:",
+                TextView::new(format!(
+                    "{} ({})",
+                    summary.block_number,
+                    shorten_hex(format!("{:#x}", summary.block_hash), 10, 8)
+                )),
+            ),
+    );
+    if summary.scanned_capacity != summary.total_capacity {
+        layout.add_child(TextView::new(format!(
+            "The chain moved while scanning, scanned cells hold {}",
+            shannons_to_ckb_string(summary.scanned_capacity)
+        )));
+    }
+    layout.child(
+        Panel::new(typed_cells_view.scrollable().max_height(10)).title("Cells by Type Script"),
+    )
+}
+
+/// Summary of the capacity of the cells matched by `search_key`
+pub fn balance_summary_dialog(
+    siv: &mut Cursive,
+    client: &CkbRpcClient,
+    search_key: SearchKey,
+) -> impl IntoBoxedView + use<> {
+    let client = client.clone();
+    Dialog::around(
+        AsyncView::new_with_bg_creator(
+            siv,
+            move || {
+                fetch_balance_summary(&client, search_key).map_err(|e| live_cells_error_message(&e))
+            },
+            balance_summary_view,
+        )
+        .with_width(80),
+    )
+    .title("Balance Summary")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_gen_types::core::ScriptHashType;
+    use ckb_sdk::{
+        CkbRpcClient,
+        rpc::ckb_indexer::{ScriptType, SearchMode},
+    };
+
+    use super::fetch_balance_summary;
+    use crate::{
+        components::details::live_cells_searcher::search_key::{ScriptInput, SearchForm},
+        mock_node::{MockNode, fixtures},
+    };
+
+    fn search_key() -> ckb_sdk::rpc::ckb_indexer::SearchKey {
+        let script = |code_hash: String| ScriptInput {
+            code_hash,
+            args: String::new(),
+            hash_type: ScriptHashType::Type,
+        };
+        SearchForm {
+            script_type: ScriptType::Lock,
+            script: script(fixtures::hash(0x9b)),
+            search_mode: SearchMode::Prefix,
+            secondary_script: script(String::new()),
+            capacity_range: Default::default(),
+            data_len_range: Default::default(),
+            block_range: Default::default(),
+        }
+        .to_search_key()
+        .unwrap()
+    }
+
+    #[test]
+    fn summarizes_free_occupied_and_typed_capacity() {
+        let node = MockNode::start();
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let summary = fetch_balance_summary(&client, search_key()).unwrap();
+        assert_eq!(summary.total_capacity, 1142 * 100_000_000);
+        assert_eq!(summary.block_number, fixtures::TIP_NUMBER);
+        assert_eq!(summary.cells_count, 2);
+        assert_eq!(summary.scanned_capacity, summary.total_capacity);
+        // 41 bytes of the plain cell, 122 bytes of the typed one
+        assert_eq!(summary.occupied_capacity, 163 * 100_000_000);
+        assert_eq!(summary.free_capacity(), 979 * 100_000_000);
+        assert_eq!(summary.typed_cells.len(), 1);
+        let ((code_hash, _), cells) = summary.typed_cells.iter().next().unwrap();
+        assert_eq!(code_hash, &fixtures::hash(0x5e));
+        assert_eq!(cells.count, 1);
+        assert_eq!(cells.capacity, 142 * 100_000_000);
+        assert_eq!(node.calls("get_cells"), 1);
+    }
+
+    #[test]
+    fn reports_missing_indexer() {
+        let node = MockNode::start();
+        node.remove("get_cells_capacity");
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let error = fetch_balance_summary(&client, search_key()).unwrap_err();
+        assert!(super::live_cells_error_message(&error).contains("Indexer RPC is unavailable"));
+    }
+}
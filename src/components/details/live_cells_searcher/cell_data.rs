@@ -0,0 +1,197 @@
+use ckb_jsonrpc_types::{Script, ScriptHashType};
+
+/// Type hash of the Nervos DAO script on mainnet and testnet
+pub const DAO_TYPE_HASH: &str =
+    "0x82d76d1b75fe2fd9a27dfbaa65a039221a380d76c926f378d3f81cf3e7e13f2e";
+/// Code hash of Type ID, a built-in script of CKB
+const TYPE_ID_CODE_HASH: &str =
+    "0x00000000000000000000000000000000000000000000000000545950455f4944";
+/// sUDT on mainnet and testnet
+const SUDT_CODE_HASHES: [&str; 2] = [
+    "0x5e7a36a77e68eecc013dfa2fe6a23f3b6c344b04005808694ae6dd45eea4cfd5",
+    "0xc5e5dcf215925f7ef4dfaf5f4b4f105bc321c02776d6e7d52a1db3fcd9d011a4",
+];
+/// xUDT on mainnet and testnet
+const XUDT_CODE_HASHES: [&str; 2] = [
+    "0x50bd8d6680b8b9cf98b73f3c08faf8b2a21914311954118ad6609be6e78a1b95",
+    "0x25c29dc317811a6f6f3985a7a9ebc4838bd388d19d0feeecf0bcd60f6c0975bb",
+];
+
+/// Bytes of the amount of sUDT and xUDT cells
+const UDT_AMOUNT_LEN: usize = 16;
+
+#[derive(Debug, PartialEq, Eq)]
+enum KnownType {
+    Sudt,
+    Xudt,
+    Dao,
+    TypeId,
+}
+
+fn known_type(script: &Script, dao_type_hash: &str) -> Option<KnownType> {
+    if script.hash_type != ScriptHashType::Type {
+        return None;
+    }
+    let code_hash = format!("{:#x}", script.code_hash);
+    if SUDT_CODE_HASHES.contains(&code_hash.as_str()) {
+        Some(KnownType::Sudt)
+    } else if XUDT_CODE_HASHES.contains(&code_hash.as_str()) {
+        Some(KnownType::Xudt)
+    } else if code_hash == dao_type_hash {
+        Some(KnownType::Dao)
+    } else if code_hash == TYPE_ID_CODE_HASH {
+        Some(KnownType::TypeId)
+    } else {
+        None
+    }
+}
+
+fn udt_amount(data: &[u8]) -> Option<u128> {
+    Some(u128::from_le_bytes(
+        data.get(..UDT_AMOUNT_LEN)?.try_into().ok()?,
+    ))
+}
+
+/// Decodes the data of cells of well-known type scripts, chosen by the code hash of the type
+/// script. Returns the label and the decoded value, `None` if the type script is unknown
+pub fn decode_cell_data(
+    type_script: Option<&Script>,
+    data: &[u8],
+    dao_type_hash: &str,
+) -> Option<(&'static str, String)> {
+    let type_script = type_script?;
+    let decoded = match known_type(type_script, dao_type_hash)? {
+        KnownType::Sudt => (
+            "sUDT Amount:",
+            match udt_amount(data) {
+                Some(amount) => amount.to_string(),
+                None => format!("Malformed, expected {} bytes of data", UDT_AMOUNT_LEN),
+            },
+        ),
+        KnownType::Xudt => (
+            "xUDT Amount:",
+            match udt_amount(data) {
+                Some(amount) if data.len() > UDT_AMOUNT_LEN => format!(
+                    "{} (with {} bytes of extension data)",
+                    amount,
+                    data.len() - UDT_AMOUNT_LEN
+                ),
+                Some(amount) => amount.to_string(),
+                None => format!("Malformed, expected {} bytes of data", UDT_AMOUNT_LEN),
+            },
+        ),
+        KnownType::Dao => (
+            "Nervos DAO:",
+            match <[u8; 8]>::try_from(data).map(u64::from_le_bytes) {
+                Ok(0) => String::from("Deposit"),
+                Ok(block_number) => {
+                    format!("Withdrawing, deposited at block {}", block_number)
+                }
+                Err(_) => String::from("Malformed, expected 8 bytes of data"),
+            },
+        ),
+        KnownType::TypeId => (
+            "Type ID:",
+            format!("0x{}", byteutils::bytes_to_hex(type_script.args.as_bytes())),
+        ),
+    };
+    Some(decoded)
+}
+
+/// The data as text, if it is UTF-8 without control characters other than whitespaces
+pub fn printable_utf8(data: &[u8]) -> Option<&str> {
+    let text = std::str::from_utf8(data).ok()?;
+    (!text.is_empty()
+        && text
+            .chars()
+            .all(|x| !x.is_control() || x.is_ascii_whitespace()))
+    .then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_jsonrpc_types::{Script, ScriptHashType};
+    use serde_json::json;
+
+    use super::{
+        DAO_TYPE_HASH, SUDT_CODE_HASHES, XUDT_CODE_HASHES, decode_cell_data, printable_utf8,
+    };
+
+    fn script(code_hash: &str, args: &str) -> Script {
+        serde_json::from_value(json!({
+            "code_hash": code_hash,
+            "hash_type": "type",
+            "args": args,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn decodes_by_type_script() {
+        let amount = 1_000_000u128.to_le_bytes();
+        let sudt = script(SUDT_CODE_HASHES[0], "0x");
+        assert_eq!(
+            decode_cell_data(Some(&sudt), &amount, DAO_TYPE_HASH),
+            Some(("sUDT Amount:", String::from("1000000")))
+        );
+        assert!(
+            decode_cell_data(Some(&sudt), &amount[..8], DAO_TYPE_HASH)
+                .unwrap()
+                .1
+                .starts_with("Malformed")
+        );
+        let xudt = script(XUDT_CODE_HASHES[1], "0x");
+        let mut extended = amount.to_vec();
+        extended.extend([0; 4]);
+        assert_eq!(
+            decode_cell_data(Some(&xudt), &extended, DAO_TYPE_HASH)
+                .unwrap()
+                .1,
+            "1000000 (with 4 bytes of extension data)"
+        );
+
+        let dao = script(DAO_TYPE_HASH, "0x");
+        assert_eq!(
+            decode_cell_data(Some(&dao), &[0; 8], DAO_TYPE_HASH)
+                .unwrap()
+                .1,
+            "Deposit"
+        );
+        assert_eq!(
+            decode_cell_data(Some(&dao), &1234u64.to_le_bytes(), DAO_TYPE_HASH)
+                .unwrap()
+                .1,
+            "Withdrawing, deposited at block 1234"
+        );
+
+        let type_id = script(
+            "0x00000000000000000000000000000000000000000000000000545950455f4944",
+            &format!("0x{}", "ab".repeat(32)),
+        );
+        assert_eq!(
+            decode_cell_data(Some(&type_id), b"code", DAO_TYPE_HASH)
+                .unwrap()
+                .1,
+            format!("0x{}", "ab".repeat(32))
+        );
+
+        // Only scripts referenced by type hash are recognized
+        let by_data = Script {
+            hash_type: ScriptHashType::Data1,
+            ..sudt
+        };
+        assert_eq!(
+            decode_cell_data(Some(&by_data), &amount, DAO_TYPE_HASH),
+            None
+        );
+        assert_eq!(decode_cell_data(None, &amount, DAO_TYPE_HASH), None);
+    }
+
+    #[test]
+    fn detects_printable_text() {
+        assert_eq!(printable_utf8(b"hello\nworld"), Some("hello\nworld"));
+        assert_eq!(printable_utf8(b""), None);
+        assert_eq!(printable_utf8(&[0, 1, 2]), None);
+        assert_eq!(printable_utf8(&[0xff, 0xfe]), None);
+    }
+}
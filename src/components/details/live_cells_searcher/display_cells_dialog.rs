@@ -1,16 +1,15 @@
 use std::sync::{Arc, Mutex, atomic::AtomicBool, mpsc::TryRecvError};
 
-use ckb_fixed_hash_core::H256;
-use ckb_gen_types::core::ScriptHashType;
-use ckb_jsonrpc_types::{JsonBytes, Script};
+use anyhow::{Context, anyhow};
+use ckb_jsonrpc_types::Script;
 use ckb_sdk::{
     CkbRpcClient,
     rpc::ckb_indexer::{Cell, Pagination, SearchKey},
 };
 use cursive::{
     CbSink, Cursive, View,
-    view::{IntoBoxedView, Nameable, Resizable},
-    views::{Button, Dialog, LinearLayout, ListView, OnLayoutView, TextView},
+    view::{IntoBoxedView, Nameable, Resizable, Scrollable},
+    views::{Button, Dialog, LinearLayout, ListView, OnLayoutView, Panel, TextView},
 };
 use cursive_aligned_view::Alignable;
 use cursive_async_view::{AsyncState, AsyncView};
@@ -18,8 +17,10 @@ use cursive_table_view::{TableView, TableViewItem};
 use log::info;
 
 use crate::{
-    components::details::live_cells_searcher::display_cells_dialog::names::{
-        CELLS_TABLE, PAGE_LABEL,
+    components::details::live_cells_searcher::{
+        cell_data::{DAO_TYPE_HASH, decode_cell_data, printable_utf8},
+        display_cells_dialog::names::{CELLS_TABLE, PAGE_LABEL},
+        export_cells::export_cells_dialog,
     },
     declare_names,
     utils::shorten_hex,
@@ -245,7 +246,7 @@ fn load_next_page(
     siv.add_layer(async_view);
 }
 
-fn live_cells_error_message(error: &anyhow::Error) -> String {
+pub(super) fn live_cells_error_message(error: &anyhow::Error) -> String {
     let method_not_found = error.chain().any(|cause| {
         cause
             .to_string()
@@ -262,24 +263,11 @@ fn live_cells_error_message(error: &anyhow::Error) -> String {
 
 pub fn display_cells_dialog(
     client: &CkbRpcClient,
-    lock_args: JsonBytes,
-    lock_hash: H256,
-    script_hash_type: ScriptHashType,
+    search_key: SearchKey,
     cb_sink: CbSink,
 ) -> impl IntoBoxedView {
     let data = Arc::new(Mutex::<CellsData>::new(CellsData::new(
-        SearchKey {
-            script: Script {
-                args: lock_args,
-                code_hash: lock_hash,
-                hash_type: script_hash_type.into(),
-            },
-            script_type: ckb_sdk::rpc::ckb_indexer::ScriptType::Lock,
-            filter: None,
-            group_by_transaction: Some(false),
-            script_search_mode: None,
-            with_data: Some(false),
-        },
+        search_key,
         client.clone(),
     )));
     let initialized = Arc::new(AtomicBool::new(false));
@@ -287,6 +275,8 @@ pub fn display_cells_dialog(
     let data_cloned = Arc::<Mutex<CellsData>>::clone(&data);
     let data_cloned_2 = Arc::<Mutex<CellsData>>::clone(&data);
     let data_cloned_3 = Arc::<Mutex<CellsData>>::clone(&data);
+    let data_cloned_4 = Arc::<Mutex<CellsData>>::clone(&data);
+    let client_cloned = client.clone();
 
     OnLayoutView::new(
         Dialog::new()
@@ -312,7 +302,7 @@ pub fn display_cells_dialog(
                             .column(CellsDisplayColumns::OutPointIndex, "OutPoint Index", |c| {
                                 c.width(20)
                             })
-                            .on_submit(|siv, _, data_index| {
+                            .on_submit(move |siv, _, data_index| {
                                 let data = siv
                                     .call_on_name(
                                         CELLS_TABLE,
@@ -321,7 +311,8 @@ pub fn display_cells_dialog(
                                         },
                                     )
                                     .unwrap();
-                                siv.add_layer(cell_detail_dialog(&data.0));
+                                let dialog = cell_detail_dialog(siv, &client_cloned, &data.0);
+                                siv.add_layer(dialog);
                             })
                             .with_name(CELLS_TABLE)
                             .min_width(110)
@@ -354,6 +345,10 @@ pub fn display_cells_dialog(
                             .align_center(),
                     ),
             )
+            .button("Export", move |siv| {
+                let guard = data_cloned_4.lock().unwrap();
+                siv.add_layer(export_cells_dialog(&guard.client, guard.search_key.clone()));
+            })
             .button("Close", |siv| {
                 siv.pop_layer();
             }),
@@ -372,7 +367,70 @@ pub fn display_cells_dialog(
     )
 }
 
-fn cell_detail_dialog(data: &Cell) -> impl IntoBoxedView {
+/// Data of a cell, with the DAO type hash of the chain to decode it
+struct CellDataDetail {
+    data: Vec<u8>,
+    dao_type_hash: String,
+}
+
+fn fetch_cell_data(client: &CkbRpcClient, cell: &Cell) -> anyhow::Result<CellDataDetail> {
+    let data = match &cell.output_data {
+        Some(data) => data.as_bytes().to_vec(),
+        None => client
+            .get_live_cell(cell.out_point.clone(), true)
+            .with_context(|| anyhow!("Unable to get live cell"))?
+            .cell
+            .and_then(|x| x.data)
+            .ok_or_else(|| anyhow!("The cell is no longer live"))?
+            .content
+            .into_bytes()
+            .to_vec(),
+    };
+    // Devnets have their own DAO type hash
+    let dao_type_hash = client
+        .get_consensus()
+        .map(|x| format!("{:#x}", x.dao_type_hash))
+        .unwrap_or_else(|_| DAO_TYPE_HASH.to_string());
+    Ok(CellDataDetail {
+        data,
+        dao_type_hash,
+    })
+}
+
+fn cell_data_view(detail: CellDataDetail, type_script: Option<Script>) -> ListView {
+    let data = &detail.data;
+    let mut list_view = ListView::new()
+        .child("Size:", TextView::new(format!("{} bytes", data.len())))
+        .child(
+            "Hex:",
+            TextView::new(format!("0x{}", byteutils::bytes_to_hex(data)))
+                .scrollable()
+                .max_height(6),
+        );
+    if let Some(text) = printable_utf8(data) {
+        list_view.add_child("UTF-8:", TextView::new(text).scrollable().max_height(6));
+    }
+    if let Some((label, value)) =
+        decode_cell_data(type_script.as_ref(), data, &detail.dao_type_hash)
+    {
+        list_view.add_child(label, TextView::new(value));
+    }
+    list_view
+}
+
+fn cell_detail_dialog(
+    siv: &mut Cursive,
+    client: &CkbRpcClient,
+    data: &Cell,
+) -> impl IntoBoxedView + use<> {
+    let client = client.clone();
+    let cell = data.clone();
+    let type_script = data.output.type_.clone();
+    let data_view = AsyncView::new_with_bg_creator(
+        siv,
+        move || fetch_cell_data(&client, &cell).map_err(|e| live_cells_error_message(&e)),
+        move |detail| cell_data_view(detail, type_script.clone()),
+    );
     let mut list_view = ListView::new()
         .child(
             "Capacity (in shannons):",
@@ -435,12 +493,20 @@ fn cell_detail_dialog(data: &Cell) -> impl IntoBoxedView {
         .button("Close", |siv| {
             siv.pop_layer();
         })
-        .content(list_view)
+        .content(
+            LinearLayout::vertical()
+                .child(list_view)
+                .child(Panel::new(data_view.min_width(80)).title("Data")),
+        )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{INDEXER_UNAVAILABLE_MESSAGE, live_cells_error_message};
+    use ckb_sdk::{CkbRpcClient, rpc::ckb_indexer::Cell};
+    use serde_json::json;
+
+    use super::{INDEXER_UNAVAILABLE_MESSAGE, fetch_cell_data, live_cells_error_message};
+    use crate::mock_node::{MockNode, fixtures};
 
     #[test]
     fn explains_how_to_enable_indexer_when_rpc_method_is_missing() {
@@ -458,4 +524,30 @@ mod tests {
 
         assert_eq!(live_cells_error_message(&error), "connection refused");
     }
+
+    #[test]
+    fn fetches_data_of_cells_listed_without_it() {
+        let node = MockNode::start();
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let mut cell = fixtures::cells()["objects"][1].clone();
+        cell["output_data"] = json!(null);
+        let cell = serde_json::from_value::<Cell>(cell).unwrap();
+        node.set_result(
+            "get_live_cell",
+            json!({
+                "cell": {
+                    "output": fixtures::cells()["objects"][1]["output"],
+                    "data": {"content": "0x68656c6c6f", "hash": fixtures::hash(0x33)},
+                },
+                "status": "live",
+            }),
+        );
+        let detail = fetch_cell_data(&client, &cell).unwrap();
+        assert_eq!(detail.data, b"hello");
+        assert!(detail.dao_type_hash.ends_with(&"82".repeat(32)));
+
+        node.set_result("get_live_cell", json!({"cell": null, "status": "dead"}));
+        let error = fetch_cell_data(&client, &cell).err().unwrap();
+        assert!(format!("{:#}", error).contains("no longer live"));
+    }
 }
@@ -0,0 +1,374 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use anyhow::{Context, anyhow};
+use ckb_jsonrpc_types::{JsonBytes, Script};
+use ckb_sdk::{
+    CkbRpcClient,
+    rpc::ckb_indexer::{Cell, Order, SearchKey},
+};
+use cursive::{
+    CbSink,
+    view::{IntoBoxedView, Nameable, Resizable},
+    views::{Checkbox, Dialog, DummyView, EditView, LinearLayout, ListView, RadioGroup, TextView},
+};
+
+use crate::{
+    components::details::live_cells_searcher::{
+        display_cells_dialog::live_cells_error_message,
+        export_cells::names::{EXPORT_PATH, EXPORT_PROGRESS, INCLUDE_DATA},
+    },
+    declare_names,
+};
+
+declare_names!(
+    names,
+    "live_cells_searcher_export_cells_",
+    EXPORT_PATH,
+    INCLUDE_DATA,
+    EXPORT_PROGRESS
+);
+
+/// Cells fetched by each `get_cells` call while exporting
+const EXPORT_PAGE_SIZE: u32 = 500;
+
+const CSV_HEADER: &str = "tx_hash,index,block_number,tx_index,capacity,lock_code_hash,lock_hash_type,lock_args,type_code_hash,type_hash_type,type_args,output_data";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    /// One `Cell` object of the indexer per line
+    JsonLines,
+    /// One row per cell, numbers in decimal and bytes in hex
+    Csv,
+}
+
+fn bytes_to_hex(bytes: &JsonBytes) -> String {
+    format!("0x{}", byteutils::bytes_to_hex(bytes.as_bytes()))
+}
+
+/// Code hash, hash type and args columns of a script, empty if there is no script
+fn script_columns(script: Option<&Script>) -> [String; 3] {
+    match script {
+        Some(script) => [
+            format!("{:#x}", script.code_hash),
+            serde_json::to_value(script.hash_type)
+                .ok()
+                .and_then(|x| x.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            bytes_to_hex(&script.args),
+        ],
+        None => Default::default(),
+    }
+}
+
+/// A CSV row of the cell. None of the columns may contain commas or quotes, so nothing is
+/// escaped
+fn csv_row(cell: &Cell) -> String {
+    let mut columns = vec![
+        format!("{:#x}", cell.out_point.tx_hash),
+        cell.out_point.index.value().to_string(),
+        cell.block_number.value().to_string(),
+        cell.tx_index.value().to_string(),
+        cell.output.capacity.value().to_string(),
+    ];
+    columns.extend(script_columns(Some(&cell.output.lock)));
+    columns.extend(script_columns(cell.output.type_.as_ref()));
+    columns.push(
+        cell.output_data
+            .as_ref()
+            .map(bytes_to_hex)
+            .unwrap_or_default(),
+    );
+    columns.join(",")
+}
+
+/// Writes all cells matched by `search_key` to `writer` page by page, calling `on_progress` with
+/// the number of exported cells after each page. Stops early once `cancelled` is set, returning
+/// the number of exported cells and whether the export was cancelled
+pub fn export_cells(
+    client: &CkbRpcClient,
+    search_key: SearchKey,
+    include_data: bool,
+    format: ExportFormat,
+    writer: &mut impl Write,
+    cancelled: &AtomicBool,
+    mut on_progress: impl FnMut(u64),
+) -> anyhow::Result<(u64, bool)> {
+    let search_key = SearchKey {
+        with_data: Some(include_data),
+        ..search_key
+    };
+    if format == ExportFormat::Csv {
+        writeln!(writer, "{}", CSV_HEADER)?;
+    }
+    let mut exported = 0;
+    let mut cursor = None;
+    loop {
+        if cancelled.load(Ordering::SeqCst) {
+            writer.flush()?;
+            return Ok((exported, true));
+        }
+        let page = client
+            .get_cells(
+                search_key.clone(),
+                Order::Asc,
+                EXPORT_PAGE_SIZE.into(),
+                cursor,
+            )
+            .with_context(|| anyhow!("Unable to get cells"))?;
+        for cell in page.objects.iter() {
+            match format {
+                ExportFormat::JsonLines => writeln!(writer, "{}", serde_json::to_string(cell)?)?,
+                ExportFormat::Csv => writeln!(writer, "{}", csv_row(cell))?,
+            }
+        }
+        exported += page.objects.len() as u64;
+        on_progress(exported);
+        if page.objects.len() < EXPORT_PAGE_SIZE as usize {
+            break;
+        }
+        cursor = Some(page.last_cursor);
+    }
+    writer.flush()?;
+    Ok((exported, false))
+}
+
+fn result_dialog(title: &str, message: String) -> Dialog {
+    Dialog::around(TextView::new(message))
+        .title(title)
+        .button("Close", |siv| {
+            siv.pop_layer();
+        })
+}
+
+/// Exports to `path` in a background thread, showing the progress in a dialog that can cancel it.
+/// The file is removed if the export fails or is cancelled
+fn start_export(
+    client: CkbRpcClient,
+    search_key: SearchKey,
+    path: String,
+    include_data: bool,
+    format: ExportFormat,
+    cb_sink: CbSink,
+) -> anyhow::Result<Dialog> {
+    let file = File::create(&path).with_context(|| anyhow!("Unable to create {}", path))?;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let cancelled_cloned = Arc::clone(&cancelled);
+    std::thread::spawn(move || {
+        let mut writer = BufWriter::new(file);
+        let progress_sink = cb_sink.clone();
+        let result = export_cells(
+            &client,
+            search_key,
+            include_data,
+            format,
+            &mut writer,
+            &cancelled_cloned,
+            |exported| {
+                progress_sink
+                    .send(Box::new(move |siv| {
+                        siv.call_on_name(EXPORT_PROGRESS, |view: &mut TextView| {
+                            view.set_content(format!("Exported {} cells..", exported));
+                        });
+                    }))
+                    .ok();
+            },
+        );
+        drop(writer);
+        let dialog = match result {
+            Ok((exported, false)) => result_dialog(
+                "Export Finished",
+                format!("Exported {} cells to {}", exported, path),
+            ),
+            Ok((exported, true)) => {
+                std::fs::remove_file(&path).ok();
+                result_dialog(
+                    "Export Cancelled",
+                    format!("Cancelled after {} cells, {} was removed", exported, path),
+                )
+            }
+            Err(e) => {
+                std::fs::remove_file(&path).ok();
+                result_dialog("Error", live_cells_error_message(&e))
+            }
+        };
+        cb_sink
+            .send(Box::new(move |siv| {
+                siv.pop_layer();
+                siv.add_layer(dialog);
+            }))
+            .ok();
+    });
+    Ok(Dialog::around(
+        TextView::new("Exported 0 cells..")
+            .with_name(EXPORT_PROGRESS)
+            .min_width(50),
+    )
+    .title("Exporting Cells")
+    .button("Cancel", move |siv| {
+        cancelled.store(true, Ordering::SeqCst);
+        siv.call_on_name(EXPORT_PROGRESS, |view: &mut TextView| {
+            view.set_content("Cancelling..");
+        });
+    }))
+}
+
+/// Asks for the file and format to export the cells matched by `search_key` to
+pub fn export_cells_dialog(client: &CkbRpcClient, search_key: SearchKey) -> impl IntoBoxedView {
+    let client = client.clone();
+    let mut format_radios = RadioGroup::<ExportFormat>::new();
+    format_radios.set_on_change(|siv, format| {
+        let extension = match format {
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::Csv => "csv",
+        };
+        siv.call_on_name(EXPORT_PATH, |view: &mut EditView| {
+            let path = view.get_content();
+            let stem = path
+                .rsplit_once('.')
+                .map(|(stem, _)| stem)
+                .unwrap_or(path.as_str());
+            let path = format!("{}.{}", stem, extension);
+            view.set_content(path);
+        });
+    });
+    Dialog::new()
+        .title("Export Cells")
+        .content(
+            ListView::new()
+                .child(
+                    "File:",
+                    EditView::new()
+                        .content("cells.jsonl")
+                        .with_name(EXPORT_PATH)
+                        .min_width(50),
+                )
+                .child(
+                    "Format:",
+                    LinearLayout::horizontal()
+                        .child(format_radios.button(ExportFormat::JsonLines, "JSON Lines"))
+                        .child(DummyView::new().min_width(5))
+                        .child(format_radios.button(ExportFormat::Csv, "CSV")),
+                )
+                .child("Include Data:", Checkbox::new().with_name(INCLUDE_DATA)),
+        )
+        .button("Export", move |siv| {
+            let path = siv
+                .call_on_name(EXPORT_PATH, |view: &mut EditView| {
+                    view.get_content().trim().to_string()
+                })
+                .unwrap();
+            let include_data = siv
+                .call_on_name(INCLUDE_DATA, |view: &mut Checkbox| view.is_checked())
+                .unwrap();
+            let format = *format_radios.selection();
+            match start_export(
+                client.clone(),
+                search_key.clone(),
+                path,
+                include_data,
+                format,
+                siv.cb_sink().clone(),
+            ) {
+                Ok(dialog) => {
+                    siv.pop_layer();
+                    siv.add_layer(dialog);
+                }
+                Err(e) => siv.add_layer(result_dialog("Error", format!("{:?}", e))),
+            }
+        })
+        .button("Close", |siv| {
+            siv.pop_layer();
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use ckb_sdk::{CkbRpcClient, rpc::ckb_indexer::SearchKey};
+    use serde_json::Value;
+
+    use super::{CSV_HEADER, ExportFormat, export_cells};
+    use crate::mock_node::{MockNode, fixtures};
+
+    fn search_key() -> SearchKey {
+        serde_json::from_value(serde_json::json!({
+            "script": {"code_hash": fixtures::hash(0x9b), "hash_type": "type", "args": "0x"},
+            "script_type": "lock",
+        }))
+        .unwrap()
+    }
+
+    fn export(node: &MockNode, include_data: bool, format: ExportFormat) -> (String, Vec<u64>) {
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let mut output = vec![];
+        let mut progress = vec![];
+        let (exported, cancelled) = export_cells(
+            &client,
+            search_key(),
+            include_data,
+            format,
+            &mut output,
+            &AtomicBool::new(false),
+            |x| progress.push(x),
+        )
+        .unwrap();
+        assert_eq!((exported, cancelled), (2, false));
+        (String::from_utf8(output).unwrap(), progress)
+    }
+
+    #[test]
+    fn exports_json_lines_and_csv() {
+        let node = MockNode::start();
+        let (output, progress) = export(&node, true, ExportFormat::JsonLines);
+        assert_eq!(progress, vec![2]);
+        let cells = output
+            .lines()
+            .map(|x| serde_json::from_str::<Value>(x).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            fixtures::cells()["objects"].as_array().unwrap().clone()
+        );
+
+        let (output, _) = export(&node, true, ExportFormat::Csv);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], CSV_HEADER);
+        let typed = lines[2].split(',').collect::<Vec<_>>();
+        assert_eq!(typed.len(), CSV_HEADER.split(',').count());
+        assert_eq!(typed[0], fixtures::hash(0x22));
+        assert_eq!(typed[4], "14200000000");
+        assert_eq!(typed[6], "type");
+        assert_eq!(typed[8], fixtures::hash(0x5e));
+        assert_eq!(typed[11], format!("0x{}", "00".repeat(16)));
+        // The plain cell has empty type script columns
+        assert!(lines[1].ends_with(",,,,0x"));
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let node = MockNode::start();
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let mut output = vec![];
+        let result = export_cells(
+            &client,
+            search_key(),
+            false,
+            ExportFormat::JsonLines,
+            &mut output,
+            &AtomicBool::new(true),
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(result, (0, true));
+        assert!(output.is_empty());
+        assert_eq!(node.calls("get_cells"), 0);
+    }
+}
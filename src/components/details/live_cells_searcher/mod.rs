@@ -1,22 +1,33 @@
-mod derive_from_ckb_address_dialog;
+pub(crate) mod balance_summary;
+pub(crate) mod cell_data;
+pub(crate) mod derive_from_ckb_address_dialog;
 mod display_cells_dialog;
-use std::str::FromStr;
+mod export_cells;
+pub(crate) mod search_key;
+mod transaction_history;
 
 use crate::{
     components::details::live_cells_searcher::{
+        balance_summary::balance_summary_dialog,
         derive_from_ckb_address_dialog::derive_from_address_dialog,
         names::{
-            HASH_TYPE_RADIO_DATA, HASH_TYPE_RADIO_DATA1, HASH_TYPE_RADIO_DATA2,
-            HASH_TYPE_RADIO_TYPE, LOCK_ARGS, LOCK_HASH,
+            BLOCK_MAX, BLOCK_MIN, CAPACITY_MAX, CAPACITY_MIN, CODE_HASH, DATA_LEN_MAX,
+            DATA_LEN_MIN, HASH_TYPE_RADIO_DATA, HASH_TYPE_RADIO_DATA1, HASH_TYPE_RADIO_DATA2,
+            HASH_TYPE_RADIO_TYPE, SCRIPT_ARGS, SCRIPT_TYPE_RADIO_LOCK, SECONDARY_ARGS,
+            SECONDARY_CODE_HASH,
         },
+        search_key::{ScriptInput, SearchForm},
+        transaction_history::transaction_history_dialog,
     },
     declare_names,
 };
-use anyhow::{Context, anyhow, bail};
-use ckb_fixed_hash_core::H256;
 use ckb_gen_types::core::ScriptHashType;
-use ckb_sdk::CkbRpcClient;
+use ckb_sdk::{
+    CkbRpcClient,
+    rpc::ckb_indexer::{ScriptType, SearchKey, SearchMode},
+};
 use cursive::{
+    Cursive,
     view::{IntoBoxedView, Nameable, Resizable},
     views::{
         Button, Dialog, DummyView, EditView, LinearLayout, ListView, RadioButton, RadioGroup,
@@ -25,22 +36,125 @@ use cursive::{
 };
 use cursive_aligned_view::Alignable;
 use display_cells_dialog::display_cells_dialog;
-use serde_json::json;
 
 declare_names!(
     names,
     "live_cells_searcher_",
-    LOCK_ARGS,
-    LOCK_HASH,
+    SCRIPT_TYPE_RADIO_LOCK,
+    SCRIPT_ARGS,
+    CODE_HASH,
     HASH_TYPE_RADIO_TYPE,
     HASH_TYPE_RADIO_DATA,
     HASH_TYPE_RADIO_DATA1,
-    HASH_TYPE_RADIO_DATA2
+    HASH_TYPE_RADIO_DATA2,
+    SECONDARY_CODE_HASH,
+    SECONDARY_ARGS,
+    CAPACITY_MIN,
+    CAPACITY_MAX,
+    DATA_LEN_MIN,
+    DATA_LEN_MAX,
+    BLOCK_MIN,
+    BLOCK_MAX
 );
 
+/// Radio buttons of the hash types, named by `names` if given
+pub(crate) fn hash_type_radios(
+    group: &mut RadioGroup<ScriptHashType>,
+    names: Option<[&'static str; 4]>,
+) -> LinearLayout {
+    let mut layout = LinearLayout::horizontal();
+    for (index, (hash_type, label)) in [
+        (ScriptHashType::Type, "Type"),
+        (ScriptHashType::Data, "Data"),
+        (ScriptHashType::Data1, "Data1"),
+        (ScriptHashType::Data2, "Data2"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        if index > 0 {
+            layout.add_child(DummyView::new().min_width(5));
+        }
+        let button = group.button(hash_type, label);
+        match names {
+            Some(names) => layout.add_child(button.with_name(names[index])),
+            None => layout.add_child(button),
+        }
+    }
+    layout
+}
+
+/// Inputs of the minimum and maximum of an inclusive range
+fn range_input(min_name: &'static str, max_name: &'static str) -> LinearLayout {
+    LinearLayout::horizontal()
+        .child(EditView::new().with_name(min_name).fixed_width(20))
+        .child(TextView::new(" to "))
+        .child(EditView::new().with_name(max_name).fixed_width(20))
+}
+
+fn edit_content(siv: &mut Cursive, name: &str) -> String {
+    siv.call_on_name(name, |view: &mut EditView| view.get_content().to_string())
+        .unwrap()
+}
+
+/// Radio groups of the searcher form
+#[derive(Clone, Default)]
+struct FormRadios {
+    script_type: RadioGroup<ScriptType>,
+    hash_type: RadioGroup<ScriptHashType>,
+    search_mode: RadioGroup<SearchMode>,
+    secondary_hash_type: RadioGroup<ScriptHashType>,
+}
+
+impl FormRadios {
+    /// Builds the search key from the form, showing an error dialog if it is invalid
+    fn read_search_key(&self, siv: &mut Cursive) -> Option<SearchKey> {
+        let form = SearchForm {
+            script_type: ScriptType::clone(&self.script_type.selection()),
+            script: ScriptInput {
+                code_hash: edit_content(siv, CODE_HASH),
+                args: edit_content(siv, SCRIPT_ARGS),
+                hash_type: ScriptHashType::clone(&self.hash_type.selection()),
+            },
+            search_mode: SearchMode::clone(&self.search_mode.selection()),
+            secondary_script: ScriptInput {
+                code_hash: edit_content(siv, SECONDARY_CODE_HASH),
+                args: edit_content(siv, SECONDARY_ARGS),
+                hash_type: ScriptHashType::clone(&self.secondary_hash_type.selection()),
+            },
+            capacity_range: (
+                edit_content(siv, CAPACITY_MIN),
+                edit_content(siv, CAPACITY_MAX),
+            ),
+            data_len_range: (
+                edit_content(siv, DATA_LEN_MIN),
+                edit_content(siv, DATA_LEN_MAX),
+            ),
+            block_range: (edit_content(siv, BLOCK_MIN), edit_content(siv, BLOCK_MAX)),
+        };
+        match form.to_search_key() {
+            Ok(search_key) => Some(search_key),
+            Err(e) => {
+                siv.add_layer(
+                    Dialog::around(TextView::new(format!("{:?}", e)))
+                        .button("Close", |siv| {
+                            siv.pop_layer();
+                        })
+                        .title("Error"),
+                );
+                None
+            }
+        }
+    }
+}
+
 pub fn live_cells_searcher(client: &CkbRpcClient) -> impl IntoBoxedView {
     let client_cloned = client.clone();
-    let mut script_hash_type_radios = RadioGroup::<ScriptHashType>::new();
+    let client_cloned_2 = client.clone();
+    let mut radios = FormRadios::default();
+    let radios_cloned = radios.clone();
+    let radios_cloned_2 = radios.clone();
+    let client_cloned_3 = client.clone();
     Dialog::new()
         .title("Live Cells Searcher")
         .content(
@@ -48,44 +162,70 @@ pub fn live_cells_searcher(client: &CkbRpcClient) -> impl IntoBoxedView {
                 .child(
                     ListView::new()
                         .child(
-                            "Lock Args:",
-                            EditView::new().with_name(LOCK_ARGS).min_width(50),
+                            "Search By:",
+                            LinearLayout::horizontal()
+                                .child(
+                                    radios.script_type
+                                        .button(ScriptType::Lock, "Lock Script")
+                                        .with_name(SCRIPT_TYPE_RADIO_LOCK),
+                                )
+                                .child(DummyView::new().min_width(5))
+                                .child(radios.script_type.button(ScriptType::Type, "Type Script")),
                         )
-                        .child(" ", DummyView::new())
                         .child(
                             "Code Hash:",
-                            EditView::new().with_name(LOCK_HASH).min_width(50),
+                            EditView::new().with_name(CODE_HASH).min_width(50),
                         )
-                        .child(" ", DummyView::new())
+                        .child("Args:", EditView::new().with_name(SCRIPT_ARGS).min_width(50))
                         .child(
                             "Script Hash Type:",
+                            hash_type_radios(
+                                &mut radios.hash_type,
+                                Some([
+                                    HASH_TYPE_RADIO_TYPE,
+                                    HASH_TYPE_RADIO_DATA,
+                                    HASH_TYPE_RADIO_DATA1,
+                                    HASH_TYPE_RADIO_DATA2,
+                                ]),
+                            ),
+                        )
+                        .child(
+                            "Args Search Mode:",
                             LinearLayout::horizontal()
-                                .child(
-                                    script_hash_type_radios
-                                        .button(ScriptHashType::Type, "Type")
-                                        .with_name(HASH_TYPE_RADIO_TYPE),
-                                )
-                                .child(DummyView::new().min_width(5))
-                                .child(
-                                    script_hash_type_radios
-                                        .button(ScriptHashType::Data, "Data")
-                                        .with_name(HASH_TYPE_RADIO_DATA),
-                                )
+                                .child(radios.search_mode.button(SearchMode::Prefix, "Prefix"))
                                 .child(DummyView::new().min_width(5))
-                                .child(
-                                    script_hash_type_radios
-                                        .button(ScriptHashType::Data1, "Data1")
-                                        .with_name(HASH_TYPE_RADIO_DATA1),
-                                )
+                                .child(radios.search_mode.button(SearchMode::Exact, "Exact"))
                                 .child(DummyView::new().min_width(5))
-                                .child(
-                                    script_hash_type_radios
-                                        .button(ScriptHashType::Data2, "Data2")
-                                        .with_name(HASH_TYPE_RADIO_DATA2),
-                                ),
+                                .child(radios.search_mode.button(SearchMode::Partial, "Partial")),
+                        )
+                        .delimiter()
+                        .child(
+                            "Filters:",
+                            TextView::new("Optional, both ends of ranges are inclusive"),
+                        )
+                        .child(
+                            "Secondary Code Hash:",
+                            EditView::new().with_name(SECONDARY_CODE_HASH).min_width(50),
+                        )
+                        .child(
+                            "Secondary Args:",
+                            EditView::new().with_name(SECONDARY_ARGS).min_width(50),
+                        )
+                        .child(
+                            "Secondary Hash Type:",
+                            hash_type_radios(&mut radios.secondary_hash_type, None),
                         )
+                        .child("Capacity (CKB):", range_input(CAPACITY_MIN, CAPACITY_MAX))
+                        .child(
+                            "Data Length (bytes):",
+                            range_input(DATA_LEN_MIN, DATA_LEN_MAX),
+                        )
+                        .child("Block Number:", range_input(BLOCK_MIN, BLOCK_MAX))
                         .min_width(50),
                 )
+                .child(TextView::new(
+                    "The secondary script is the type script when searching by lock script, and vice versa",
+                ))
                 .child(DummyView::new())
                 .child(
                     Button::new("Derive from CKB address", move |siv| {
@@ -96,10 +236,16 @@ pub fn live_cells_searcher(client: &CkbRpcClient) -> impl IntoBoxedView {
                             move |lock_args, lock_hash, script_hash_type| {
                                 cb_sink
                                     .send(Box::new(move |siv| {
-                                        siv.call_on_name(LOCK_ARGS, |view: &mut EditView| {
+                                        siv.call_on_name(
+                                            SCRIPT_TYPE_RADIO_LOCK,
+                                            |view: &mut RadioButton<ScriptType>| {
+                                                view.select();
+                                            },
+                                        );
+                                        siv.call_on_name(SCRIPT_ARGS, |view: &mut EditView| {
                                             view.set_content(lock_args)
                                         });
-                                        siv.call_on_name(LOCK_HASH, |view: &mut EditView| {
+                                        siv.call_on_name(CODE_HASH, |view: &mut EditView| {
                                             view.set_content(lock_hash)
                                         });
                                         siv.call_on_name(
@@ -124,49 +270,22 @@ pub fn live_cells_searcher(client: &CkbRpcClient) -> impl IntoBoxedView {
                 ),
         )
         .button("Search", move |siv| {
-            let result = (|| {
-                let lock_args = siv
-                    .call_on_name(LOCK_ARGS, |view: &mut EditView| {
-                        view.get_content().to_string()
-                    })
-                    .unwrap();
-                let lock_hash = siv
-                    .call_on_name(LOCK_HASH, |view: &mut EditView| {
-                        view.get_content().to_string()
-                    })
-                    .unwrap();
-                if lock_hash.len() < 2 {
-                    bail!("Invalid lock hash");
-                }
-                let lock_hash = H256::from_str(&lock_hash[2..])
-                    .with_context(|| anyhow!("Bad lock hash: {}", lock_hash))?;
-                let lock_args = serde_json::from_value(json!(lock_args))
-                    .with_context(|| anyhow!("Bad lock args: {}", lock_args))?;
-
-                anyhow::Ok((lock_args, lock_hash))
-            })();
-            let script_hash_type = ScriptHashType::clone(&script_hash_type_radios.selection());
-            let (lock_args, lock_hash) = match result {
-                Ok((a, b)) => (a, b),
-                Err(e) => {
-                    siv.add_layer(
-                        Dialog::around(TextView::new(format!("{:?}", e)))
-                            .button("Close", |siv| {
-                                siv.pop_layer();
-                            })
-                            .title("Error"),
-                    );
-                    return;
-                }
-            };
-            let cb_sink = siv.cb_sink().clone();
-            siv.add_layer(display_cells_dialog(
-                &client_cloned,
-                lock_args,
-                lock_hash,
-                script_hash_type,
-                cb_sink,
-            ));
+            if let Some(search_key) = radios.read_search_key(siv) {
+                let cb_sink = siv.cb_sink().clone();
+                siv.add_layer(display_cells_dialog(&client_cloned, search_key, cb_sink));
+            }
+        })
+        .button("Balance Summary", move |siv| {
+            if let Some(search_key) = radios_cloned.read_search_key(siv) {
+                let dialog = balance_summary_dialog(siv, &client_cloned_2, search_key);
+                siv.add_layer(dialog);
+            }
+        })
+        .button("Transaction History", move |siv| {
+            if let Some(search_key) = radios_cloned_2.read_search_key(siv) {
+                let dialog = transaction_history_dialog(siv, &client_cloned_3, search_key);
+                siv.add_layer(dialog);
+            }
         })
         .button("Close", |siv| {
             siv.pop_layer();
@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use anyhow::{Context, anyhow, bail};
+use ckb_fixed_hash_core::H256;
+use ckb_gen_types::core::ScriptHashType;
+use ckb_jsonrpc_types::{JsonBytes, Script, Uint64};
+use ckb_sdk::rpc::ckb_indexer::{ScriptType, SearchKey, SearchKeyFilter, SearchMode};
+use serde_json::json;
+
+const SHANNONS_PER_CKB: u64 = 100_000_000;
+
+/// A script as entered in the searcher form
+pub struct ScriptInput {
+    pub code_hash: String,
+    pub args: String,
+    pub hash_type: ScriptHashType,
+}
+
+impl ScriptInput {
+    fn is_empty(&self) -> bool {
+        self.code_hash.trim().is_empty() && self.args.trim().is_empty()
+    }
+
+    pub fn parse(&self) -> anyhow::Result<Script> {
+        let code_hash = self.code_hash.trim();
+        let Some(hex) = code_hash.strip_prefix("0x") else {
+            bail!("Invalid code hash: {}", code_hash);
+        };
+        let code_hash =
+            H256::from_str(hex).with_context(|| anyhow!("Bad code hash: {}", code_hash))?;
+        // Empty args match any args in prefix mode
+        let args = match self.args.trim() {
+            "" => "0x",
+            args => args,
+        };
+        let args = serde_json::from_value::<JsonBytes>(json!(args))
+            .with_context(|| anyhow!("Bad args: {}", args))?;
+        Ok(Script {
+            code_hash,
+            hash_type: self.hash_type.into(),
+            args,
+        })
+    }
+}
+
+/// An inclusive range as entered in the searcher form, either end may be left empty
+pub type RangeInput = (String, String);
+
+/// Fields of the searcher form
+pub struct SearchForm {
+    pub script_type: ScriptType,
+    pub script: ScriptInput,
+    pub search_mode: SearchMode,
+    /// The type script when searching by lock script and vice versa
+    pub secondary_script: ScriptInput,
+    /// In CKB
+    pub capacity_range: RangeInput,
+    /// In bytes
+    pub data_len_range: RangeInput,
+    pub block_range: RangeInput,
+}
+
+/// Parses an amount of CKB with up to 8 decimals into shannons
+fn parse_ckb_amount(amount: &str) -> anyhow::Result<u64> {
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if (integer.is_empty() && fraction.is_empty())
+        || fraction.len() > 8
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|x| x.is_ascii_digit())
+    {
+        bail!("Bad CKB amount: {}", amount);
+    }
+    let integer = if integer.is_empty() {
+        0
+    } else {
+        integer.parse::<u64>()?
+    };
+    let fraction = format!("{:0<8}", fraction).parse::<u64>()?;
+    integer
+        .checked_mul(SHANNONS_PER_CKB)
+        .and_then(|x| x.checked_add(fraction))
+        .ok_or_else(|| anyhow!("CKB amount is too large: {}", amount))
+}
+
+/// Converts an inclusive range into the `[start, end)` range of the indexer, `None` if both ends
+/// are empty
+fn parse_range(
+    name: &str,
+    (min, max): &RangeInput,
+    parse: impl Fn(&str) -> anyhow::Result<u64>,
+) -> anyhow::Result<Option<[Uint64; 2]>> {
+    let (min, max) = (min.trim(), max.trim());
+    if min.is_empty() && max.is_empty() {
+        return Ok(None);
+    }
+    let start = if min.is_empty() {
+        0
+    } else {
+        parse(min).with_context(|| anyhow!("Bad minimum of {}", name))?
+    };
+    let end = if max.is_empty() {
+        u64::MAX
+    } else {
+        parse(max)
+            .with_context(|| anyhow!("Bad maximum of {}", name))?
+            .saturating_add(1)
+    };
+    if start >= end {
+        bail!("Minimum of {} is larger than its maximum", name);
+    }
+    Ok(Some([start.into(), end.into()]))
+}
+
+fn parse_integer(value: &str) -> anyhow::Result<u64> {
+    value
+        .parse::<u64>()
+        .with_context(|| anyhow!("Not an integer: {}", value))
+}
+
+impl SearchForm {
+    pub fn to_search_key(&self) -> anyhow::Result<SearchKey> {
+        let script = self.script.parse()?;
+        let filter = SearchKeyFilter {
+            script: if self.secondary_script.is_empty() {
+                None
+            } else {
+                Some(
+                    self.secondary_script
+                        .parse()
+                        .with_context(|| anyhow!("Bad secondary script"))?,
+                )
+            },
+            output_capacity_range: parse_range("capacity", &self.capacity_range, parse_ckb_amount)?,
+            output_data_len_range: parse_range("data length", &self.data_len_range, parse_integer)?,
+            block_range: parse_range("block range", &self.block_range, parse_integer)?,
+            ..Default::default()
+        };
+        let has_filter = filter.script.is_some()
+            || filter.output_capacity_range.is_some()
+            || filter.output_data_len_range.is_some()
+            || filter.block_range.is_some();
+        Ok(SearchKey {
+            script,
+            script_type: self.script_type.clone(),
+            filter: has_filter.then_some(filter),
+            group_by_transaction: Some(false),
+            script_search_mode: Some(self.search_mode.clone()),
+            with_data: Some(false),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_gen_types::core::ScriptHashType;
+    use ckb_sdk::rpc::ckb_indexer::{ScriptType, SearchMode};
+    use serde_json::json;
+
+    use super::{ScriptInput, SearchForm, parse_ckb_amount};
+
+    fn form() -> SearchForm {
+        SearchForm {
+            script_type: ScriptType::Type,
+            script: ScriptInput {
+                code_hash: format!("0x{}", "5e".repeat(32)),
+                args: String::new(),
+                hash_type: ScriptHashType::Type,
+            },
+            search_mode: SearchMode::Prefix,
+            secondary_script: ScriptInput {
+                code_hash: String::new(),
+                args: String::new(),
+                hash_type: ScriptHashType::Type,
+            },
+            capacity_range: Default::default(),
+            data_len_range: Default::default(),
+            block_range: Default::default(),
+        }
+    }
+
+    #[test]
+    fn builds_search_key_with_filters() {
+        let key = serde_json::to_value(form().to_search_key().unwrap()).unwrap();
+        assert_eq!(key["script_type"], json!("type"));
+        assert_eq!(key["script"]["args"], json!("0x"));
+        assert_eq!(key["script_search_mode"], json!("prefix"));
+        assert!(key["filter"].is_null());
+
+        let mut form = form();
+        form.search_mode = SearchMode::Exact;
+        form.secondary_script.code_hash = format!("0x{}", "9b".repeat(32));
+        form.secondary_script.args = String::from("0x1234");
+        form.capacity_range = (String::from("61"), String::new());
+        form.data_len_range = (String::from("16"), String::from("16"));
+        form.block_range = (String::new(), String::from("100"));
+        let key = serde_json::to_value(form.to_search_key().unwrap()).unwrap();
+        let filter = &key["filter"];
+        assert_eq!(key["script_search_mode"], json!("exact"));
+        assert_eq!(filter["script"]["args"], json!("0x1234"));
+        assert_eq!(
+            filter["output_capacity_range"],
+            json!(["0x16b969d00", "0xffffffffffffffff"])
+        );
+        assert_eq!(filter["output_data_len_range"], json!(["0x10", "0x11"]));
+        assert_eq!(filter["block_range"], json!(["0x0", "0x65"]));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let mut bad_hash = form();
+        bad_hash.script.code_hash = String::from("5e5e");
+        assert!(bad_hash.to_search_key().is_err());
+        let mut empty_range = form();
+        empty_range.block_range = (String::from("10"), String::from("9"));
+        assert!(empty_range.to_search_key().is_err());
+
+        assert_eq!(parse_ckb_amount("1.5").unwrap(), 150_000_000);
+        assert_eq!(parse_ckb_amount(".00000001").unwrap(), 1);
+        assert!(parse_ckb_amount("1.000000001").is_err());
+        assert!(parse_ckb_amount("-1").is_err());
+        assert!(parse_ckb_amount("184467440738").is_err());
+    }
+}
@@ -0,0 +1,410 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, anyhow};
+use ckb_fixed_hash_core::H256;
+use ckb_jsonrpc_types::{JsonBytes, TransactionView};
+use ckb_sdk::{
+    CkbRpcClient,
+    rpc::ckb_indexer::{CellType, Order, SearchKey, Tx},
+};
+use cursive::{
+    CbSink, Cursive,
+    view::{IntoBoxedView, Nameable, Resizable},
+    views::{Button, Dialog, LinearLayout, TextView},
+};
+use cursive_aligned_view::Alignable;
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    components::details::{
+        live_cells_searcher::{
+            display_cells_dialog::live_cells_error_message,
+            transaction_history::names::{HISTORY_STATUS, HISTORY_TABLE},
+        },
+        transaction_inspector::{get_transaction_view, transaction_detail_dialog},
+    },
+    declare_names,
+    utils::shorten_hex,
+};
+
+declare_names!(
+    names,
+    "live_cells_searcher_transaction_history_",
+    HISTORY_TABLE,
+    HISTORY_STATUS
+);
+
+/// Transactions fetched per page
+const PAGE_SIZE: u32 = 18;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IoType {
+    Input,
+    Output,
+    Both,
+}
+
+/// A transaction involving cells matched by the search key
+#[derive(Clone, Debug)]
+pub struct HistoryItem {
+    pub tx_hash: H256,
+    pub block_number: u64,
+    pub tx_index: u32,
+    pub io_type: IoType,
+    /// Capacity of the matched outputs minus the matched inputs in shannons, `None` if any of
+    /// the cells couldn't be resolved
+    pub capacity_change: Option<i128>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum HistoryColumn {
+    BlockNumber,
+    TxIndex,
+    TxHash,
+    IoType,
+    CapacityChange,
+}
+
+fn capacity_change_to_string(change: Option<i128>) -> String {
+    match change {
+        Some(change) => format!("{:+} CKB", change as f64 / 1e8),
+        None => String::from("N/A"),
+    }
+}
+
+impl TableViewItem<HistoryColumn> for HistoryItem {
+    fn to_column(&self, column: HistoryColumn) -> String {
+        match column {
+            HistoryColumn::BlockNumber => self.block_number.to_string(),
+            HistoryColumn::TxIndex => self.tx_index.to_string(),
+            HistoryColumn::TxHash => shorten_hex(format!("{:#x}", self.tx_hash), 10, 8),
+            HistoryColumn::IoType => match self.io_type {
+                IoType::Input => "Input",
+                IoType::Output => "Output",
+                IoType::Both => "Input & Output",
+            }
+            .to_string(),
+            HistoryColumn::CapacityChange => capacity_change_to_string(self.capacity_change),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: HistoryColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            HistoryColumn::BlockNumber => self
+                .block_number
+                .cmp(&other.block_number)
+                .then(self.tx_index.cmp(&other.tx_index)),
+            HistoryColumn::TxIndex => self.tx_index.cmp(&other.tx_index),
+            HistoryColumn::TxHash => self.tx_hash.cmp(&other.tx_hash),
+            HistoryColumn::IoType => self.io_type.cmp(&other.io_type),
+            HistoryColumn::CapacityChange => self.capacity_change.cmp(&other.capacity_change),
+        }
+    }
+}
+
+/// Transactions fetched for resolving the matched cells, by hash
+type TransactionCache = HashMap<H256, Option<TransactionView>>;
+
+fn cached_transaction<'a>(
+    client: &CkbRpcClient,
+    cache: &'a mut TransactionCache,
+    tx_hash: &H256,
+) -> anyhow::Result<Option<&'a TransactionView>> {
+    if !cache.contains_key(tx_hash) {
+        let tx = get_transaction_view(client, tx_hash)?.map(|(tx, ..)| tx);
+        cache.insert(tx_hash.clone(), tx);
+    }
+    Ok(cache[tx_hash].as_ref())
+}
+
+/// Sums the capacities of the matched outputs minus the matched inputs of the transaction,
+/// resolving inputs from their previous transactions
+fn capacity_change(
+    client: &CkbRpcClient,
+    cache: &mut TransactionCache,
+    tx_hash: &H256,
+    cells: &[(CellType, u32)],
+) -> anyhow::Result<Option<i128>> {
+    let Some(tx) = cached_transaction(client, cache, tx_hash)?.cloned() else {
+        return Ok(None);
+    };
+    let mut change = 0i128;
+    for (io_type, index) in cells {
+        let index = *index as usize;
+        let capacity = match io_type {
+            CellType::Output => tx.inner.outputs.get(index).map(|x| x.capacity.value()),
+            CellType::Input => {
+                let Some(input) = tx.inner.inputs.get(index) else {
+                    return Ok(None);
+                };
+                let previous_output = &input.previous_output;
+                cached_transaction(client, cache, &previous_output.tx_hash)?.and_then(|x| {
+                    x.inner
+                        .outputs
+                        .get(previous_output.index.value() as usize)
+                        .map(|x| x.capacity.value())
+                })
+            }
+        };
+        let Some(capacity) = capacity else {
+            return Ok(None);
+        };
+        change += match io_type {
+            CellType::Output => capacity as i128,
+            CellType::Input => -(capacity as i128),
+        };
+    }
+    Ok(Some(change))
+}
+
+/// Fetches one page of transactions after `cursor`, returning them with the cursor of the next
+/// page
+pub fn fetch_history_page(
+    client: &CkbRpcClient,
+    search_key: SearchKey,
+    cursor: Option<JsonBytes>,
+) -> anyhow::Result<(Vec<HistoryItem>, JsonBytes)> {
+    let search_key = SearchKey {
+        group_by_transaction: Some(true),
+        ..search_key
+    };
+    let page = client
+        .get_transactions(search_key, Order::Desc, PAGE_SIZE.into(), cursor)
+        .with_context(|| anyhow!("Unable to get transactions"))?;
+    let mut cache = TransactionCache::new();
+    let mut items = vec![];
+    for tx in page.objects {
+        let (tx_hash, block_number, tx_index, cells) = match tx {
+            Tx::Grouped(tx) => (
+                tx.tx_hash,
+                tx.block_number.value(),
+                tx.tx_index.value(),
+                tx.cells
+                    .into_iter()
+                    .map(|(io_type, index)| (io_type, index.value()))
+                    .collect::<Vec<_>>(),
+            ),
+            Tx::Ungrouped(tx) => (
+                tx.tx_hash,
+                tx.block_number.value(),
+                tx.tx_index.value(),
+                vec![(tx.io_type, tx.io_index.value())],
+            ),
+        };
+        let has_input = cells.iter().any(|(x, _)| matches!(x, CellType::Input));
+        let has_output = cells.iter().any(|(x, _)| matches!(x, CellType::Output));
+        let io_type = match (has_input, has_output) {
+            (true, true) => IoType::Both,
+            (true, false) => IoType::Input,
+            _ => IoType::Output,
+        };
+        items.push(HistoryItem {
+            capacity_change: capacity_change(client, &mut cache, &tx_hash, &cells)?,
+            tx_hash,
+            block_number,
+            tx_index,
+            io_type,
+        });
+    }
+    Ok((items, page.last_cursor))
+}
+
+struct HistoryData {
+    client: CkbRpcClient,
+    search_key: SearchKey,
+    /// Fetched pages with the cursor of the next page
+    pages: Vec<(Vec<HistoryItem>, JsonBytes)>,
+    current_page: usize,
+    loading: bool,
+}
+
+fn set_status(cb_sink: &CbSink, status: String) {
+    cb_sink
+        .send(Box::new(move |siv| {
+            siv.call_on_name(HISTORY_STATUS, |view: &mut TextView| {
+                view.set_content(status);
+            });
+        }))
+        .ok();
+}
+
+fn show_page(cb_sink: &CbSink, items: Vec<HistoryItem>, page: usize) {
+    cb_sink
+        .send(Box::new(move |siv| {
+            siv.call_on_name(
+                HISTORY_TABLE,
+                |view: &mut TableView<HistoryItem, HistoryColumn>| {
+                    view.set_items(items);
+                },
+            );
+            siv.call_on_name(HISTORY_STATUS, |view: &mut TextView| {
+                view.set_content(format!("Page {}", page + 1));
+            });
+        }))
+        .ok();
+}
+
+/// Shows the page at `index`, fetching it in a background thread if it's the next one
+fn switch_to_page(data: Arc<Mutex<HistoryData>>, cb_sink: CbSink, index: usize) {
+    let mut guard = data.lock().unwrap();
+    if guard.loading {
+        return;
+    }
+    if let Some(items) = guard.pages.get(index).map(|(items, _)| items.clone()) {
+        guard.current_page = index;
+        show_page(&cb_sink, items, index);
+        return;
+    }
+    if let Some((items, _)) = guard.pages.last()
+        && items.len() < PAGE_SIZE as usize
+    {
+        set_status(&cb_sink, String::from("No more transactions"));
+        return;
+    }
+    guard.loading = true;
+    let client = guard.client.clone();
+    let search_key = guard.search_key.clone();
+    let cursor = guard.pages.last().map(|(_, cursor)| cursor.clone());
+    drop(guard);
+    set_status(&cb_sink, String::from("Loading.."));
+    std::thread::spawn(move || {
+        let result = fetch_history_page(&client, search_key, cursor);
+        let mut guard = data.lock().unwrap();
+        guard.loading = false;
+        match result {
+            Ok(page) => {
+                guard.pages.push(page);
+                guard.current_page = guard.pages.len() - 1;
+                show_page(
+                    &cb_sink,
+                    guard.pages[guard.current_page].0.clone(),
+                    guard.current_page,
+                );
+            }
+            Err(e) => set_status(&cb_sink, live_cells_error_message(&e)),
+        }
+    });
+}
+
+/// Transactions involving the cells matched by `search_key`, newest first
+pub fn transaction_history_dialog(
+    siv: &mut Cursive,
+    client: &CkbRpcClient,
+    search_key: SearchKey,
+) -> impl IntoBoxedView + use<> {
+    let data = Arc::new(Mutex::new(HistoryData {
+        client: client.clone(),
+        search_key,
+        pages: vec![],
+        current_page: 0,
+        loading: false,
+    }));
+    switch_to_page(Arc::clone(&data), siv.cb_sink().clone(), 0);
+    let client = client.clone();
+    let data_prev = Arc::clone(&data);
+    let data_next = Arc::clone(&data);
+    Dialog::new()
+        .title("Transaction History")
+        .content(
+            LinearLayout::vertical()
+                .child(
+                    TableView::<HistoryItem, HistoryColumn>::new()
+                        .column(HistoryColumn::BlockNumber, "Block Number", |c| c.width(15))
+                        .column(HistoryColumn::TxIndex, "Tx Index", |c| c.width(10))
+                        .column(HistoryColumn::TxHash, "Tx Hash", |c| c.width(25))
+                        .column(HistoryColumn::IoType, "IO Type", |c| c.width(16))
+                        .column(HistoryColumn::CapacityChange, "Capacity Change", |c| {
+                            c.width(25)
+                        })
+                        .on_submit(move |siv, _, index| {
+                            let item = siv
+                                .call_on_name(
+                                    HISTORY_TABLE,
+                                    |view: &mut TableView<HistoryItem, HistoryColumn>| {
+                                        view.borrow_item(index).cloned()
+                                    },
+                                )
+                                .flatten();
+                            if let Some(item) = item {
+                                let dialog = transaction_detail_dialog(siv, &client, item.tx_hash);
+                                siv.add_layer(dialog);
+                            }
+                        })
+                        .with_name(HISTORY_TABLE)
+                        .min_width(100)
+                        .min_height(20),
+                )
+                .child(
+                    LinearLayout::horizontal()
+                        .child(Button::new("Prev", move |siv| {
+                            let current_page = data_prev.lock().unwrap().current_page;
+                            if current_page > 0 {
+                                switch_to_page(
+                                    Arc::clone(&data_prev),
+                                    siv.cb_sink().clone(),
+                                    current_page - 1,
+                                );
+                            }
+                        }))
+                        .child(
+                            TextView::new("Loading..")
+                                .center()
+                                .with_name(HISTORY_STATUS)
+                                .min_width(40),
+                        )
+                        .child(Button::new("Next", move |siv| {
+                            let current_page = data_next.lock().unwrap().current_page;
+                            switch_to_page(
+                                Arc::clone(&data_next),
+                                siv.cb_sink().clone(),
+                                current_page + 1,
+                            );
+                        }))
+                        .align_center(),
+                ),
+        )
+        .button("Close", |siv| {
+            siv.pop_layer();
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_sdk::{CkbRpcClient, rpc::ckb_indexer::SearchKey};
+    use serde_json::json;
+
+    use super::{IoType, fetch_history_page};
+    use crate::mock_node::{MockNode, fixtures};
+
+    fn search_key() -> SearchKey {
+        serde_json::from_value(json!({
+            "script": {"code_hash": fixtures::hash(0x9b), "hash_type": "type", "args": "0x"},
+            "script_type": "lock",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn computes_io_type_and_capacity_change() {
+        let node = MockNode::start();
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let (items, _) = fetch_history_page(&client, search_key(), None).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].block_number, fixtures::TIP_NUMBER);
+        assert_eq!(items[0].io_type, IoType::Output);
+        assert_eq!(items[0].capacity_change, Some(1000 * 100_000_000));
+        assert_eq!(items[1].io_type, IoType::Input);
+        assert_eq!(items[1].capacity_change, Some(-1000 * 100_000_000));
+
+        // Unknown transactions can't be resolved
+        node.set_result("get_transaction", json!(null));
+        let (items, _) = fetch_history_page(&client, search_key(), None).unwrap();
+        assert_eq!(items[0].capacity_change, None);
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
+
+use anyhow::{Context, anyhow, bail};
+use ckb_fixed_hash_core::H256;
+use ckb_jsonrpc_types::{RawTxPool, TxPoolEntry};
+use ckb_sdk::CkbRpcClient;
+use cursive::{
+    CbSink, Cursive, View,
+    view::{IntoBoxedView, Nameable, Resizable},
+    views::{Dialog, EditView, LinearLayout, ListView, Panel, TextView},
+};
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    components::details::{
+        mempool_browser::names::{MIN_FEE_RATE, PENDING_TABLE, POOL_STATUS, PROPOSED_TABLE},
+        transaction_inspector::transaction_detail_dialog,
+    },
+    declare_names,
+    utils::shorten_hex,
+};
+
+declare_names!(
+    names,
+    "mempool_browser_",
+    MIN_FEE_RATE,
+    PENDING_TABLE,
+    PROPOSED_TABLE,
+    POOL_STATUS
+);
+
+/// Interval of refreshing the pool while the browser is open
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A transaction in the pool
+#[derive(Clone, Debug)]
+pub struct PoolTxItem {
+    pub tx_hash: H256,
+    pub size: u64,
+    pub cycles: u64,
+    /// In shannons
+    pub fee: u64,
+    /// In shannons per KB
+    pub fee_rate: u64,
+    pub ancestors_count: u64,
+    /// In seconds
+    pub time_in_pool: u64,
+}
+
+impl PoolTxItem {
+    fn new(tx_hash: H256, entry: &TxPoolEntry, now_millis: u64) -> Self {
+        let size = entry.size.value();
+        Self {
+            tx_hash,
+            size,
+            cycles: entry.cycles.value(),
+            fee: entry.fee.value(),
+            fee_rate: entry.fee.value() * 1000 / size.max(1),
+            ancestors_count: entry.ancestors_count.value(),
+            time_in_pool: now_millis.saturating_sub(entry.timestamp.value()) / 1000,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum PoolTxColumn {
+    TxHash,
+    Size,
+    Cycles,
+    Fee,
+    FeeRate,
+    AncestorsCount,
+    TimeInPool,
+}
+
+impl TableViewItem<PoolTxColumn> for PoolTxItem {
+    fn to_column(&self, column: PoolTxColumn) -> String {
+        match column {
+            PoolTxColumn::TxHash => shorten_hex(format!("{:#x}", self.tx_hash), 10, 8),
+            PoolTxColumn::Size => self.size.to_string(),
+            PoolTxColumn::Cycles => self.cycles.to_string(),
+            PoolTxColumn::Fee => self.fee.to_string(),
+            PoolTxColumn::FeeRate => self.fee_rate.to_string(),
+            PoolTxColumn::AncestorsCount => self.ancestors_count.to_string(),
+            PoolTxColumn::TimeInPool => format!("{}s", self.time_in_pool),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: PoolTxColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            PoolTxColumn::TxHash => self.tx_hash.cmp(&other.tx_hash),
+            PoolTxColumn::Size => self.size.cmp(&other.size),
+            PoolTxColumn::Cycles => self.cycles.cmp(&other.cycles),
+            PoolTxColumn::Fee => self.fee.cmp(&other.fee),
+            PoolTxColumn::FeeRate => self.fee_rate.cmp(&other.fee_rate),
+            PoolTxColumn::AncestorsCount => self.ancestors_count.cmp(&other.ancestors_count),
+            PoolTxColumn::TimeInPool => self.time_in_pool.cmp(&other.time_in_pool),
+        }
+    }
+}
+
+/// Pending and proposed transactions of the pool
+#[derive(Clone, Default)]
+pub struct PoolSnapshot {
+    pub pending: Vec<PoolTxItem>,
+    pub proposed: Vec<PoolTxItem>,
+}
+
+pub fn fetch_pool_snapshot(client: &CkbRpcClient) -> anyhow::Result<PoolSnapshot> {
+    let RawTxPool::Verbose(pool) = client
+        .get_raw_tx_pool(Some(true))
+        .with_context(|| anyhow!("Unable to get raw tx pool"))?
+    else {
+        bail!("Node returned transaction ids for a verbose raw tx pool");
+    };
+    let now_millis = chrono::Utc::now().timestamp_millis() as u64;
+    let items = |entries: std::collections::HashMap<H256, TxPoolEntry>| {
+        entries
+            .into_iter()
+            .map(|(tx_hash, entry)| PoolTxItem::new(tx_hash, &entry, now_millis))
+            .collect::<Vec<_>>()
+    };
+    Ok(PoolSnapshot {
+        pending: items(pool.pending),
+        proposed: items(pool.proposed),
+    })
+}
+
+/// Transactions paying at least `min_fee_rate` shannons per KB
+fn filter_by_fee_rate(items: &[PoolTxItem], min_fee_rate: u64) -> Vec<PoolTxItem> {
+    items
+        .iter()
+        .filter(|x| x.fee_rate >= min_fee_rate)
+        .cloned()
+        .collect()
+}
+
+/// Replaces the items of the table, keeping the selected transaction selected if it's still
+/// listed
+fn set_items_keeping_selection(
+    view: &mut TableView<PoolTxItem, PoolTxColumn>,
+    items: Vec<PoolTxItem>,
+) {
+    let selected = view
+        .item()
+        .and_then(|index| view.borrow_item(index))
+        .map(|x| x.tx_hash.clone());
+    let selected_index =
+        selected.and_then(|tx_hash| items.iter().position(|x| x.tx_hash == tx_hash));
+    view.set_items(items);
+    if let Some(index) = selected_index {
+        view.set_selected_item(index);
+    }
+}
+
+/// Shows the snapshot in the tables, filtered by the minimum fee rate of the form
+fn update_tables(siv: &mut Cursive, snapshot: &PoolSnapshot) {
+    let min_fee_rate = siv
+        .call_on_name(MIN_FEE_RATE, |view: &mut EditView| {
+            view.get_content().trim().parse::<u64>().unwrap_or(0)
+        })
+        .unwrap_or(0);
+    let pending = filter_by_fee_rate(&snapshot.pending, min_fee_rate);
+    let proposed = filter_by_fee_rate(&snapshot.proposed, min_fee_rate);
+    let status = format!(
+        "Showing {}/{} pending and {}/{} proposed transactions",
+        pending.len(),
+        snapshot.pending.len(),
+        proposed.len(),
+        snapshot.proposed.len()
+    );
+    siv.call_on_name(
+        PENDING_TABLE,
+        |view: &mut TableView<PoolTxItem, PoolTxColumn>| set_items_keeping_selection(view, pending),
+    );
+    siv.call_on_name(
+        PROPOSED_TABLE,
+        |view: &mut TableView<PoolTxItem, PoolTxColumn>| {
+            set_items_keeping_selection(view, proposed)
+        },
+    );
+    siv.call_on_name(POOL_STATUS, |view: &mut TextView| view.set_content(status));
+}
+
+/// Shows a fetched pool, returns false if the browser was closed meanwhile
+fn show_snapshot(
+    siv: &mut Cursive,
+    snapshot: &Mutex<PoolSnapshot>,
+    result: anyhow::Result<PoolSnapshot>,
+) -> bool {
+    if siv
+        .call_on_name(POOL_STATUS, |_: &mut TextView| ())
+        .is_none()
+    {
+        return false;
+    }
+    match result {
+        Ok(fetched) => {
+            update_tables(siv, &fetched);
+            *snapshot.lock().unwrap() = fetched;
+        }
+        Err(e) => {
+            siv.call_on_name(POOL_STATUS, |view: &mut TextView| {
+                view.set_content(format!("{:#}", e))
+            });
+        }
+    }
+    true
+}
+
+/// Fetches the pool every `REFRESH_INTERVAL` or when requested, one fetch at a time, until the
+/// browser is closed
+fn refresh_loop(
+    client: CkbRpcClient,
+    snapshot: Arc<Mutex<PoolSnapshot>>,
+    cb_sink: CbSink,
+    refresh_requests: mpsc::Receiver<()>,
+) {
+    loop {
+        let result = fetch_pool_snapshot(&client);
+        let (open_tx, open_rx) = mpsc::channel();
+        let snapshot = Arc::clone(&snapshot);
+        let sent = cb_sink.send(Box::new(move |siv| {
+            open_tx.send(show_snapshot(siv, &snapshot, result)).ok();
+        }));
+        if sent.is_err() || open_rx.recv() != Ok(true) {
+            break;
+        }
+        match refresh_requests.recv_timeout(REFRESH_INTERVAL) {
+            Ok(()) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+fn pool_table(name: &'static str, client: CkbRpcClient) -> impl View + use<> {
+    TableView::<PoolTxItem, PoolTxColumn>::new()
+        .column(PoolTxColumn::TxHash, "Tx Hash", |c| c.width(22))
+        .column(PoolTxColumn::Size, "Size (Bytes)", |c| c.width(14))
+        .column(PoolTxColumn::Cycles, "Cycles", |c| c.width(14))
+        .column(PoolTxColumn::Fee, "Fee (shannons)", |c| c.width(16))
+        .column(PoolTxColumn::FeeRate, "Fee Rate (shannons/kB)", |c| {
+            c.width(24)
+        })
+        .column(PoolTxColumn::AncestorsCount, "Ancestors", |c| c.width(11))
+        .column(PoolTxColumn::TimeInPool, "In Pool", |c| c.width(10))
+        .default_column(PoolTxColumn::FeeRate)
+        .on_submit(move |siv, _row, index| {
+            let item = siv
+                .call_on_name(name, |view: &mut TableView<PoolTxItem, PoolTxColumn>| {
+                    view.borrow_item(index).cloned()
+                })
+                .flatten();
+            if let Some(item) = item {
+                let dialog = transaction_detail_dialog(siv, &client, item.tx_hash);
+                siv.add_layer(dialog);
+            }
+        })
+        .with_name(name)
+        .min_size((115, 10))
+}
+
+/// Pending and proposed transactions of the pool, refreshed while the browser is open
+pub fn mempool_browser(siv: &mut Cursive, client: &CkbRpcClient) -> impl IntoBoxedView + use<> {
+    let snapshot = Arc::new(Mutex::new(PoolSnapshot::default()));
+    let (refresh_tx, refresh_rx) = mpsc::channel();
+    {
+        let client = client.clone();
+        let snapshot = Arc::clone(&snapshot);
+        let cb_sink = siv.cb_sink().clone();
+        std::thread::spawn(move || refresh_loop(client, snapshot, cb_sink, refresh_rx));
+    }
+    Dialog::new()
+        .title("Mempool Browser")
+        .content(
+            LinearLayout::vertical()
+                .child(
+                    ListView::new().child(
+                        "Min Fee Rate (shannons/kB):",
+                        EditView::new()
+                            .on_edit(move |siv, _, _| {
+                                let snapshot = snapshot.lock().unwrap().clone();
+                                update_tables(siv, &snapshot);
+                            })
+                            .with_name(MIN_FEE_RATE)
+                            .fixed_width(20),
+                    ),
+                )
+                .child(TextView::new("Loading..").with_name(POOL_STATUS))
+                .child(Panel::new(pool_table(PENDING_TABLE, client.clone())).title("Pending"))
+                .child(Panel::new(pool_table(PROPOSED_TABLE, client.clone())).title("Proposed")),
+        )
+        .button("Refresh", move |_| {
+            refresh_tx.send(()).ok();
+        })
+        .button("Close", |siv| {
+            siv.pop_layer();
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_sdk::CkbRpcClient;
+    use serde_json::json;
+
+    use super::{fetch_pool_snapshot, filter_by_fee_rate};
+    use crate::mock_node::{MockNode, fixtures};
+
+    #[test]
+    fn splits_pool_and_filters_by_fee_rate() {
+        let node = MockNode::start();
+        let entry = |fee: u64, ancestors: u64| {
+            json!({
+                "cycles": "0x100000",
+                "size": "0x200",
+                "fee": format!("{:#x}", fee),
+                "ancestors_size": "0x200",
+                "ancestors_cycles": "0x100000",
+                "ancestors_count": format!("{:#x}", ancestors),
+                "timestamp": "0x0",
+            })
+        };
+        node.set_result(
+            "get_raw_tx_pool",
+            json!({
+                "pending": {
+                    (fixtures::hash(1)): entry(512, 1),
+                    (fixtures::hash(2)): entry(2048, 3),
+                },
+                "proposed": {(fixtures::hash(3)): entry(1024, 1)},
+                "conflicted": [],
+            }),
+        );
+        let client = CkbRpcClient::new(&node.rpc_url());
+        let snapshot = fetch_pool_snapshot(&client).unwrap();
+        assert_eq!(snapshot.pending.len(), 2);
+        assert_eq!(snapshot.proposed.len(), 1);
+        // Fee rates are per 1000 bytes of the 512 bytes transactions
+        assert_eq!(snapshot.proposed[0].fee_rate, 2000);
+        assert!(snapshot.proposed[0].time_in_pool > 0);
+
+        let filtered = filter_by_fee_rate(&snapshot.pending, 2000);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].fee_rate, 4000);
+        assert_eq!(filtered[0].ancestors_count, 3);
+    }
+}
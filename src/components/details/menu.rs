@@ -4,18 +4,69 @@ use cursive::{
     views::{Button, Dialog, LinearLayout},
 };
 
-use crate::components::details::live_cells_searcher::live_cells_searcher;
+use crate::components::details::{
+    block_explorer::block_explorer, dao_tracker::dao_tracker,
+    live_cells_searcher::live_cells_searcher, mempool_browser::mempool_browser,
+    transaction_inspector::transaction_inspector,
+};
 
 pub fn details_menu(client: &CkbRpcClient) -> impl IntoBoxedView {
     let client_cloned = client.clone();
+    let client_cloned_2 = client.clone();
+    let client_cloned_3 = client.clone();
+    let client_cloned_4 = client.clone();
+    let client_cloned_5 = client.clone();
     Dialog::new()
         .content(
-            LinearLayout::vertical().child(Button::new("Live Cells Searcher", move |siv| {
-                siv.add_layer(live_cells_searcher(&client_cloned));
-            })),
+            LinearLayout::vertical()
+                .child(Button::new("Live Cells Searcher", move |siv| {
+                    siv.add_layer(live_cells_searcher(&client_cloned));
+                }))
+                .child(Button::new("Block Explorer", move |siv| {
+                    siv.add_layer(block_explorer(&client_cloned_2));
+                }))
+                .child(Button::new("Transaction Inspector", move |siv| {
+                    siv.add_layer(transaction_inspector(&client_cloned_3));
+                }))
+                .child(Button::new("Nervos DAO Tracker", move |siv| {
+                    siv.add_layer(dao_tracker(&client_cloned_4));
+                }))
+                .child(Button::new("Mempool Browser", move |siv| {
+                    let dialog = mempool_browser(siv, &client_cloned_5);
+                    siv.add_layer(dialog);
+                })),
         )
         .title("Menu")
         .button("Close", |siv| {
             siv.pop_layer();
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use ckb_sdk::CkbRpcClient;
+    use cursive::event::Key;
+
+    use super::details_menu;
+    use crate::utils::puppet::PuppetScreen;
+
+    #[test]
+    fn opens_tools_with_keyboard() {
+        let client = CkbRpcClient::new("http://127.0.0.1:8114");
+        let mut screen = PuppetScreen::new(120, 40);
+        screen
+            .siv()
+            .add_global_callback('m', move |siv| siv.add_layer(details_menu(&client)));
+        screen.press('m');
+        assert!(screen.contains("Live Cells Searcher"));
+
+        // Nothing is requested from the node before a query is submitted
+        screen.press(Key::Tab);
+        screen.press(Key::Enter);
+        assert!(screen.contains("Block Number or Hash:"));
+        screen.type_text("tip");
+        screen.press(Key::Tab);
+        screen.press(Key::Enter);
+        assert!(screen.contains("Error"));
+    }
+}
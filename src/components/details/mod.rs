@@ -1,2 +1,6 @@
+pub mod block_explorer;
+pub mod dao_tracker;
 pub mod live_cells_searcher;
+pub mod mempool_browser;
 pub mod menu;
+pub mod transaction_inspector;
@@ -0,0 +1,663 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{Context, anyhow, bail};
+use chrono::{Local, TimeZone, Utc};
+use ckb_fixed_hash_core::H256;
+use ckb_gen_types::prelude::Entity;
+use ckb_jsonrpc_types::{
+    CellOutput, DepType, Either, OutPoint, Script, Status, TransactionView,
+    TransactionWithStatusResponse, TxStatus,
+};
+use ckb_sdk::CkbRpcClient;
+use cursive::{
+    Cursive,
+    view::{IntoBoxedView, Nameable, Resizable, Scrollable},
+    views::{Dialog, EditView, LinearLayout, ListView, Panel, ScrollView, TextView},
+};
+use cursive_async_view::AsyncView;
+use cursive_table_view::{TableView, TableViewItem};
+
+use crate::{
+    components::details::transaction_inspector::names::TX_HASH_INPUT, declare_names,
+    utils::shorten_hex,
+};
+
+declare_names!(names, "transaction_inspector_", TX_HASH_INPUT);
+
+/// Witnesses longer than this (in hex chars) are shortened when displayed
+const MAX_WITNESS_DISPLAY_LEN: usize = 256;
+
+struct ResolvedInput {
+    since: u64,
+    previous_output: OutPoint,
+    // `None` for the cellbase input, or if the previous transaction is unknown to the node
+    output: Option<CellOutput>,
+}
+
+struct TransactionDetail {
+    tx: TransactionView,
+    tx_status: TxStatus,
+    cycles: Option<u64>,
+    // In milliseconds
+    time_added_to_pool: Option<u64>,
+    inputs: Vec<ResolvedInput>,
+    // Serialized size in bytes, as used by the tx-pool for fee rate
+    size: u64,
+}
+
+impl TransactionDetail {
+    fn is_cellbase(&self) -> bool {
+        self.tx.inner.inputs.len() == 1
+            && self.tx.inner.inputs[0].previous_output.tx_hash == H256::default()
+    }
+
+    /// Fee in shannons, `None` if any of the inputs couldn't be resolved
+    fn fee(&self) -> Option<u64> {
+        if self.is_cellbase() {
+            return None;
+        }
+        let input_capacity = self
+            .inputs
+            .iter()
+            .map(|x| x.output.as_ref().map(|x| x.capacity.value()))
+            .sum::<Option<u64>>()?;
+        let output_capacity = self
+            .tx
+            .inner
+            .outputs
+            .iter()
+            .map(|x| x.capacity.value())
+            .sum::<u64>();
+        input_capacity.checked_sub(output_capacity)
+    }
+
+    /// Fee rate in shannons per KB
+    fn fee_rate(&self) -> Option<u64> {
+        self.fee().map(|fee| fee * 1000 / self.size.max(1))
+    }
+}
+
+/// The transaction in JSON format, `None` if the node doesn't know it
+pub(crate) fn get_transaction_view(
+    client: &CkbRpcClient,
+    tx_hash: &H256,
+) -> anyhow::Result<Option<(TransactionView, TransactionWithStatusResponse)>> {
+    let Some(mut response) = client
+        .get_transaction(tx_hash.clone())
+        .with_context(|| anyhow!("Unable to get transaction {:#x}", tx_hash))?
+    else {
+        return Ok(None);
+    };
+    let tx = match response.transaction.take().map(|x| x.inner) {
+        Some(Either::Left(tx)) => tx,
+        Some(Either::Right(_)) => bail!("Unexpected hex format of transaction {:#x}", tx_hash),
+        None => return Ok(None),
+    };
+    Ok(Some((tx, response)))
+}
+
+fn fetch_transaction_detail(
+    client: &CkbRpcClient,
+    tx_hash: &H256,
+) -> anyhow::Result<TransactionDetail> {
+    let (tx, response) = get_transaction_view(client, tx_hash)?
+        .ok_or_else(|| anyhow!("Transaction {:#x} not found", tx_hash))?;
+    let mut previous_txs = HashMap::<H256, Option<TransactionView>>::new();
+    let mut inputs = vec![];
+    for input in tx.inner.inputs.iter() {
+        let previous_tx_hash = &input.previous_output.tx_hash;
+        let output = if *previous_tx_hash == H256::default() {
+            None
+        } else {
+            if !previous_txs.contains_key(previous_tx_hash) {
+                let previous_tx =
+                    get_transaction_view(client, previous_tx_hash)?.map(|(tx, ..)| tx);
+                previous_txs.insert(previous_tx_hash.clone(), previous_tx);
+            }
+            previous_txs[previous_tx_hash].as_ref().and_then(|x| {
+                x.inner
+                    .outputs
+                    .get(input.previous_output.index.value() as usize)
+                    .cloned()
+            })
+        };
+        inputs.push(ResolvedInput {
+            since: input.since.value(),
+            previous_output: input.previous_output.clone(),
+            output,
+        });
+    }
+    // Same as the tx-pool: molecule serialized size plus 4 bytes of offset in the block
+    let size = ckb_gen_types::packed::Transaction::from(tx.inner.clone())
+        .as_slice()
+        .len() as u64
+        + 4;
+    Ok(TransactionDetail {
+        tx,
+        tx_status: response.tx_status,
+        cycles: response.cycles.map(|x| x.value()),
+        time_added_to_pool: response.time_added_to_pool.map(|x| x.value()),
+        inputs,
+        size,
+    })
+}
+
+fn script_to_string(script: &Script) -> String {
+    format!(
+        "{} ({:?}) {}",
+        shorten_hex(script.code_hash.to_string(), 6, 4),
+        script.hash_type,
+        shorten_hex(byteutils::bytes_to_hex(script.args.as_bytes()), 6, 4)
+    )
+}
+
+fn tx_status_to_string(status: &TxStatus) -> String {
+    match status.status {
+        Status::Pending => String::from("Pending"),
+        Status::Proposed => String::from("Proposed"),
+        Status::Committed => format!(
+            "Committed in block {} ({})",
+            status
+                .block_number
+                .map(|x| x.value().to_string())
+                .unwrap_or("?".to_string()),
+            status
+                .block_hash
+                .as_ref()
+                .map(|x| format!("{:#x}", x))
+                .unwrap_or("?".to_string())
+        ),
+        Status::Rejected => format!(
+            "Rejected: {}",
+            status.reason.as_deref().unwrap_or("<unknown reason>")
+        ),
+        Status::Unknown => String::from("Unknown"),
+    }
+}
+
+#[derive(Clone)]
+struct InputItem {
+    index: usize,
+    since: u64,
+    previous_output: OutPoint,
+    output: Option<CellOutput>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum InputColumn {
+    Index,
+    PreviousOutput,
+    Capacity,
+    Lock,
+    Since,
+}
+
+impl TableViewItem<InputColumn> for InputItem {
+    fn to_column(&self, column: InputColumn) -> String {
+        match column {
+            InputColumn::Index => self.index.to_string(),
+            InputColumn::PreviousOutput => {
+                if self.previous_output.tx_hash == H256::default() {
+                    String::from("(cellbase)")
+                } else {
+                    format!(
+                        "{}:{}",
+                        shorten_hex(self.previous_output.tx_hash.to_string(), 6, 6),
+                        self.previous_output.index.value()
+                    )
+                }
+            }
+            InputColumn::Capacity => self
+                .output
+                .as_ref()
+                .map(|x| (x.capacity.value() as f64 / 1e8).to_string())
+                .unwrap_or("N/A".to_string()),
+            InputColumn::Lock => self
+                .output
+                .as_ref()
+                .map(|x| script_to_string(&x.lock))
+                .unwrap_or("N/A".to_string()),
+            InputColumn::Since => format!("{:#x}", self.since),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: InputColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            InputColumn::Index => self.index.cmp(&other.index),
+            InputColumn::PreviousOutput => self
+                .previous_output
+                .tx_hash
+                .cmp(&other.previous_output.tx_hash)
+                .then(
+                    self.previous_output
+                        .index
+                        .value()
+                        .cmp(&other.previous_output.index.value()),
+                ),
+            InputColumn::Capacity => self
+                .output
+                .as_ref()
+                .map(|x| x.capacity.value())
+                .cmp(&other.output.as_ref().map(|x| x.capacity.value())),
+            InputColumn::Lock => self
+                .output
+                .as_ref()
+                .map(|x| x.lock.code_hash.clone())
+                .cmp(&other.output.as_ref().map(|x| x.lock.code_hash.clone())),
+            InputColumn::Since => self.since.cmp(&other.since),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct OutputItem {
+    index: usize,
+    output: CellOutput,
+    data_len: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum OutputColumn {
+    Index,
+    Capacity,
+    Lock,
+    Type,
+    DataSize,
+}
+
+impl TableViewItem<OutputColumn> for OutputItem {
+    fn to_column(&self, column: OutputColumn) -> String {
+        match column {
+            OutputColumn::Index => self.index.to_string(),
+            OutputColumn::Capacity => (self.output.capacity.value() as f64 / 1e8).to_string(),
+            OutputColumn::Lock => script_to_string(&self.output.lock),
+            OutputColumn::Type => self
+                .output
+                .type_
+                .as_ref()
+                .map(script_to_string)
+                .unwrap_or("-".to_string()),
+            OutputColumn::DataSize => self.data_len.to_string(),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: OutputColumn) -> std::cmp::Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            OutputColumn::Index => self.index.cmp(&other.index),
+            OutputColumn::Capacity => self
+                .output
+                .capacity
+                .value()
+                .cmp(&other.output.capacity.value()),
+            OutputColumn::Lock => self.output.lock.code_hash.cmp(&other.output.lock.code_hash),
+            OutputColumn::Type => self
+                .output
+                .type_
+                .as_ref()
+                .map(|x| x.code_hash.clone())
+                .cmp(&other.output.type_.as_ref().map(|x| x.code_hash.clone())),
+            OutputColumn::DataSize => self.data_len.cmp(&other.data_len),
+        }
+    }
+}
+
+fn transaction_detail_view(
+    detail: TransactionDetail,
+    client: CkbRpcClient,
+) -> ScrollView<LinearLayout> {
+    let summary_view = ListView::new()
+        .child("Tx Hash:", TextView::new(format!("{:#x}", detail.tx.hash)))
+        .child(
+            "Status:",
+            TextView::new(tx_status_to_string(&detail.tx_status)),
+        )
+        .child(
+            "Cycles:",
+            TextView::new(
+                detail
+                    .cycles
+                    .map(|x| x.to_string())
+                    .unwrap_or("N/A".to_string()),
+            ),
+        )
+        .child("Size:", TextView::new(format!("{} bytes", detail.size)))
+        .child(
+            "Fee:",
+            TextView::new(match detail.fee() {
+                Some(fee) => format!("{} shannons", fee),
+                None if detail.is_cellbase() => String::from("N/A (cellbase)"),
+                None => String::from("N/A (unresolved inputs)"),
+            }),
+        )
+        .child(
+            "Fee Rate:",
+            TextView::new(
+                detail
+                    .fee_rate()
+                    .map(|x| format!("{} shannons/KB", x))
+                    .unwrap_or("N/A".to_string()),
+            ),
+        )
+        .child(
+            "Added To Pool:",
+            TextView::new(
+                detail
+                    .time_added_to_pool
+                    .map(|x| {
+                        Utc.timestamp_millis_opt(x as i64)
+                            .unwrap()
+                            .with_timezone(&Local)
+                            .to_rfc2822()
+                    })
+                    .unwrap_or("N/A".to_string()),
+            ),
+        );
+    let input_items = detail
+        .inputs
+        .into_iter()
+        .enumerate()
+        .map(|(index, input)| InputItem {
+            index,
+            since: input.since,
+            previous_output: input.previous_output,
+            output: input.output,
+        })
+        .collect::<Vec<_>>();
+    let input_count = input_items.len();
+    let input_items_cloned = input_items.clone();
+    let output_items = detail
+        .tx
+        .inner
+        .outputs
+        .iter()
+        .enumerate()
+        .map(|(index, output)| OutputItem {
+            index,
+            output: output.clone(),
+            data_len: detail
+                .tx
+                .inner
+                .outputs_data
+                .get(index)
+                .map(|x| x.len())
+                .unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    let cell_deps = if detail.tx.inner.cell_deps.is_empty() {
+        String::from("None")
+    } else {
+        detail
+            .tx
+            .inner
+            .cell_deps
+            .iter()
+            .map(|x| {
+                format!(
+                    "{:#x}:{} ({})",
+                    x.out_point.tx_hash,
+                    x.out_point.index.value(),
+                    match x.dep_type {
+                        DepType::Code => "code",
+                        DepType::DepGroup => "dep_group",
+                    }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let header_deps = if detail.tx.inner.header_deps.is_empty() {
+        String::from("None")
+    } else {
+        detail
+            .tx
+            .inner
+            .header_deps
+            .iter()
+            .map(|x| format!("{:#x}", x))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let witnesses = if detail.tx.inner.witnesses.is_empty() {
+        String::from("None")
+    } else {
+        detail
+            .tx
+            .inner
+            .witnesses
+            .iter()
+            .enumerate()
+            .map(|(index, witness)| {
+                let hex = byteutils::bytes_to_hex(witness.as_bytes());
+                format!(
+                    "#{} ({} bytes) {}",
+                    index,
+                    witness.len(),
+                    if hex.len() > MAX_WITNESS_DISPLAY_LEN {
+                        shorten_hex(
+                            hex,
+                            MAX_WITNESS_DISPLAY_LEN / 2,
+                            MAX_WITNESS_DISPLAY_LEN / 2,
+                        )
+                    } else {
+                        format!("0x{}", hex)
+                    }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    LinearLayout::vertical()
+        .child(Panel::new(summary_view).title("Summary"))
+        .child(
+            Panel::new(
+                TableView::<InputItem, InputColumn>::new()
+                    .column(InputColumn::Index, "Index", |c| c.width(6))
+                    .column(InputColumn::PreviousOutput, "Previous Output", |c| {
+                        c.width(22)
+                    })
+                    .column(InputColumn::Capacity, "Capacity (CKB)", |c| c.width(16))
+                    .column(InputColumn::Lock, "Lock Script", |c| c.width(40))
+                    .column(InputColumn::Since, "Since", |c| c)
+                    .items(input_items)
+                    .default_column(InputColumn::Index)
+                    .on_submit(move |siv, _row, index| {
+                        let previous_tx_hash =
+                            input_items_cloned[index].previous_output.tx_hash.clone();
+                        if previous_tx_hash == H256::default() {
+                            return;
+                        }
+                        let dialog = transaction_detail_dialog(siv, &client, previous_tx_hash);
+                        siv.add_layer(dialog);
+                    })
+                    .min_size((100, 6)),
+            )
+            .title(format!(
+                "Inputs ({}), press Enter to open the previous transaction",
+                input_count
+            )),
+        )
+        .child(
+            Panel::new(
+                TableView::<OutputItem, OutputColumn>::new()
+                    .column(OutputColumn::Index, "Index", |c| c.width(6))
+                    .column(OutputColumn::Capacity, "Capacity (CKB)", |c| c.width(16))
+                    .column(OutputColumn::Lock, "Lock Script", |c| c.width(32))
+                    .column(OutputColumn::Type, "Type Script", |c| c.width(32))
+                    .column(OutputColumn::DataSize, "Data (Bytes)", |c| c)
+                    .items(output_items)
+                    .default_column(OutputColumn::Index)
+                    .min_size((100, 6)),
+            )
+            .title(format!("Outputs ({})", detail.tx.inner.outputs.len())),
+        )
+        .child(Panel::new(TextView::new(cell_deps)).title("Cell Deps"))
+        .child(Panel::new(TextView::new(header_deps)).title("Header Deps"))
+        .child(Panel::new(TextView::new(witnesses)).title("Witnesses"))
+        .scrollable()
+}
+
+pub fn transaction_detail_dialog(
+    siv: &mut Cursive,
+    client: &CkbRpcClient,
+    tx_hash: H256,
+) -> impl IntoBoxedView + use<> {
+    let client_cloned = client.clone();
+    let client_for_view = client.clone();
+    Dialog::around(
+        AsyncView::new_with_bg_creator(
+            siv,
+            move || {
+                fetch_transaction_detail(&client_cloned, &tx_hash).map_err(|e| format!("{:#}", e))
+            },
+            move |detail| transaction_detail_view(detail, client_for_view.clone()),
+        )
+        .with_width(110),
+    )
+    .title("Transaction Inspector")
+    .button("Close", |siv| {
+        siv.pop_layer();
+    })
+}
+
+pub fn transaction_inspector(client: &CkbRpcClient) -> impl IntoBoxedView + use<> {
+    let client = client.clone();
+    Dialog::new()
+        .title("Transaction Inspector")
+        .content(ListView::new().child(
+            "Transaction Hash:",
+            EditView::new().with_name(TX_HASH_INPUT).min_width(70),
+        ))
+        .button("Open", move |siv| {
+            let tx_hash = siv
+                .call_on_name(TX_HASH_INPUT, |view: &mut EditView| {
+                    view.get_content().to_string()
+                })
+                .unwrap();
+            let result = tx_hash
+                .trim()
+                .strip_prefix("0x")
+                .ok_or_else(|| anyhow!("Transaction hash should start with 0x"))
+                .and_then(|x| {
+                    H256::from_str(x).with_context(|| anyhow!("Bad transaction hash: {}", tx_hash))
+                });
+            match result {
+                Ok(tx_hash) => {
+                    let dialog = transaction_detail_dialog(siv, &client, tx_hash);
+                    siv.add_layer(dialog);
+                }
+                Err(e) => {
+                    siv.add_layer(
+                        Dialog::around(TextView::new(format!("{:?}", e)))
+                            .title("Error")
+                            .button("Close", |siv| {
+                                siv.pop_layer();
+                            }),
+                    );
+                }
+            }
+        })
+        .button("Close", |siv| {
+            siv.pop_layer();
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_fixed_hash_core::H256;
+    use ckb_sdk::CkbRpcClient;
+    use serde_json::json;
+
+    use super::fetch_transaction_detail;
+    use crate::mock_node::{MockNode, fixtures};
+
+    const CKB: u64 = 100_000_000;
+
+    fn tx_hash() -> H256 {
+        H256::from_slice(&[1; 32]).unwrap()
+    }
+
+    /// Serves `inputs` as the inputs of the transaction. The node returns the same transaction
+    /// for every hash, so inputs resolve to its own outputs of 1000 and 999.99 CKB
+    fn serve_transaction(node: &MockNode, inputs: serde_json::Value) {
+        let mut response = fixtures::transaction_with_status(1);
+        response["transaction"]["inputs"] = inputs;
+        let mut change = response["transaction"]["outputs"][0].clone();
+        change["capacity"] = json!(format!("{:#x}", 1000 * CKB - CKB / 100));
+        response["transaction"]["outputs"]
+            .as_array_mut()
+            .unwrap()
+            .push(change);
+        response["transaction"]["outputs_data"] = json!(["0x", "0x"]);
+        node.set_result("get_transaction", response);
+    }
+
+    fn input(tx_hash: String, index: u32) -> serde_json::Value {
+        json!({
+            "since": "0x0",
+            "previous_output": {"tx_hash": tx_hash, "index": format!("{:#x}", index)},
+        })
+    }
+
+    #[test]
+    fn computes_fee_of_resolved_inputs() {
+        let node = MockNode::start();
+        serve_transaction(
+            &node,
+            json!([input(fixtures::hash(2), 0), input(fixtures::hash(2), 0)]),
+        );
+        let detail =
+            fetch_transaction_detail(&CkbRpcClient::new(&node.rpc_url()), &tx_hash()).unwrap();
+        // The previous transaction is fetched once for both inputs
+        assert_eq!(node.calls("get_transaction"), 2);
+        assert!(!detail.is_cellbase());
+        assert!(detail.inputs.iter().all(|x| x.output.is_some()));
+        assert_eq!(detail.cycles, Some(0x100000));
+        // 2000 CKB in, 1999.99 CKB out
+        assert_eq!(detail.fee(), Some(CKB / 100));
+        assert!(detail.size > 0);
+        assert_eq!(detail.fee_rate(), Some(CKB / 100 * 1000 / detail.size));
+    }
+
+    #[test]
+    fn has_no_fee_for_cellbase() {
+        let node = MockNode::start();
+        serve_transaction(&node, json!([input(fixtures::hash(0), u32::MAX)]));
+        let detail =
+            fetch_transaction_detail(&CkbRpcClient::new(&node.rpc_url()), &tx_hash()).unwrap();
+        // The cellbase input isn't resolved
+        assert_eq!(node.calls("get_transaction"), 1);
+        assert!(detail.is_cellbase());
+        assert!(detail.inputs[0].output.is_none());
+        assert_eq!(detail.fee(), None);
+        assert_eq!(detail.fee_rate(), None);
+    }
+
+    #[test]
+    fn has_no_fee_with_unresolvable_inputs() {
+        let node = MockNode::start();
+        // The previous transaction has no output 5
+        serve_transaction(
+            &node,
+            json!([input(fixtures::hash(2), 0), input(fixtures::hash(2), 5)]),
+        );
+        let detail =
+            fetch_transaction_detail(&CkbRpcClient::new(&node.rpc_url()), &tx_hash()).unwrap();
+        assert!(!detail.is_cellbase());
+        assert!(detail.inputs[0].output.is_some());
+        assert!(detail.inputs[1].output.is_none());
+        assert_eq!(detail.fee(), None);
+        assert_eq!(detail.fee_rate(), None);
+
+        node.set_result("get_transaction", serde_json::Value::Null);
+        let error = fetch_transaction_detail(&CkbRpcClient::new(&node.rpc_url()), &tx_hash())
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("not found"));
+    }
+}
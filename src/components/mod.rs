@@ -2,10 +2,10 @@ use anyhow::Context;
 use anyhow::anyhow;
 use ckb_jsonrpc_types::HeaderView;
 use ckb_jsonrpc_types::PoolTransactionReject;
-use ckb_sdk::CkbRpcClient;
 use cursive::Cursive;
 
 use crate::components::dashboard::TUIEvent;
+use crate::utils::rpc_cache::CachedRpcClient;
 
 pub mod dashboard;
 pub mod details;
@@ -16,12 +16,14 @@ pub trait UpdateToView {
 pub trait DashboardData: UpdateToView {
     fn fetch_data_through_client(
         &mut self,
-        client: &CkbRpcClient,
+        client: &CachedRpcClient,
     ) -> anyhow::Result<Box<dyn DashboardData + Send + Sync>>;
     fn should_update(&self) -> bool {
         true
     }
     fn set_enable_overview_data(&mut self, flag: bool);
+    /// Name of the panel showing this data, used to mark it as stale when fetching fails
+    fn panel_name(&self) -> &'static str;
 }
 
 pub trait DashboardState: Sized + Clone + UpdateToView {
@@ -29,6 +31,11 @@ pub trait DashboardState: Sized + Clone + UpdateToView {
     fn accept_event(&mut self, _event: &TUIEvent) {}
 }
 
+/// Whether the node provides the `get_overview` RPC (Terminal module)
+pub fn is_overview_supported(client: &CachedRpcClient) -> bool {
+    client.get_overview().is_ok()
+}
+
 pub fn extract_epoch(epoch_field: u64) -> (u64, u64, u64) {
     let epoch = epoch_field & 0xffffff;
     let epoch_block = (epoch_field >> 24) & 0xffff;
@@ -38,7 +45,7 @@ pub fn extract_epoch(epoch_field: u64) -> (u64, u64, u64) {
 
 fn get_average_block_time_and_estimated_epoch_time(
     tip_header: &HeaderView,
-    client: &CkbRpcClient,
+    client: &CachedRpcClient,
 ) -> anyhow::Result<(f64, f64)> {
     let (_, epoch_block, epoch_block_count) = extract_epoch(tip_header.inner.epoch.value());
 
@@ -73,3 +80,21 @@ fn map_pool_transaction_to_reason(rej: &PoolTransactionReject) -> &'static str {
         PoolTransactionReject::Invalidated(_) => "Invalidated rejected",
     }
 }
+
+/// The detailed message of the rejection, e.g. the verification error or the fee numbers
+fn pool_transaction_reject_description(rej: &PoolTransactionReject) -> &str {
+    match rej {
+        PoolTransactionReject::LowFeeRate(description)
+        | PoolTransactionReject::ExceededMaximumAncestorsCount(description)
+        | PoolTransactionReject::ExceededTransactionSizeLimit(description)
+        | PoolTransactionReject::Full(description)
+        | PoolTransactionReject::Duplicated(description)
+        | PoolTransactionReject::Malformed(description)
+        | PoolTransactionReject::DeclaredWrongCycles(description)
+        | PoolTransactionReject::Resolve(description)
+        | PoolTransactionReject::Verification(description)
+        | PoolTransactionReject::Expiry(description)
+        | PoolTransactionReject::RBFRejected(description)
+        | PoolTransactionReject::Invalidated(description) => description,
+    }
+}
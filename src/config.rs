@@ -0,0 +1,301 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, anyhow, bail};
+use serde::Deserialize;
+
+use crate::{NodeProfile, alerts::AlertRule};
+
+pub const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8114";
+pub const DEFAULT_REFRESH_INTERVAL: usize = 300;
+
+/// Settings of ckb-tui, read from the config file and overridden by command line flags
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(rename = "node")]
+    pub nodes: Vec<NodeProfile>,
+    /// Refresh interval of dashboards without a specific one, in milliseconds
+    pub refresh_interval: usize,
+    pub refresh: DashboardRefresh,
+    pub keys: KeyBindings,
+    pub default_tab: DashboardTab,
+    pub theme_file: Option<String>,
+    pub data_dir: Option<PathBuf>,
+    /// Whether to record metrics history to `data_dir`
+    pub history: bool,
+    #[serde(rename = "alert")]
+    pub alerts: Vec<AlertRule>,
+    /// Address to serve Prometheus metrics on
+    pub metrics_listen: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            nodes: vec![],
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            refresh: Default::default(),
+            keys: Default::default(),
+            default_tab: Default::default(),
+            theme_file: None,
+            data_dir: None,
+            history: true,
+            alerts: vec![],
+            metrics_listen: None,
+        }
+    }
+}
+
+/// Refresh interval of each dashboard in milliseconds, falls back to `refresh_interval`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DashboardRefresh {
+    pub overview: Option<usize>,
+    pub blockchain: Option<usize>,
+    pub mempool: Option<usize>,
+    pub peers: Option<usize>,
+    pub logs: Option<usize>,
+}
+
+impl DashboardRefresh {
+    pub fn interval_of(&self, tab: DashboardTab) -> Option<usize> {
+        match tab {
+            DashboardTab::Overview => self.overview,
+            DashboardTab::Blockchain => self.blockchain,
+            DashboardTab::Mempool => self.mempool,
+            DashboardTab::Peers => self.peers,
+            DashboardTab::Logs => self.logs,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DashboardTab {
+    #[default]
+    Overview,
+    Blockchain,
+    Mempool,
+    Peers,
+    Logs,
+}
+
+impl DashboardTab {
+    pub const ALL: [DashboardTab; 5] = [
+        DashboardTab::Overview,
+        DashboardTab::Blockchain,
+        DashboardTab::Mempool,
+        DashboardTab::Peers,
+        DashboardTab::Logs,
+    ];
+
+    /// Index of the tab, as stored in `CURRENT_TAB`
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|x| x == self).unwrap()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub quit: char,
+    pub debug_console: char,
+    pub menu: char,
+    pub refresh: char,
+    pub switch_node: char,
+    pub fleet: char,
+    pub chart_range: char,
+    pub alerts: char,
+    pub notifications: char,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: 'q',
+            debug_console: '~',
+            menu: 'm',
+            refresh: 'r',
+            switch_node: 'n',
+            fleet: 'f',
+            chart_range: 'h',
+            alerts: 'a',
+            notifications: 'e',
+        }
+    }
+}
+
+impl KeyBindings {
+    fn all(&self) -> [(&'static str, char); 9] {
+        [
+            ("quit", self.quit),
+            ("debug_console", self.debug_console),
+            ("menu", self.menu),
+            ("refresh", self.refresh),
+            ("switch_node", self.switch_node),
+            ("fleet", self.fleet),
+            ("chart_range", self.chart_range),
+            ("alerts", self.alerts),
+            ("notifications", self.notifications),
+        ]
+    }
+}
+
+/// `$XDG_CONFIG_HOME/ckb-tui/config.toml`, or `~/.config/ckb-tui/config.toml`
+pub fn default_config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|x| PathBuf::from(x).join(".config")))
+        .map(|x| x.join("ckb-tui").join("config.toml"))
+}
+
+impl Config {
+    /// Loads the config from `path`, or from the default path if it exists
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_config_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| anyhow!("Unable to read config file {}", path.display()))?;
+        Self::parse(&content).with_context(|| anyhow!("Invalid config file {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let config = toml::from_str::<Self>(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the loaded values, again after they were overridden by command line flags
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        for node in self.nodes.iter() {
+            if node.name.is_empty() || node.rpc_url.is_empty() {
+                bail!(
+                    "Node `{}` must have a non-empty name and rpc_url",
+                    node.name
+                );
+            }
+            if !names.insert(&node.name) {
+                bail!("Node name `{}` is used more than once", node.name);
+            }
+        }
+        if self.refresh_interval == 0 {
+            bail!("refresh_interval must be greater than 0");
+        }
+        for tab in DashboardTab::ALL {
+            if self.refresh.interval_of(tab) == Some(0) {
+                bail!("refresh.{:?} must be greater than 0", tab);
+            }
+        }
+        let mut rule_names = HashSet::new();
+        for rule in self.alerts.iter() {
+            rule.validate()?;
+            if !rule_names.insert(&rule.name) {
+                bail!("Alert name `{}` is used more than once", rule.name);
+            }
+        }
+        let keys = self.keys.all();
+        for (i, (action, key)) in keys.iter().enumerate() {
+            if let Some((other, _)) = keys[..i].iter().find(|(_, other_key)| other_key == key) {
+                bail!(
+                    "Key `{}` is bound to both keys.{} and keys.{}",
+                    key,
+                    other,
+                    action
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Refresh interval of the given tab, in milliseconds
+    pub fn refresh_interval_of(&self, tab: DashboardTab) -> usize {
+        self.refresh
+            .interval_of(tab)
+            .unwrap_or(self.refresh_interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, DashboardTab};
+    use crate::alerts::AlertMetric;
+
+    #[test]
+    fn parses_full_config() {
+        let config = Config::parse(
+            r#"
+            refresh_interval = 500
+            default_tab = "mempool"
+            theme_file = "theme.toml"
+
+            [[node]]
+            name = "mainnet"
+            rpc_url = "http://127.0.0.1:8114"
+            tcp_url = "127.0.0.1:18114"
+
+            [refresh]
+            peers = 2000
+
+            [keys]
+            quit = "x"
+
+            [[alert]]
+            name = "High CPU"
+            metric = "cpu"
+            above = 90
+            for_secs = 300
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.nodes.len(), 1);
+        assert_eq!(config.nodes[0].tcp_url.as_deref(), Some("127.0.0.1:18114"));
+        assert_eq!(config.default_tab, DashboardTab::Mempool);
+        assert_eq!(config.refresh_interval_of(DashboardTab::Peers), 2000);
+        assert_eq!(config.refresh_interval_of(DashboardTab::Logs), 500);
+        assert_eq!(config.keys.quit, 'x');
+        assert_eq!(config.keys.menu, 'm');
+        assert_eq!(config.alerts[0].metric, AlertMetric::Cpu);
+        assert_eq!(config.alerts[0].above, Some(90.0));
+        assert_eq!(config.alerts[0].for_secs, 300);
+    }
+
+    #[test]
+    fn rejects_conflicting_keys_and_unknown_fields() {
+        let err = Config::parse("[keys]\nquit = \"m\"").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Key `m` is bound to both keys.quit and keys.menu"
+        );
+        assert!(Config::parse("refresh_intervl = 100").is_err());
+        let err =
+            Config::parse("[[alert]]\nname = \"Peers\"\nmetric = \"peers\"\nabove = 1\nbelow = 3")
+                .unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "Alert `Peers` must set exactly one of `above` and `below`"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_overrides() {
+        let mut config = Config::parse("refresh_interval = 500").unwrap();
+        config.refresh_interval = 0;
+        assert!(config.validate().is_err());
+        config.refresh_interval = 500;
+        config.nodes = vec!["a=http://127.0.0.1:8114".parse().unwrap(); 2];
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "Node name `a` is used more than once"
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::{Context, anyhow, bail};
+
+use crate::{
+    NodeProfile,
+    components::{
+        DashboardData, DashboardState,
+        dashboard::{
+            UpdatedSources, blockchain::BlockchainDashboardState, mempool::MempoolDashboardState,
+            overview::OverviewDashboardState, peers::PeersDashboardData,
+        },
+        is_overview_supported,
+    },
+    config::Config,
+    history::{self, MetricsHistory, NodeHistory},
+    utils::{rpc_cache::CachedRpcClient, subscription::SubscriptionMultiplexer},
+};
+
+/// Implemented by dashboard states and data that publish their values to the exporter
+pub trait ExportMetrics {
+    fn export_metrics(&self, metrics: &mut MetricsWriter);
+}
+
+struct MetricFamily {
+    name: &'static str,
+    help: &'static str,
+    // `gauge` or `counter`
+    kind: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+/// Gauges and counters of one node, in the order they were written
+#[derive(Default)]
+pub struct MetricsWriter {
+    families: Vec<MetricFamily>,
+}
+
+impl MetricsWriter {
+    pub fn gauge(&mut self, name: &'static str, help: &'static str, value: f64) {
+        self.gauge_with_labels(name, help, vec![], value);
+    }
+
+    pub fn gauge_with_labels(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: Vec<(&'static str, String)>,
+        value: f64,
+    ) {
+        self.sample(name, help, "gauge", labels, value);
+    }
+
+    /// A value that only increases, named with a `_total` suffix
+    pub fn counter(&mut self, name: &'static str, help: &'static str, value: f64) {
+        self.counter_with_labels(name, help, vec![], value);
+    }
+
+    pub fn counter_with_labels(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        labels: Vec<(&'static str, String)>,
+        value: f64,
+    ) {
+        self.sample(name, help, "counter", labels, value);
+    }
+
+    fn sample(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        kind: &'static str,
+        labels: Vec<(&'static str, String)>,
+        value: f64,
+    ) {
+        let family = match self.families.iter().position(|x| x.name == name) {
+            Some(index) => &mut self.families[index],
+            None => {
+                self.families.push(MetricFamily {
+                    name,
+                    help,
+                    kind,
+                    samples: vec![],
+                });
+                self.families.last_mut().unwrap()
+            }
+        };
+        family.samples.push((labels, value));
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "+Inf" } else { "-Inf" })
+    } else {
+        value.to_string()
+    }
+}
+
+/// Latest metrics of the monitored nodes, served in the Prometheus text format
+#[derive(Default)]
+pub struct MetricsExporter {
+    nodes: RwLock<BTreeMap<String, MetricsWriter>>,
+}
+
+impl MetricsExporter {
+    /// Replaces the metrics of the given node
+    pub fn publish(&self, node: &str, metrics: MetricsWriter) {
+        self.nodes
+            .write()
+            .unwrap()
+            .insert(node.to_string(), metrics);
+    }
+
+    /// Drops metrics of all nodes, e.g. after the TUI switched to another node
+    pub fn clear(&self) {
+        self.nodes.write().unwrap().clear();
+    }
+
+    pub fn render(&self) -> String {
+        let guard = self.nodes.read().unwrap();
+        // Samples of all nodes are grouped by family, the `node` label tells them apart
+        let mut families = Vec::<(&str, &str, &str, Vec<String>)>::new();
+        for (node, metrics) in guard.iter() {
+            for family in metrics.families.iter() {
+                let index = match families.iter().position(|x| x.0 == family.name) {
+                    Some(index) => index,
+                    None => {
+                        families.push((family.name, family.help, family.kind, vec![]));
+                        families.len() - 1
+                    }
+                };
+                for (labels, value) in family.samples.iter() {
+                    let labels = std::iter::once(("node", node.as_str()))
+                        .chain(labels.iter().map(|(k, v)| (*k, v.as_str())))
+                        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label_value(v)))
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    families[index].3.push(format!(
+                        "{}{{{}}} {}",
+                        family.name,
+                        labels,
+                        format_value(*value)
+                    ));
+                }
+            }
+        }
+        let mut result = String::new();
+        for (name, help, kind, lines) in families.into_iter() {
+            result.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} {}\n",
+                name, help, name, kind
+            ));
+            for line in lines.into_iter() {
+                result.push_str(&line);
+                result.push('\n');
+            }
+        }
+        result
+    }
+
+    /// Serves `/metrics` on the given address from a background thread, returning the bound address
+    pub fn serve(self: Arc<Self>, addr: &str) -> anyhow::Result<SocketAddr> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| anyhow!("Unable to listen on {}", addr))?;
+        let local_addr = listener.local_addr()?;
+        log::info!("Serving metrics on http://{}/metrics", local_addr);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream
+                    .map_err(anyhow::Error::from)
+                    .and_then(|stream| self.handle_connection(stream));
+                if let Err(e) = result {
+                    log::warn!("Unable to serve metrics request: {:?}", e);
+                }
+            }
+        });
+        Ok(local_addr)
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> anyhow::Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Headers are not used, but must be read before responding
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+                break;
+            }
+        }
+        let mut parts = request_line.split_whitespace();
+        let (status, content_type, body) = match (parts.next(), parts.next()) {
+            (Some("GET"), Some(path)) if path == "/metrics" || path.starts_with("/metrics?") => {
+                ("200 OK", "text/plain; version=0.0.4", self.render())
+            }
+            (Some("GET"), _) => (
+                "404 Not Found",
+                "text/plain",
+                String::from("Metrics are served at /metrics\n"),
+            ),
+            _ => (
+                "405 Method Not Allowed",
+                "text/plain",
+                String::from("Only GET is supported\n"),
+            ),
+        };
+        let mut writer = &stream;
+        write!(
+            writer,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Collects the metrics of one node from the dashboard states, shared by the TUI and headless mode.
+/// Series of the sources whose update failed are left out instead of repeating stale values
+pub(crate) fn collect_metrics(sources: &UpdatedSources) -> MetricsWriter {
+    let mut metrics = MetricsWriter::default();
+    metrics.gauge(
+        "ckb_node_up",
+        "Whether the node could be queried",
+        if sources.node_up() { 1.0 } else { 0.0 },
+    );
+    let exported: [(&str, Option<&dyn ExportMetrics>); 4] = [
+        ("overview", sources.overview.map(|x| x as _)),
+        ("blockchain", sources.blockchain.map(|x| x as _)),
+        ("mempool", sources.mempool.map(|x| x as _)),
+        ("peers", sources.peers.map(|x| x as _)),
+    ];
+    for (source, exporter) in exported.iter() {
+        metrics.gauge_with_labels(
+            "ckb_source_up",
+            "Whether the last update of the source succeeded",
+            vec![("source", source.to_string())],
+            if exporter.is_some() { 1.0 } else { 0.0 },
+        );
+    }
+    for exporter in exported.into_iter().filter_map(|(_, x)| x) {
+        exporter.export_metrics(&mut metrics);
+    }
+    metrics
+}
+
+struct NodeCollector {
+    name: String,
+    client: CachedRpcClient,
+    overview_state: OverviewDashboardState,
+    blockchain_state: BlockchainDashboardState,
+    mempool_state: MempoolDashboardState,
+    peers_data: PeersDashboardData,
+    node_history: NodeHistory,
+}
+
+impl NodeCollector {
+    fn new(profile: &NodeProfile, history: &Arc<MetricsHistory>) -> anyhow::Result<Self> {
+        let client = CachedRpcClient::new(&profile.rpc_url);
+        let enable_fetch_overview = is_overview_supported(&client);
+        let node_history = NodeHistory::new(Arc::clone(history), &profile.name);
+        let mut peers_data = PeersDashboardData::default();
+        peers_data.set_enable_overview_data(enable_fetch_overview);
+        let multiplexer = profile.tcp_url.clone().map(SubscriptionMultiplexer::new);
+        Ok(Self {
+            overview_state: OverviewDashboardState::new(
+                client.clone(),
+                enable_fetch_overview,
+                node_history.clone(),
+            )?,
+            blockchain_state: BlockchainDashboardState::new(
+                client.clone(),
+                enable_fetch_overview,
+                multiplexer.as_ref(),
+                node_history.clone(),
+            ),
+            // Nothing shows the notifications in headless mode, but new errors are logged
+            mempool_state: MempoolDashboardState::new(
+                client.clone(),
+                multiplexer.as_ref(),
+                node_history.clone(),
+                Default::default(),
+            ),
+            peers_data,
+            node_history,
+            client,
+            name: profile.name.clone(),
+        })
+    }
+
+    /// Metrics of the sources that could be updated, the failed ones are logged
+    fn collect(&mut self) -> MetricsWriter {
+        let results = [
+            ("overview", self.overview_state.update_state()),
+            ("blockchain", self.blockchain_state.update_state()),
+            ("mempool", self.mempool_state.update_state()),
+            (
+                "peers",
+                self.peers_data
+                    .fetch_data_through_client(&self.client)
+                    .map(|_| ()),
+            ),
+        ];
+        for (source, result) in results.iter() {
+            if let Err(e) = result {
+                log::warn!(
+                    "Unable to update {} metrics of `{}`: {:?}",
+                    source,
+                    self.name,
+                    e
+                );
+            }
+        }
+        if results[3].1.is_ok() {
+            self.node_history
+                .record(history::PEERS, self.peers_data.connections() as f64);
+        }
+        collect_metrics(&UpdatedSources {
+            overview: results[0].1.is_ok().then_some(&self.overview_state),
+            blockchain: results[1].1.is_ok().then_some(&self.blockchain_state),
+            mempool: results[2].1.is_ok().then_some(&self.mempool_state),
+            peers: results[3].1.is_ok().then_some(&self.peers_data),
+        })
+    }
+}
+
+/// Serves metrics of all configured nodes without starting the TUI, never returns unless failed
+pub fn start_headless_exporter(config: Config) -> anyhow::Result<()> {
+    let Some(addr) = &config.metrics_listen else {
+        bail!("Headless mode requires a metrics listen address");
+    };
+    if config.nodes.is_empty() {
+        bail!("At least one node profile is required");
+    }
+    let history = Arc::new(match config.data_dir.as_ref().filter(|_| config.history) {
+        Some(data_dir) => MetricsHistory::open(data_dir)
+            .with_context(|| anyhow!("Unable to open metrics history"))?,
+        None => MetricsHistory::in_memory(),
+    });
+    let exporter = Arc::new(MetricsExporter::default());
+    Arc::clone(&exporter).serve(addr)?;
+    let mut collectors = config
+        .nodes
+        .iter()
+        .map(|_| None)
+        .collect::<Vec<Option<NodeCollector>>>();
+    loop {
+        for (profile, collector) in config.nodes.iter().zip(collectors.iter_mut()) {
+            if collector.is_none() {
+                match NodeCollector::new(profile, &history) {
+                    Ok(new_collector) => *collector = Some(new_collector),
+                    Err(e) => log::warn!("Unable to connect to node `{}`: {:?}", profile.name, e),
+                }
+            }
+            let metrics = match collector.as_mut() {
+                Some(collector) => collector.collect(),
+                None => {
+                    let mut metrics = MetricsWriter::default();
+                    metrics.gauge("ckb_node_up", "Whether the node could be queried", 0.0);
+                    metrics
+                }
+            };
+            exporter.publish(&profile.name, metrics);
+        }
+        std::thread::sleep(Duration::from_millis(config.refresh_interval as u64));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        sync::Arc,
+    };
+
+    use super::{MetricsExporter, MetricsWriter, NodeCollector};
+    use crate::{
+        NodeProfile,
+        history::MetricsHistory,
+        mock_node::{MockNode, fixtures},
+    };
+
+    #[test]
+    fn renders_families_of_all_nodes() {
+        let exporter = MetricsExporter::default();
+        let mut metrics = MetricsWriter::default();
+        metrics.gauge("ckb_live_cells", "Estimated number of live cells", 10.0);
+        metrics.counter_with_labels(
+            "ckb_rejected_transactions_total",
+            "Rejected transactions",
+            vec![("reason", String::from("Said \"no\""))],
+            2.0,
+        );
+        exporter.publish("a", metrics);
+        let mut metrics = MetricsWriter::default();
+        metrics.gauge("ckb_live_cells", "Estimated number of live cells", 20.5);
+        exporter.publish("b", metrics);
+        assert_eq!(
+            exporter.render(),
+            "# HELP ckb_live_cells Estimated number of live cells\n\
+             # TYPE ckb_live_cells gauge\n\
+             ckb_live_cells{node=\"a\"} 10\n\
+             ckb_live_cells{node=\"b\"} 20.5\n\
+             # HELP ckb_rejected_transactions_total Rejected transactions\n\
+             # TYPE ckb_rejected_transactions_total counter\n\
+             ckb_rejected_transactions_total{node=\"a\",reason=\"Said \\\"no\\\"\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn serves_metrics_over_http() {
+        let exporter = Arc::new(MetricsExporter::default());
+        let mut metrics = MetricsWriter::default();
+        metrics.gauge("ckb_node_up", "Whether the node could be queried", 1.0);
+        exporter.publish("a", metrics);
+        let addr = Arc::clone(&exporter).serve("127.0.0.1:0").unwrap();
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = request("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("ckb_node_up{node=\"a\"} 1\n"));
+        assert!(request("/").starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    #[test]
+    fn collects_metrics_of_updated_sources() {
+        let node = MockNode::start();
+        let profile = NodeProfile {
+            name: String::from("a"),
+            rpc_url: node.rpc_url(),
+            tcp_url: None,
+        };
+        let history = Arc::new(MetricsHistory::in_memory());
+        let mut collector = NodeCollector::new(&profile, &history).unwrap();
+        // Only the mempool fails
+        node.set_error("tx_pool_info", "tx-pool is busy");
+        let exporter = MetricsExporter::default();
+        exporter.publish("a", collector.collect());
+        let text = exporter.render();
+        assert!(text.contains("ckb_node_up{node=\"a\"} 1\n"));
+        assert!(text.contains("ckb_source_up{node=\"a\",source=\"mempool\"} 0\n"));
+        assert!(text.contains("ckb_source_up{node=\"a\",source=\"peers\"} 1\n"));
+        assert!(text.contains(&format!(
+            "ckb_tip_block_number{{node=\"a\"}} {}\n",
+            fixtures::TIP_NUMBER
+        )));
+        assert!(!text.contains("ckb_recommended_fee_rate"));
+
+        let node = MockNode::empty();
+        let profile = NodeProfile {
+            rpc_url: node.rpc_url(),
+            ..profile
+        };
+        let mut collector = NodeCollector::new(&profile, &history).unwrap();
+        exporter.publish("a", collector.collect());
+        let text = exporter.render();
+        assert!(text.contains("ckb_node_up{node=\"a\"} 0\n"));
+        assert!(!text.contains("ckb_tip_block_number"));
+    }
+}
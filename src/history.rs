@@ -0,0 +1,332 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions, TryLockError},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock, atomic::AtomicUsize},
+};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// CPU usage of the node, in range [0, 1]
+pub const CPU: &str = "cpu";
+pub const LIVE_CELLS: &str = "live_cells";
+pub const OCCUPIED_CAPACITY: &str = "occupied_capacity";
+pub const TIP_NUMBER: &str = "tip_number";
+/// Best known block number of the network, the target of the sync
+pub const SYNC_HEIGHT: &str = "sync_height";
+/// Connected peers, inbound and outbound
+pub const PEERS: &str = "peers";
+/// Transactions in the pool, including orphans
+pub const POOL_SIZE: &str = "pool_size";
+/// Median fee rate of the last 21 blocks, in shannons/KB
+pub const FEE_RATE: &str = "fee_rate";
+
+/// Number of bars of a history chart
+pub const CHART_POINTS: usize = 20;
+
+const HISTORY_FILE_NAME: &str = "metrics.jsonl";
+// Samples are persisted at most once per this interval for each metric, to keep the file small
+const PERSIST_INTERVAL_MS: i64 = 10 * 1000;
+const RETENTION_MS: i64 = 7 * 24 * 3600 * 1000;
+
+/// Index of the history range used by charts, see [`HistoryRange::ALL`]
+pub static CURRENT_HISTORY_RANGE: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryRange {
+    /// Samples of the current session only, kept in memory
+    Live,
+    Hour,
+    Day,
+    Week,
+}
+
+impl HistoryRange {
+    pub const ALL: [HistoryRange; 4] = [
+        HistoryRange::Live,
+        HistoryRange::Hour,
+        HistoryRange::Day,
+        HistoryRange::Week,
+    ];
+
+    pub fn current() -> Self {
+        Self::ALL[CURRENT_HISTORY_RANGE.load(std::sync::atomic::Ordering::SeqCst) % Self::ALL.len()]
+    }
+
+    /// Switches charts to the next range and returns it
+    pub fn cycle() -> Self {
+        let next =
+            (CURRENT_HISTORY_RANGE.load(std::sync::atomic::Ordering::SeqCst) + 1) % Self::ALL.len();
+        CURRENT_HISTORY_RANGE.store(next, std::sync::atomic::Ordering::SeqCst);
+        Self::ALL[next]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HistoryRange::Live => "Live",
+            HistoryRange::Hour => "Last hour",
+            HistoryRange::Day => "Last day",
+            HistoryRange::Week => "Last week",
+        }
+    }
+
+    fn duration_ms(&self) -> Option<i64> {
+        match self {
+            HistoryRange::Live => None,
+            HistoryRange::Hour => Some(3600 * 1000),
+            HistoryRange::Day => Some(24 * 3600 * 1000),
+            HistoryRange::Week => Some(RETENTION_MS),
+        }
+    }
+}
+
+/// `$XDG_DATA_HOME/ckb-tui`, or `~/.local/share/ckb-tui`
+pub fn default_data_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|x| PathBuf::from(x).join(".local").join("share")))
+        .map(|x| x.join("ckb-tui"))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Sample {
+    #[serde(rename = "t")]
+    time: i64,
+    node: String,
+    metric: String,
+    #[serde(rename = "v")]
+    value: f64,
+}
+
+type SeriesKey = (String, String);
+
+/// Append-only time series of sampled metrics, stored under the data directory
+pub struct MetricsHistory {
+    // None if the history is only kept in memory
+    file: Option<Mutex<File>>,
+    series: RwLock<HashMap<SeriesKey, Vec<(i64, f64)>>>,
+}
+
+impl MetricsHistory {
+    pub fn in_memory() -> Self {
+        Self {
+            file: None,
+            series: Default::default(),
+        }
+    }
+
+    /// Loads samples of the last week from the data directory. Several instances (e.g. the TUI
+    /// and the headless exporter) may append to the same file, each holding a shared lock on it.
+    /// Expired samples are only dropped from the file by an instance opening it alone
+    pub fn open(data_dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(data_dir)
+            .with_context(|| anyhow!("Unable to create data directory {}", data_dir.display()))?;
+        let path = data_dir.join(HISTORY_FILE_NAME);
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .with_context(|| anyhow!("Unable to open {}", path.display()))?;
+        let exclusive = match file.try_lock() {
+            Ok(()) => true,
+            Err(TryLockError::WouldBlock) => {
+                file.lock_shared()
+                    .with_context(|| anyhow!("Unable to lock {}", path.display()))?;
+                false
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| anyhow!("Unable to lock {}", path.display()));
+            }
+        };
+        let since = chrono::Utc::now().timestamp_millis() - RETENTION_MS;
+        let mut samples = vec![];
+        let mut dropped = 0;
+        for line in BufReader::new(&file).lines() {
+            let line = line.with_context(|| anyhow!("Unable to read {}", path.display()))?;
+            match serde_json::from_str::<Sample>(&line) {
+                Ok(sample) if sample.time >= since => samples.push(sample),
+                Ok(_) => dropped += 1,
+                // A partially written line, e.g. when ckb-tui was killed
+                Err(e) => {
+                    log::warn!("Skipping bad history line `{}`: {:?}", line, e);
+                    dropped += 1;
+                }
+            }
+        }
+        if exclusive {
+            // Rewritten in place, so that the file stays the one other instances open
+            if dropped > 0 {
+                file.set_len(0)
+                    .with_context(|| anyhow!("Unable to truncate {}", path.display()))?;
+                let mut writer = BufWriter::new(&file);
+                for sample in samples.iter() {
+                    writeln!(writer, "{}", serde_json::to_string(sample)?)?;
+                }
+                writer
+                    .flush()
+                    .with_context(|| anyhow!("Unable to write {}", path.display()))?;
+            }
+            file.unlock()
+                .with_context(|| anyhow!("Unable to unlock {}", path.display()))?;
+            file.lock_shared()
+                .with_context(|| anyhow!("Unable to lock {}", path.display()))?;
+        }
+        let mut series = HashMap::<SeriesKey, Vec<(i64, f64)>>::new();
+        for sample in samples.into_iter() {
+            series
+                .entry((sample.node, sample.metric))
+                .or_default()
+                .push((sample.time, sample.value));
+        }
+        Ok(Self {
+            file: Some(Mutex::new(file)),
+            series: RwLock::new(series),
+        })
+    }
+
+    pub fn record(&self, node: &str, metric: &str, value: f64) {
+        let now = chrono::Utc::now().timestamp_millis();
+        {
+            let mut guard = self.series.write().unwrap();
+            let series = guard
+                .entry((node.to_string(), metric.to_string()))
+                .or_default();
+            if series
+                .last()
+                .is_some_and(|(time, _)| now - time < PERSIST_INTERVAL_MS)
+            {
+                return;
+            }
+            series.push((now, value));
+            let expired = series.partition_point(|(time, _)| *time < now - RETENTION_MS);
+            series.drain(..expired);
+        }
+        if let Some(file) = &self.file {
+            let line = serde_json::to_string(&Sample {
+                time: now,
+                node: node.to_string(),
+                metric: metric.to_string(),
+                value,
+            })
+            .unwrap();
+            if let Err(e) = writeln!(file.lock().unwrap(), "{}", line) {
+                log::warn!("Unable to write metrics history: {:?}", e);
+            }
+        }
+    }
+
+    /// Samples of the given range, averaged into `points` buckets
+    pub fn series(&self, node: &str, metric: &str, range_ms: i64, points: usize) -> Vec<f64> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let guard = self.series.read().unwrap();
+        match guard.get(&(node.to_string(), metric.to_string())) {
+            // Including the samples recorded within the current millisecond
+            Some(series) => bucketize(series, now - range_ms, now + 1, points),
+            None => vec![0.0; points],
+        }
+    }
+}
+
+/// Averages the samples in [from, to) into `points` equal-width buckets, empty buckets are 0
+fn bucketize(samples: &[(i64, f64)], from: i64, to: i64, points: usize) -> Vec<f64> {
+    let mut sums = vec![(0.0, 0usize); points];
+    let width = ((to - from) as f64 / points as f64).max(1.0);
+    let start = samples.partition_point(|(time, _)| *time < from);
+    for (time, value) in samples[start..].iter() {
+        if *time >= to {
+            break;
+        }
+        let index = (((time - from) as f64 / width) as usize).min(points - 1);
+        sums[index].0 += value;
+        sums[index].1 += 1;
+    }
+    sums.into_iter()
+        .map(|(sum, count)| if count == 0 { 0.0 } else { sum / count as f64 })
+        .collect()
+}
+
+/// History of one node, handed to dashboard states
+#[derive(Clone)]
+pub struct NodeHistory {
+    store: Arc<MetricsHistory>,
+    node: String,
+}
+
+impl NodeHistory {
+    pub fn new(store: Arc<MetricsHistory>, node: &str) -> Self {
+        Self {
+            store,
+            node: node.to_string(),
+        }
+    }
+
+    pub fn record(&self, metric: &str, value: f64) {
+        self.store.record(&self.node, metric, value);
+    }
+
+    /// Chart data of the current range, `None` if charts should show the live session data
+    pub fn current_series(&self, metric: &str) -> Option<Vec<f64>> {
+        HistoryRange::current().duration_ms().map(|range_ms| {
+            self.store
+                .series(&self.node, metric, range_ms, CHART_POINTS)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HISTORY_FILE_NAME, MetricsHistory, bucketize};
+
+    #[test]
+    fn averages_samples_into_buckets() {
+        let samples = [(0, 1.0), (5, 3.0), (10, 5.0), (35, 7.0), (40, 100.0)];
+        assert_eq!(bucketize(&samples, 0, 40, 4), vec![2.0, 5.0, 0.0, 7.0]);
+    }
+
+    #[test]
+    fn keeps_samples_across_reopen() {
+        let dir = std::env::temp_dir().join(format!("ckb-tui-history-{}", std::process::id()));
+        {
+            let history = MetricsHistory::open(&dir).unwrap();
+            history.record("node", "cpu", 0.5);
+            // Throttled, the first sample is kept
+            history.record("node", "cpu", 0.9);
+        }
+        let history = MetricsHistory::open(&dir).unwrap();
+        let series = history.series("node", "cpu", 3600 * 1000, 4);
+        assert_eq!(
+            series.iter().filter(|x| **x != 0.0).collect::<Vec<_>>(),
+            vec![&0.5]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_appends_of_concurrent_instances() {
+        let dir = std::env::temp_dir().join(format!("ckb-tui-shared-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(HISTORY_FILE_NAME),
+            "{\"t\":0,\"node\":\"a\",\"metric\":\"cpu\",\"v\":1.0}\n",
+        )
+        .unwrap();
+        {
+            let tui = MetricsHistory::open(&dir).unwrap();
+            let headless = MetricsHistory::open(&dir).unwrap();
+            tui.record("a", "cpu", 0.5);
+            headless.record("b", "cpu", 0.7);
+        }
+        let content = std::fs::read_to_string(dir.join(HISTORY_FILE_NAME)).unwrap();
+        // The expired sample was dropped by the first instance
+        assert_eq!(content.lines().count(), 2);
+        let history = MetricsHistory::open(&dir).unwrap();
+        for (node, value) in [("a", 0.5), ("b", 0.7)] {
+            assert_eq!(history.series(node, "cpu", 3600 * 1000, 1), vec![value]);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
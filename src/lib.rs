@@ -1,97 +1,215 @@
 use std::{
+    collections::BTreeMap,
+    str::FromStr,
     sync::{
-        Arc,
+        Arc, Mutex,
         atomic::{AtomicBool, AtomicUsize},
         mpsc,
     },
     time::Duration,
 };
 
-use anyhow::{anyhow, bail};
-use ckb_jsonrpc_types::Overview;
-use ckb_sdk::CkbRpcClient;
+use anyhow::{Context, anyhow, bail};
 use cursive::{
     Cursive,
     view::Resizable,
-    views::{Dialog, DummyView, TextView},
+    views::{Dialog, DummyView},
 };
 use cursive_async_view::AsyncView;
+use serde::Deserialize;
 
 use crate::components::{
     DashboardData, DashboardState, UpdateToView,
     dashboard::{
-        GeneralDashboardData, TUIEvent,
+        GeneralDashboardData, TUIEvent, UpdatedSources,
+        alerts::alerts_history_dialog,
         blockchain::{BlockchainDashboardData, BlockchainDashboardState},
-        dashboard,
+        cycle_chart_range, dashboard,
+        fleet::{fleet_summary_dialog, node_selector_dialog},
         logs::LogsDashboardState,
         mempool::{MempoolDashboardData, MempoolDashboardState},
+        notifications::notifications_dialog,
         overview::{OverviewDashboardData, OverviewDashboardState},
         peers::PeersDashboardData,
-        set_loading,
+        set_loading, update_stale_panels,
     },
     details::menu::details_menu,
+    is_overview_supported,
+};
+use crate::{
+    alerts::AlertEngine,
+    config::{Config, DashboardTab},
+    exporter::{MetricsExporter, collect_metrics},
+    history::{MetricsHistory, NodeHistory},
+    notifications::NotificationCenter,
+    utils::{rpc_cache::CachedRpcClient, subscription::SubscriptionMultiplexer},
 };
 
 pub static CURRENT_TAB: AtomicUsize = AtomicUsize::new(0);
+/// Index of the node profile currently shown by the dashboard
+pub static CURRENT_NODE: AtomicUsize = AtomicUsize::new(0);
 
+pub mod alerts;
 mod components;
+pub mod config;
+pub mod exporter;
+pub mod history;
+#[cfg(test)]
+mod mock_node;
+pub mod notifications;
+pub mod snapshot;
 mod utils;
 enum SyncRequest {
     Stop,
-    RequestSync { pop_layer_at_end: bool },
+    RequestSync {
+        pop_layer_at_end: bool,
+    },
+    SwitchNode {
+        client: CachedRpcClient,
+        name: String,
+    },
+}
+
+/// A named CKB node that ckb-tui is able to monitor
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeProfile {
+    pub name: String,
+    pub rpc_url: String,
+    pub tcp_url: Option<String>,
+}
+
+impl FromStr for NodeProfile {
+    type Err = anyhow::Error;
+
+    /// Parses `NAME=RPC_URL[,TCP_URL]`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, endpoints) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Bad node profile `{}`, expected NAME=RPC_URL[,TCP_URL]", s))?;
+        if name.is_empty() {
+            bail!("Bad node profile `{}`, name must not be empty", s);
+        }
+        let (rpc_url, tcp_url) = match endpoints.split_once(',') {
+            Some((rpc_url, tcp_url)) => (rpc_url, Some(tcp_url.to_string())),
+            None => (endpoints, None),
+        };
+        if rpc_url.is_empty() {
+            bail!("Bad node profile `{}`, RPC url must not be empty", s);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            rpc_url: rpc_url.to_string(),
+            tcp_url,
+        })
+    }
 }
 
-pub fn start_ckb_tui(
-    rpc_url: &str,
-    tcp_url: Option<String>,
-    refresh_interval: usize,
-    theme_file: Option<String>,
-    debug: bool,
-) -> anyhow::Result<()> {
+pub fn start_ckb_tui(config: Config, debug: bool) -> anyhow::Result<()> {
     if debug {
         cursive::logger::set_external_filter_level(log::LevelFilter::Debug);
     } else {
         cursive::logger::set_filter_levels_from_env();
     }
-    let client = CkbRpcClient::new(rpc_url);
+    if config.nodes.is_empty() {
+        bail!("At least one node profile is required");
+    }
+    let history = Arc::new(match config.data_dir.as_ref().filter(|_| config.history) {
+        Some(data_dir) => MetricsHistory::open(data_dir)
+            .with_context(|| anyhow!("Unable to open metrics history"))?,
+        None => MetricsHistory::in_memory(),
+    });
+    let alerts = Arc::new(Mutex::new(AlertEngine::new(config.alerts.clone())));
+    let notifications = Arc::new(Mutex::new(NotificationCenter::default()));
+    let exporter = match &config.metrics_listen {
+        Some(addr) => {
+            let exporter = Arc::new(MetricsExporter::default());
+            Arc::clone(&exporter).serve(addr)?;
+            Some(exporter)
+        }
+        None => None,
+    };
+    let keys = config.keys.clone();
+    let nodes = Arc::new(config.nodes.clone());
+    let clients = Arc::new(
+        nodes
+            .iter()
+            .map(|x| CachedRpcClient::new(&x.rpc_url))
+            .collect::<Vec<_>>(),
+    );
+    CURRENT_NODE.store(0, std::sync::atomic::Ordering::SeqCst);
+    let client = clients[0].clone();
     let mut siv = cursive::default();
-    if let Some(theme_file) = theme_file {
+    if let Some(theme_file) = &config.theme_file {
         siv.load_theme_file(theme_file)
             .map_err(|e| anyhow!("{:?}", e))?;
     }
-    siv.add_global_callback('q', |s| s.quit());
-    siv.add_global_callback('~', cursive::Cursive::toggle_debug_console);
-    let client_cloned = client.clone();
-    siv.add_global_callback('m', move |s| {
-        s.add_layer(details_menu(&client_cloned));
-    });
+    siv.add_global_callback(keys.quit, |s| s.quit());
+    siv.add_global_callback(keys.debug_console, cursive::Cursive::toggle_debug_console);
+    siv.add_global_callback(keys.chart_range, cycle_chart_range);
+    {
+        let clients = Arc::clone(&clients);
+        siv.add_global_callback(keys.menu, move |s| {
+            s.add_layer(details_menu(
+                clients[CURRENT_NODE.load(std::sync::atomic::Ordering::SeqCst)].inner(),
+            ));
+        });
+    }
+    {
+        let alerts = Arc::clone(&alerts);
+        siv.add_global_callback(keys.alerts, move |s| {
+            let dialog = alerts_history_dialog(&alerts.lock().unwrap());
+            s.add_layer(dialog);
+        });
+    }
+    {
+        let notifications = Arc::clone(&notifications);
+        siv.add_global_callback(keys.notifications, move |s| {
+            let dialog = notifications_dialog(&notifications.lock().unwrap());
+            s.add_layer(dialog);
+        });
+    }
     let loading_variable = Arc::new(AtomicBool::new(false));
-    if let Err(e) = client.local_node_info() {
+    if let Err(e) = client.inner().local_node_info() {
         bail!(
-            "Unable to send a basic request (local_node_info) to RPC server: {}, please check RPC url",
+            "Unable to send a basic request (local_node_info) to RPC server of node `{}`: {}, please check RPC url",
+            nodes[0].name,
             e
         );
     }
-    let enable_fetch_overview = client.post::<(), Overview>("get_overview", ()).is_ok();
+    let enable_fetch_overview = is_overview_supported(&client);
+    // Panels whose last fetch or update failed, and the error. Filled by both update loops
+    let stale_panels = Arc::new(Mutex::new(BTreeMap::<&'static str, String>::new()));
     let sync_request_tx = {
         let (tx, rx) = std::sync::mpsc::channel::<SyncRequest>();
         let cb_sink = siv.cb_sink().clone();
         let loading_variable = std::sync::Arc::<AtomicBool>::clone(&loading_variable);
-        let client = client.clone();
+        let mut client = client.clone();
+        let node_name = nodes[0].name.clone();
+        let notifications = Arc::clone(&notifications);
+        let stale_panels = Arc::clone(&stale_panels);
         std::thread::spawn(move || {
-            {
-                let mut general_data = GeneralDashboardData::default();
-                general_data.set_enable_overview_data(enable_fetch_overview);
-                let result = general_data.fetch_data_through_client(&client);
-                cb_sink
-                    .send(Box::new(move |siv| {
-                        if let Ok(result) = result {
-                            result.update_to_view(siv);
-                        };
-                    }))
-                    .unwrap();
-            }
-            let mut data: Vec<Box<dyn DashboardData>> = vec![
+            let update_general_data =
+                |client: &CachedRpcClient, node_name: String, enable_fetch_overview: bool| {
+                    let mut general_data = GeneralDashboardData::default();
+                    general_data.node_name = node_name;
+                    general_data.set_enable_overview_data(enable_fetch_overview);
+                    let result = general_data.fetch_data_through_client(client);
+                    notifications
+                        .lock()
+                        .unwrap()
+                        .track(&format!("{} panel", general_data.panel_name()), &result);
+                    cb_sink
+                        .send(Box::new(move |siv| {
+                            if let Ok(result) = result {
+                                result.update_to_view(siv);
+                            };
+                        }))
+                        .unwrap();
+                };
+            let mut enable_fetch_overview = enable_fetch_overview;
+            update_general_data(&client, node_name, enable_fetch_overview);
+            let mut data: Vec<Box<dyn DashboardData + Send>> = vec![
                 Box::new(OverviewDashboardData::default()),
                 Box::new(BlockchainDashboardData::default()),
                 Box::new(MempoolDashboardData::default()),
@@ -103,46 +221,67 @@ pub fn start_ckb_tui(
             loop {
                 match rx.recv().unwrap() {
                     SyncRequest::Stop => break,
+                    SyncRequest::SwitchNode {
+                        client: new_client,
+                        name,
+                    } => {
+                        client = new_client;
+                        enable_fetch_overview = is_overview_supported(&client);
+                        for item in data.iter_mut() {
+                            item.set_enable_overview_data(enable_fetch_overview);
+                        }
+                        stale_panels.lock().unwrap().clear();
+                        update_general_data(&client, name, enable_fetch_overview);
+                    }
                     SyncRequest::RequestSync { pop_layer_at_end } => {
                         loading_variable.store(true, std::sync::atomic::Ordering::SeqCst);
-                        let mut result_list = anyhow::Ok(vec![]);
-                        for item in data.iter_mut() {
-                            if item.should_update() {
-                                result_list = match item.fetch_data_through_client(&client) {
-                                    Ok(s) => match result_list {
-                                        Err(e) => Err(e),
-                                        Ok(mut new_list) => {
-                                            new_list.push(s);
-                                            Ok(new_list)
-                                        }
-                                    },
-                                    Err(e) => Err(e),
-                                };
+                        client.prefetch_refresh_cycle(enable_fetch_overview);
+                        // Fetch concurrently, a failing panel keeps its last fetched data
+                        let results = std::thread::scope(|scope| {
+                            data.iter_mut()
+                                .filter(|item| item.should_update())
+                                .map(|item| {
+                                    let client = client.clone();
+                                    scope.spawn(move || {
+                                        (item.panel_name(), item.fetch_data_through_client(&client))
+                                    })
+                                })
+                                .collect::<Vec<_>>()
+                                .into_iter()
+                                .map(|handle| handle.join().unwrap())
+                                .collect::<Vec<_>>()
+                        });
+                        let mut result_list = vec![];
+                        let mut stale_panels = stale_panels.lock().unwrap();
+                        for (panel, result) in results.into_iter() {
+                            notifications
+                                .lock()
+                                .unwrap()
+                                .track(&format!("{} panel", panel), &result);
+                            match result {
+                                Ok(fetched) => {
+                                    stale_panels.remove(panel);
+                                    result_list.push(fetched);
+                                }
+                                Err(e) => {
+                                    stale_panels.insert(panel, e.to_string());
+                                }
                             }
                         }
+                        let stale_snapshot = stale_panels.clone();
+                        drop(stale_panels);
+                        let notifications = Arc::clone(&notifications);
 
                         cb_sink
                             .send(Box::new(move |siv: &mut Cursive| {
                                 if pop_layer_at_end {
                                     siv.pop_layer();
                                 }
-
-                                match result_list {
-                                    Ok(result) => {
-                                        for item in result.into_iter() {
-                                            item.update_to_view(siv);
-                                        }
-                                    }
-                                    Err(err) => {
-                                        siv.add_layer(
-                                            Dialog::around(TextView::new(format!("{:?}", err)))
-                                                .title("Error")
-                                                .button("Close", |s| {
-                                                    s.pop_layer();
-                                                }),
-                                        );
-                                    }
+                                for item in result_list.into_iter() {
+                                    item.update_to_view(siv);
                                 }
+                                update_stale_panels(siv, &stale_snapshot);
+                                notifications.lock().unwrap().update_to_view(siv);
                                 set_loading(siv, false);
                             }))
                             .unwrap();
@@ -156,7 +295,7 @@ pub fn start_ckb_tui(
     {
         let tx = sync_request_tx.clone();
         let loading_variable = std::sync::Arc::<AtomicBool>::clone(&loading_variable);
-        siv.add_global_callback('r', move |siv| {
+        siv.add_global_callback(keys.refresh, move |siv| {
             if loading_variable.load(std::sync::atomic::Ordering::SeqCst) {
                 return;
             }
@@ -178,77 +317,261 @@ pub fn start_ckb_tui(
         let (event_tx, event_rx) = mpsc::channel::<TUIEvent>();
         let tx = sync_request_tx.clone();
         let cb_sink = siv.cb_sink().clone();
+        let nodes = Arc::clone(&nodes);
+        let clients = Arc::clone(&clients);
+        let tcp_url = nodes[0].tcp_url.clone();
+        let config = config.clone();
+        let alerts = Arc::clone(&alerts);
+        let exporter = exporter.clone();
+        let notifications = Arc::clone(&notifications);
+        let stale_panels = Arc::clone(&stale_panels);
         std::thread::spawn(move || {
-            let mut overview_state =
-                OverviewDashboardState::new(client.clone(), enable_fetch_overview).unwrap();
+            let mut node_history = NodeHistory::new(Arc::clone(&history), &nodes[0].name);
+            // One pubsub connection per node, shared by the dashboards
+            let mut multiplexer = tcp_url.map(SubscriptionMultiplexer::new);
+            let mut overview_state = OverviewDashboardState::new(
+                client.clone(),
+                enable_fetch_overview,
+                node_history.clone(),
+            )
+            .unwrap();
             let mut blockchain_state = BlockchainDashboardState::new(
                 client.clone(),
                 enable_fetch_overview,
-                tcp_url.clone(),
+                multiplexer.as_ref(),
+                node_history.clone(),
+            );
+            let mut mempool_state = MempoolDashboardState::new(
+                client.clone(),
+                multiplexer.as_ref(),
+                node_history.clone(),
+                Arc::clone(&notifications),
             );
-            let mut mempool_state = MempoolDashboardState::new(tcp_url.clone());
-            let mut logs_state = LogsDashboardState::new(tcp_url.clone());
-            let mut tick_count = 0;
+            let mut logs_state = LogsDashboardState::new(multiplexer.as_ref());
+            let mut peers_data = PeersDashboardData::default();
+            peers_data.set_enable_overview_data(enable_fetch_overview && exporter.is_some());
+            // Ticks since the last update of each dashboard, in the order of `DashboardTab::ALL`.
+            // Starting due, so that all dashboards are updated at once
+            let mut tick_counts: [usize; 5] =
+                std::array::from_fn(|i| config.refresh_interval_of(DashboardTab::ALL[i]));
+            // Whether the last update of the sources of alerts and metrics succeeded, in the order
+            // of `UpdatedSources`. Their values are used until the next update
+            let mut succeeded = [false; 4];
             loop {
                 // Accept events per millisesond
-                tick_count += 1;
+                for tick_count in tick_counts.iter_mut() {
+                    *tick_count += 1;
+                }
                 if let Ok(e) = event_rx.try_recv() {
+                    if let TUIEvent::SwitchNode(index) = e {
+                        let profile = &nodes[index];
+                        log::info!("Switching to node {}", profile.name);
+                        let client = clients[index].clone();
+                        let enable_fetch_overview = is_overview_supported(&client);
+                        let new_node_history =
+                            NodeHistory::new(Arc::clone(&history), &profile.name);
+                        let current_name =
+                            &nodes[CURRENT_NODE.load(std::sync::atomic::Ordering::SeqCst)].name;
+                        let result = OverviewDashboardState::new(
+                            client.clone(),
+                            enable_fetch_overview,
+                            new_node_history.clone(),
+                        )
+                        .with_context(|| {
+                            anyhow!(
+                                "Unable to switch to node `{}`, staying on `{}`",
+                                profile.name,
+                                current_name
+                            )
+                        });
+                        // Shown in the status bar until a switch succeeds
+                        notifications.lock().unwrap().track("NodeSwitch", &result);
+                        match result {
+                            Ok(new_overview_state) => {
+                                blockchain_state.stop();
+                                mempool_state.stop();
+                                logs_state.stop();
+                                overview_state = new_overview_state;
+                                node_history = new_node_history;
+                                // The old connection is closed as all of its subscribers stopped
+                                multiplexer =
+                                    profile.tcp_url.clone().map(SubscriptionMultiplexer::new);
+                                blockchain_state = BlockchainDashboardState::new(
+                                    client.clone(),
+                                    enable_fetch_overview,
+                                    multiplexer.as_ref(),
+                                    node_history.clone(),
+                                );
+                                mempool_state = MempoolDashboardState::new(
+                                    client.clone(),
+                                    multiplexer.as_ref(),
+                                    node_history.clone(),
+                                    Arc::clone(&notifications),
+                                );
+                                logs_state = LogsDashboardState::new(multiplexer.as_ref());
+                                alerts.lock().unwrap().reset();
+                                peers_data.set_enable_overview_data(
+                                    enable_fetch_overview && exporter.is_some(),
+                                );
+                                if let Some(exporter) = &exporter {
+                                    exporter.clear();
+                                }
+                                CURRENT_NODE.store(index, std::sync::atomic::Ordering::SeqCst);
+                                tx.send(SyncRequest::SwitchNode {
+                                    client,
+                                    name: profile.name.clone(),
+                                })
+                                .ok();
+                            }
+                            Err(_) => {
+                                let notifications = Arc::clone(&notifications);
+                                cb_sink
+                                    .send(Box::new(move |siv| {
+                                        notifications.lock().unwrap().update_to_view(siv);
+                                    }))
+                                    .unwrap();
+                            }
+                        }
+                        continue;
+                    }
                     overview_state.accept_event(&e);
                     blockchain_state.accept_event(&e);
                     mempool_state.accept_event(&e);
                     logs_state.accept_event(&e);
                 }
-                if tick_count < refresh_interval {
+                // Each dashboard is updated on its own refresh interval
+                let due: [bool; 5] = std::array::from_fn(|i| {
+                    tick_counts[i] >= config.refresh_interval_of(DashboardTab::ALL[i])
+                });
+                if !due.contains(&true) {
                     std::thread::sleep(Duration::from_millis(1));
                     continue;
-                } else {
-                    // But only update state per 300 millisecond
-                    tick_count = 0;
                 }
+                for (tick_count, due) in tick_counts.iter_mut().zip(due) {
+                    if due {
+                        *tick_count = 0;
+                    }
+                }
+                let current_tab = CURRENT_TAB.load(std::sync::atomic::Ordering::SeqCst);
                 log::debug!("Updating state..");
-                cb_sink
-                    .send(Box::new(|siv| set_loading(siv, true)))
-                    .unwrap();
-                let result = (|| {
-                    anyhow::Ok((
-                        overview_state.update_state()?,
-                        blockchain_state.update_state()?,
-                        mempool_state.update_state()?,
-                        logs_state.update_state()?,
-                    ))
-                })();
-                if let Err(e) = result {
+                if due[current_tab] {
                     cb_sink
-                        .send(Box::new(move |siv| {
-                            siv.add_layer(
-                                Dialog::around(TextView::new(format!(
-                                    "Unable to update state: {:?}",
-                                    e
-                                )))
-                                .button("Ok", |siv| {
-                                    siv.pop_layer();
-                                }),
-                            )
-                        }))
+                        .send(Box::new(|siv| set_loading(siv, true)))
                         .unwrap();
                 }
+                let [
+                    overview_due,
+                    blockchain_due,
+                    mempool_due,
+                    peers_due,
+                    logs_due,
+                ] = due;
+                // Source of notifications, stale panel label and the result of each due state
+                let results = [
+                    (
+                        "OverviewDashboardState",
+                        "Live overview",
+                        overview_due.then(|| overview_state.update_state()),
+                    ),
+                    (
+                        "BlockchainDashboardState",
+                        "Live blockchain",
+                        blockchain_due.then(|| blockchain_state.update_state()),
+                    ),
+                    (
+                        "MempoolDashboardState",
+                        "Live mempool",
+                        mempool_due.then(|| mempool_state.update_state()),
+                    ),
+                    (
+                        "LogsDashboardState",
+                        "Live logs",
+                        logs_due.then(|| logs_state.update_state()),
+                    ),
+                ];
+                let stale_snapshot = {
+                    let mut stale_panels = stale_panels.lock().unwrap();
+                    for (source, panel, result) in results.iter() {
+                        let Some(result) = result else {
+                            continue;
+                        };
+                        notifications.lock().unwrap().track(source, result);
+                        match result {
+                            Ok(()) => stale_panels.remove(panel),
+                            Err(e) => stale_panels.insert(*panel, e.to_string()),
+                        };
+                    }
+                    stale_panels.clone()
+                };
+                let updated: [bool; 4] =
+                    std::array::from_fn(|i| matches!(results[i].2, Some(Ok(()))));
+                for (succeeded, (_, _, result)) in succeeded.iter_mut().zip(&results[..3]) {
+                    if let Some(result) = result {
+                        *succeeded = result.is_ok();
+                    }
+                }
+                // Fetched for alerts, the exporter and the history, the Peers tab has its own data
+                if peers_due {
+                    let result = peers_data.fetch_data_through_client(&overview_state.client);
+                    notifications
+                        .lock()
+                        .unwrap()
+                        .track("PeersDashboardData", &result);
+                    succeeded[3] = result.is_ok();
+                    if succeeded[3] {
+                        node_history.record(history::PEERS, peers_data.connections() as f64);
+                    }
+                }
+                // Failed states keep their last values, which aren't evaluated again
+                let sources = UpdatedSources {
+                    overview: succeeded[0].then_some(&overview_state),
+                    blockchain: succeeded[1].then_some(&blockchain_state),
+                    mempool: succeeded[2].then_some(&mempool_state),
+                    peers: succeeded[3].then_some(&peers_data),
+                };
+                let node_name = &nodes[CURRENT_NODE.load(std::sync::atomic::Ordering::SeqCst)].name;
+                alerts.lock().unwrap().evaluate(
+                    node_name,
+                    &sources.alert_metrics(),
+                    chrono::Local::now(),
+                );
+                if let Some(exporter) = &exporter {
+                    exporter.publish(node_name, collect_metrics(&sources));
+                }
                 let overview_state = overview_state.clone();
                 let blockchain_state = blockchain_state.clone();
                 let mempool_state = mempool_state.clone();
                 let logs_state = logs_state.clone();
+                let alerts = Arc::clone(&alerts);
+                let notifications = Arc::clone(&notifications);
                 cb_sink
                     .send(Box::new(move |siv| {
-                        overview_state.update_to_view(siv);
-                        blockchain_state.update_to_view(siv);
-                        mempool_state.update_to_view(siv);
-                        logs_state.update_to_view(siv);
+                        // Failed states leave their panels as they are, marked stale
+                        if updated[0] {
+                            overview_state.update_to_view(siv);
+                        }
+                        if updated[1] {
+                            blockchain_state.update_to_view(siv);
+                        }
+                        if updated[2] {
+                            mempool_state.update_to_view(siv);
+                        }
+                        if updated[3] {
+                            logs_state.update_to_view(siv);
+                        }
+                        update_stale_panels(siv, &stale_snapshot);
+                        alerts.lock().unwrap().update_to_view(siv);
+                        notifications.lock().unwrap().update_to_view(siv);
                     }))
                     .unwrap();
 
-                tx.send(SyncRequest::RequestSync {
-                    pop_layer_at_end: false,
-                })
-                .ok();
+                // The panel data of the visible tab, on the refresh interval of that tab
+                if due[current_tab] {
+                    tx.send(SyncRequest::RequestSync {
+                        pop_layer_at_end: false,
+                    })
+                    .ok();
+                }
             }
         });
         event_tx
@@ -258,7 +581,22 @@ pub fn start_ckb_tui(
             pop_layer_at_end: false,
         })
         .unwrap();
-    let ui = dashboard(event_sender, &mut siv);
+    {
+        let nodes = Arc::clone(&nodes);
+        let event_sender = event_sender.clone();
+        siv.add_global_callback(keys.switch_node, move |siv| {
+            siv.add_layer(node_selector_dialog(&nodes, event_sender.clone()));
+        });
+    }
+    {
+        let nodes = Arc::clone(&nodes);
+        let event_sender = event_sender.clone();
+        siv.add_global_callback(keys.fleet, move |siv| {
+            let dialog = fleet_summary_dialog(siv, &nodes, event_sender.clone());
+            siv.add_layer(dialog);
+        });
+    }
+    let ui = dashboard(event_sender, &mut siv, &keys, config.default_tab);
 
     siv.set_autorefresh(true);
     siv.add_layer(ui);
@@ -266,3 +604,37 @@ pub fn start_ckb_tui(
     sync_request_tx.send(SyncRequest::Stop).ok();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::NodeProfile;
+
+    #[test]
+    fn parses_node_profile_with_and_without_tcp_url() {
+        assert_eq!(
+            NodeProfile::from_str("mainnet=http://127.0.0.1:8114,127.0.0.1:18114").unwrap(),
+            NodeProfile {
+                name: String::from("mainnet"),
+                rpc_url: String::from("http://127.0.0.1:8114"),
+                tcp_url: Some(String::from("127.0.0.1:18114")),
+            }
+        );
+        assert_eq!(
+            NodeProfile::from_str("testnet=http://127.0.0.1:8124").unwrap(),
+            NodeProfile {
+                name: String::from("testnet"),
+                rpc_url: String::from("http://127.0.0.1:8124"),
+                tcp_url: None,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_node_profile() {
+        assert!(NodeProfile::from_str("http://127.0.0.1:8114").is_err());
+        assert!(NodeProfile::from_str("=http://127.0.0.1:8114").is_err());
+        assert!(NodeProfile::from_str("devnet=").is_err());
+    }
+}
@@ -1,34 +1,135 @@
-use ckb_tui::start_ckb_tui;
+use std::path::PathBuf;
+
+use anyhow::{Context, anyhow};
+use ckb_tui::{
+    NodeProfile,
+    config::{Config, DEFAULT_RPC_URL},
+    exporter::start_headless_exporter,
+    history::default_data_dir,
+    snapshot::{SnapshotFormat, print_snapshot},
+    start_ckb_tui,
+};
 use clap::Parser;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// RPC endpoint of CKB node
-    #[arg(short = 'r', long, default_value_t = String::from("http://127.0.0.1:8114"))]
-    rpc_url: String,
+    /// Config file to use, defaults to `$XDG_CONFIG_HOME/ckb-tui/config.toml` if it exists.
+    /// Flags below override values from the config file
+    #[arg(short = 'c', long)]
+    config: Option<PathBuf>,
+    /// RPC endpoint of CKB node, defaults to http://127.0.0.1:8114
+    #[arg(short = 'r', long)]
+    rpc_url: Option<String>,
     /// TCP endpoint of CKB node, used for receiving pushed transactions data
     /// If not provided, latest transactions and rejected transactions won't be displayed
     #[arg(short, long)]
     tcp_url: Option<String>,
+    /// Named node profile in the form of NAME=RPC_URL[,TCP_URL], can be repeated to monitor several nodes.
+    /// If provided, `--rpc-url` and `--tcp-url` are ignored
+    #[arg(short = 'n', long = "node")]
+    nodes: Vec<NodeProfile>,
     /// Refresh interval of displayed data, defaults to 300ms
-    #[arg(short = 'i', long, default_value_t = 300)]
-    refresh_interval: usize,
+    #[arg(short = 'i', long)]
+    refresh_interval: Option<usize>,
 
     /// Theme file to use for cursive. See https://github.com/gyscos/cursive/blob/main/cursive/examples/assets/style.toml for an example.
     #[arg(long)]
     theme_file: Option<String>,
+
+    /// Directory to keep the metrics history in, defaults to `$XDG_DATA_HOME/ckb-tui`
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+    /// Don't record metrics history to disk, charts only show the current session
+    #[arg(long)]
+    no_history: bool,
+
+    /// Print dashboard data of all nodes once and exit, without starting the TUI
+    #[arg(long)]
+    snapshot: bool,
+    /// Output format of `--snapshot`
+    #[arg(long, value_enum, default_value_t = SnapshotFormat::Json)]
+    snapshot_format: SnapshotFormat,
+
+    /// Serve Prometheus metrics of the monitored node on this address, e.g. `127.0.0.1:9115`
+    #[arg(long)]
+    metrics_listen: Option<String>,
+    /// Only serve metrics of all nodes on `--metrics-listen` (or `metrics_listen` in the config
+    /// file), without starting the TUI. Logs are written to stderr
+    #[arg(long)]
+    headless: bool,
 }
+
+/// Logger of headless mode, which has no cursive debug console to show the logs in
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{} {:<5} {}: {}",
+                chrono::Local::now().to_rfc3339(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
 fn main() -> anyhow::Result<()> {
-    cursive::logger::init();
     let args = Args::parse();
-    start_ckb_tui(
-        &args.rpc_url,
-        args.tcp_url,
-        args.refresh_interval,
-        args.theme_file,
-        false,
-    )?;
+    if args.headless {
+        log::set_logger(&StderrLogger)
+            .map_err(|e| anyhow!("Unable to initialize the logger: {}", e))?;
+        log::set_max_level(log::LevelFilter::Info);
+    } else {
+        cursive::logger::init();
+    }
+    let mut config = Config::load(args.config.as_deref())?;
+    if !args.nodes.is_empty() {
+        config.nodes = args.nodes;
+    } else if args.rpc_url.is_some() || args.tcp_url.is_some() || config.nodes.is_empty() {
+        config.nodes = vec![NodeProfile {
+            name: String::from("default"),
+            rpc_url: args.rpc_url.unwrap_or(DEFAULT_RPC_URL.to_string()),
+            tcp_url: args.tcp_url,
+        }];
+    }
+    if let Some(refresh_interval) = args.refresh_interval {
+        config.refresh_interval = refresh_interval;
+    }
+    if args.theme_file.is_some() {
+        config.theme_file = args.theme_file;
+    }
+    if args.data_dir.is_some() {
+        config.data_dir = args.data_dir;
+    }
+    if args.no_history {
+        config.history = false;
+    }
+    if args.metrics_listen.is_some() {
+        config.metrics_listen = args.metrics_listen;
+    }
+    if config.data_dir.is_none() {
+        config.data_dir = default_data_dir();
+    }
+    config
+        .validate()
+        .with_context(|| anyhow!("Invalid command line arguments"))?;
+    if args.snapshot {
+        print_snapshot(&config.nodes, args.snapshot_format)?;
+    } else if args.headless {
+        start_headless_exporter(config)?;
+    } else {
+        start_ckb_tui(config, false)?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,417 @@
+//! Responses of a healthy testnet node, following the JSON schema of the RPCs of CKB 0.203
+
+use serde_json::{Value, json};
+
+/// Number of the tip block, the 100th block of epoch 10
+pub const TIP_NUMBER: u64 = 10000;
+/// Timestamp of the tip block in milliseconds
+pub const TIP_TIMESTAMP: u64 = 1_700_000_800_000;
+/// `epoch` field of the tip, i.e. epoch 10, block 100 of 1800
+pub const TIP_EPOCH: u64 = 10 | (100 << 24) | (1800 << 40);
+/// Seconds between blocks of the current epoch
+pub const BLOCK_INTERVAL_SECS: u64 = 8;
+
+/// Peer ids of the peers returned by `get_peers`
+pub const PEER_IDS: [&str; 2] = [
+    "QmNQa1FSTXNHmrjjfgUW3Px3Vkke4oKiFWdigWkYSux2Pi",
+    "QmNUVJPjvXxb55spvBuoNKEvGWzoGzzmwLC8MAovVWhMiR",
+];
+
+/// A 32 bytes hash made of `byte`
+pub fn hash(byte: u8) -> String {
+    format!("0x{}", format!("{:02x}", byte).repeat(32))
+}
+
+fn hex(value: u64) -> String {
+    format!("{:#x}", value)
+}
+
+pub fn header(number: u64, timestamp: u64) -> Value {
+    json!({
+        "compact_target": "0x1d08cfa6",
+        "dao": hash(0xda),
+        "epoch": hex(TIP_EPOCH - ((TIP_NUMBER - number.min(TIP_NUMBER)) << 24)),
+        "extra_hash": hash(0),
+        "hash": hash((number % 256) as u8),
+        "nonce": "0x0",
+        "number": hex(number),
+        "parent_hash": hash((number.wrapping_sub(1) % 256) as u8),
+        "proposals_hash": hash(0),
+        "timestamp": hex(timestamp),
+        "transactions_root": hash(0x7e),
+        "version": "0x0",
+    })
+}
+
+pub fn tip_header() -> Value {
+    header(TIP_NUMBER, TIP_TIMESTAMP)
+}
+
+/// Header of the first block of the tip's epoch
+pub fn first_header_in_epoch() -> Value {
+    header(
+        TIP_NUMBER - 100,
+        TIP_TIMESTAMP - 100 * BLOCK_INTERVAL_SECS * 1000,
+    )
+}
+
+pub fn transaction(byte: u8) -> Value {
+    json!({
+        "version": "0x0",
+        "cell_deps": [],
+        "header_deps": [],
+        "inputs": [{
+            "since": "0x0",
+            "previous_output": {"tx_hash": hash(byte.wrapping_add(1)), "index": "0x0"},
+        }],
+        "outputs": [{
+            "capacity": hex(1000 * 100_000_000),
+            "lock": {"code_hash": hash(0x9b), "hash_type": "type", "args": "0x"},
+            "type": null,
+        }],
+        "outputs_data": ["0x"],
+        "witnesses": [],
+        "hash": hash(byte),
+    })
+}
+
+/// Response of `get_transaction`, committed in the tip block
+pub fn transaction_with_status(byte: u8) -> Value {
+    json!({
+        "transaction": transaction(byte),
+        "cycles": "0x100000",
+        "time_added_to_pool": null,
+        "tx_status": {
+            "status": "committed",
+            "block_number": hex(TIP_NUMBER),
+            "block_hash": hash((TIP_NUMBER % 256) as u8),
+            "tx_index": "0x1",
+            "reason": null,
+        },
+        "fee": null,
+        "min_replace_fee": null,
+    })
+}
+
+pub fn local_node_info() -> Value {
+    json!({
+        "version": "0.203.0",
+        "node_id": PEER_IDS[0],
+        "active": true,
+        "addresses": [{"address": format!("/ip4/127.0.0.1/tcp/8115/p2p/{}", PEER_IDS[0]), "score": "0x1"}],
+        "protocols": [{"id": "0x0", "name": "/ckb/ping", "support_versions": ["0.0.1"]}],
+        "connections": "0x2",
+    })
+}
+
+fn remote_node(peer_id: &str, is_outbound: bool, best_known: u64) -> Value {
+    json!({
+        "version": "0.203.0",
+        "node_id": peer_id,
+        "addresses": [{"address": format!("/ip4/10.0.0.1/tcp/8115/p2p/{}", peer_id), "score": "0x1"}],
+        "is_outbound": is_outbound,
+        "connected_duration": "0x3e8",
+        "last_ping_duration": "0x20",
+        "sync_state": {
+            "best_known_header_hash": null,
+            "best_known_header_number": hex(best_known),
+            "last_common_header_hash": null,
+            "last_common_header_number": null,
+            "unknown_header_list_size": "0x0",
+            "inflight_count": "0x0",
+            "can_fetch_count": "0x0",
+        },
+        "protocols": [{"id": "0x0", "version": "0.0.1"}],
+    })
+}
+
+pub fn peers() -> Value {
+    json!([
+        remote_node(PEER_IDS[0], true, TIP_NUMBER),
+        remote_node(PEER_IDS[1], false, TIP_NUMBER - 5),
+    ])
+}
+
+/// Response of the Terminal module, missing on nodes without it enabled
+pub fn overview() -> Value {
+    json!({
+        "sys": {
+            "global": {
+                "total_memory": 16_000_000_000u64,
+                "used_memory": 4_000_000_000u64,
+                "global_cpu_usage": 25.0,
+                "disks": [{
+                    "total_space": 1_000_000_000_000u64,
+                    "available_space": 600_000_000_000u64,
+                    "is_removable": false,
+                }],
+                "networks": [{
+                    "interface_name": "eth0",
+                    "received": 1000,
+                    "total_received": 5_000_000,
+                    "transmitted": 2000,
+                    "total_transmitted": 3_000_000,
+                }],
+            },
+            "cpu_usage": 12.5,
+            "memory": 2_000_000_000u64,
+            "virtual_memory": 4_000_000_000u64,
+            "disk_usage": {
+                "total_written_bytes": 7_000_000,
+                "written_bytes": 7000,
+                "total_read_bytes": 9_000_000,
+                "read_bytes": 9000,
+            },
+        },
+        "cells": {
+            "estimate_live_cells_num": hex(2_000_000),
+            "total_occupied_capacities": hex(500_000_000 * 100_000_000),
+        },
+        "mining": {
+            "difficulty": "0x1000000",
+            "hash_rate": "0x200000",
+        },
+        "pool": {
+            "pending": "0x5",
+            "proposed": "0x3",
+            "orphan": "0x0",
+            "committing": "0x2",
+            "total_recent_reject_num": "0x1",
+            "total_tx_size": "0x1000",
+            "total_tx_cycles": "0x100000",
+            "min_fee_rate": "0x3e8",
+            "min_rbf_rate": "0x5dc",
+            "max_tx_pool_size": "0xaba9500",
+            "last_txs_updated_at": hex(TIP_TIMESTAMP),
+            "tip_hash": hash((TIP_NUMBER % 256) as u8),
+            "tip_number": hex(TIP_NUMBER),
+            "verify_queue_size": "0x0",
+        },
+        "network": {
+            "connected_peers": "0x2",
+            "outbound_peers": "0x1",
+            "inbound_peers": "0x1",
+            "peers": [
+                {
+                    "peer_id": 0,
+                    "is_outbound": true,
+                    "address": format!("/ip4/10.0.0.1/tcp/8115/p2p/{}", PEER_IDS[0]),
+                    "latency_ms": "0x20",
+                },
+                {
+                    "peer_id": 1,
+                    "is_outbound": false,
+                    "address": format!("/ip4/10.0.0.2/tcp/8115/p2p/{}", PEER_IDS[1]),
+                    "latency_ms": "0x40",
+                },
+            ],
+        },
+        "version": "0.203.0 (mock)",
+    })
+}
+
+pub fn blockchain_info() -> Value {
+    json!({
+        "chain": "ckb_testnet",
+        "median_time": hex(TIP_TIMESTAMP - 60_000),
+        "epoch": hex(TIP_EPOCH),
+        "difficulty": "0x1000000",
+        "is_initial_block_download": false,
+        "alerts": [],
+    })
+}
+
+pub fn consensus() -> Value {
+    json!({
+        "id": "ckb_testnet",
+        "genesis_hash": hash(0x10),
+        "dao_type_hash": hash(0x82),
+        "secp256k1_blake160_sighash_all_type_hash": hash(0x9b),
+        "secp256k1_blake160_multisig_all_type_hash": hash(0x5c),
+        "initial_primary_epoch_reward": "0x71afd498d000",
+        "secondary_epoch_reward": "0x37d0c8e28542",
+        "max_uncles_num": "0x2",
+        "orphan_rate_target": {"denom": "0x28", "numer": "0x1"},
+        "epoch_duration_target": "0x3840",
+        "tx_proposal_window": {"closest": "0x2", "farthest": "0xa"},
+        "proposer_reward_ratio": {"denom": "0xa", "numer": "0x4"},
+        "cellbase_maturity": "0x10000000000",
+        "median_time_block_count": "0x25",
+        "max_block_cycles": "0x2540be400",
+        "max_block_bytes": "0x91c08",
+        "block_version": "0x0",
+        "tx_version": "0x0",
+        "type_id_code_hash": "0x00000000000000000000000000000000000000000000000000545950455f4944",
+        "max_block_proposals_limit": "0x5dc",
+        "primary_epoch_reward_halving_interval": "0x2238",
+        "permanent_difficulty_in_dummy": false,
+        "hardfork_features": [{"rfc": "0028", "epoch_number": "0x1526"}],
+        "softforks": {},
+    })
+}
+
+pub fn sync_state() -> Value {
+    json!({
+        "ibd": false,
+        "assume_valid_target_reached": true,
+        "assume_valid_target": hash(0),
+        "min_chain_work": "0x0",
+        "min_chain_work_reached": true,
+        "best_known_block_number": hex(TIP_NUMBER + 100),
+        "best_known_block_timestamp": hex(TIP_TIMESTAMP + 800_000),
+        "orphan_blocks_count": "0x0",
+        "orphan_blocks_size": "0x0",
+        "inflight_blocks_count": "0x0",
+        "unverified_tip_number": hex(TIP_NUMBER),
+        "unverified_tip_hash": hash((TIP_NUMBER % 256) as u8),
+        "tip_number": hex(TIP_NUMBER),
+        "tip_hash": hash((TIP_NUMBER % 256) as u8),
+        "fast_time": "0x0",
+        "normal_time": "0x0",
+        "low_time": "0x0",
+    })
+}
+
+pub fn fee_rate_statistics() -> Value {
+    json!({"mean": "0x5dc", "median": "0x3e8"})
+}
+
+/// One page of live cells of the indexer, without a next page. The second cell carries a
+/// type script and 16 bytes of data
+pub fn cells() -> Value {
+    json!({
+        "objects": [{
+            "output": {
+                "capacity": hex(1000 * 100_000_000),
+                "lock": {"code_hash": hash(0x9b), "hash_type": "type", "args": "0x"},
+                "type": null,
+            },
+            "output_data": "0x",
+            "out_point": {"tx_hash": hash(0x21), "index": "0x0"},
+            "block_number": hex(TIP_NUMBER - 1),
+            "tx_index": "0x1",
+        }, {
+            "output": {
+                "capacity": hex(142 * 100_000_000),
+                "lock": {"code_hash": hash(0x9b), "hash_type": "type", "args": "0x"},
+                "type": {"code_hash": hash(0x5e), "hash_type": "type", "args": hash(0x11)},
+            },
+            "output_data": format!("0x{}", "00".repeat(16)),
+            "out_point": {"tx_hash": hash(0x22), "index": "0x1"},
+            "block_number": hex(TIP_NUMBER),
+            "tx_index": "0x1",
+        }],
+        "last_cursor": "0x",
+    })
+}
+
+/// One page of transactions of the indexer grouped by transaction, without a next page. The
+/// first transaction creates a cell and the second one consumes one
+pub fn transactions() -> Value {
+    json!({
+        "objects": [{
+            "tx_hash": hash(0x31),
+            "block_number": hex(TIP_NUMBER),
+            "tx_index": "0x1",
+            "cells": [["output", "0x0"]],
+        }, {
+            "tx_hash": hash(0x32),
+            "block_number": hex(TIP_NUMBER - 1),
+            "tx_index": "0x2",
+            "cells": [["input", "0x0"]],
+        }],
+        "last_cursor": "0x",
+    })
+}
+
+/// Total capacity of [`cells`]
+pub fn cells_capacity() -> Value {
+    json!({
+        "capacity": hex(1142 * 100_000_000),
+        "block_hash": hash((TIP_NUMBER % 256) as u8),
+        "block_number": hex(TIP_NUMBER),
+    })
+}
+
+pub fn tx_pool_info() -> Value {
+    json!({
+        "tip_hash": hash((TIP_NUMBER % 256) as u8),
+        "tip_number": hex(TIP_NUMBER),
+        "pending": "0x5",
+        "proposed": "0x2",
+        "orphan": "0x0",
+        "total_tx_size": "0x1000",
+        "total_tx_cycles": "0x800000",
+        "min_fee_rate": "0x3e8",
+        "min_rbf_rate": "0x5dc",
+        "last_txs_updated_at": hex(TIP_TIMESTAMP),
+        "tx_size_limit": "0x7d000",
+        "max_tx_pool_size": "0xa000000",
+        "verify_queue_size": "0x0",
+    })
+}
+
+/// Fixtures served by [`super::MockNode::start`]
+pub fn default_rpc_fixtures() -> Vec<(&'static str, Value)> {
+    vec![
+        ("local_node_info", local_node_info()),
+        ("get_overview", overview()),
+        ("get_peers", peers()),
+        ("get_cells", cells()),
+        ("get_cells_capacity", cells_capacity()),
+        ("get_transactions", transactions()),
+        ("get_transaction", transaction_with_status(0x31)),
+        ("get_consensus", consensus()),
+        ("get_blockchain_info", blockchain_info()),
+        ("get_tip_header", tip_header()),
+        ("get_header_by_number", first_header_in_epoch()),
+        ("sync_state", sync_state()),
+        ("get_fee_rate_statistics", fee_rate_statistics()),
+        ("tx_pool_info", tx_pool_info()),
+        ("get_block_by_number", new_tip_block(TIP_NUMBER)),
+    ]
+}
+
+/// Message of the `new_tip_block` topic
+pub fn new_tip_block(number: u64) -> Value {
+    json!({
+        "header": header(number, TIP_TIMESTAMP + (number - TIP_NUMBER) * BLOCK_INTERVAL_SECS * 1000),
+        "uncles": [],
+        "transactions": [transaction((number % 256) as u8)],
+        "proposals": [],
+    })
+}
+
+fn pool_transaction_entry(byte: u8) -> Value {
+    json!({
+        "transaction": transaction(byte),
+        "cycles": "0x100000",
+        "size": "0x200",
+        "fee": "0x400",
+        "timestamp": hex(TIP_TIMESTAMP),
+    })
+}
+
+/// Message of the `new_transaction` topic
+pub fn new_transaction(byte: u8) -> Value {
+    pool_transaction_entry(byte)
+}
+
+/// Message of the `rejected_transaction` topic, `reason` is the type of the rejection, e.g.
+/// `LowFeeRate`
+pub fn rejected_transaction(byte: u8, reason: &str) -> Value {
+    json!([
+        pool_transaction_entry(byte),
+        {"type": reason, "description": format!("Rejected by the mock node: {}", reason)},
+    ])
+}
+
+/// Message of the `log` topic, `level` is one of `trace`, `debug`, `info`, `warn` and `error`
+pub fn log(level: &str, message: &str) -> Value {
+    json!({
+        "message": message,
+        "level": level,
+        "target": "ckb_chain",
+        "date": "2025-01-01 00:00:00.000 +00:00",
+    })
+}
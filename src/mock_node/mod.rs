@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, anyhow};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+pub mod fixtures;
+
+/// Code of the error returned for methods without a fixture, the same as a node without the
+/// RPC module of the method enabled
+const METHOD_NOT_FOUND: i64 = -32601;
+
+struct PubsubSubscriber {
+    topic: String,
+    subscription: String,
+    stream: TcpStream,
+}
+
+#[derive(Default)]
+struct MockNodeState {
+    /// Result or error message responded to each method
+    fixtures: HashMap<String, Result<Value, String>>,
+    /// Number of calls received of each method
+    calls: HashMap<String, usize>,
+    subscribers: Vec<PubsubSubscriber>,
+    next_subscription: u64,
+}
+
+/// An in-process fake CKB node for tests, serving scripted fixtures over JSON-RPC HTTP and
+/// publishing scripted messages over a TCP pubsub endpoint. Methods without a fixture fail like
+/// the ones of a disabled RPC module
+pub struct MockNode {
+    rpc_addr: SocketAddr,
+    tcp_addr: SocketAddr,
+    state: Arc<Mutex<MockNodeState>>,
+}
+
+impl MockNode {
+    /// Starts a node serving the fixtures of [`fixtures::default_rpc_fixtures`]
+    pub fn start() -> Self {
+        let node = Self::empty();
+        for (method, result) in fixtures::default_rpc_fixtures() {
+            node.set_result(method, result);
+        }
+        node
+    }
+
+    /// Starts a node without any fixtures
+    pub fn empty() -> Self {
+        let state = Arc::new(Mutex::new(MockNodeState::default()));
+        let rpc_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let node = Self {
+            rpc_addr: rpc_listener.local_addr().unwrap(),
+            tcp_addr: tcp_listener.local_addr().unwrap(),
+            state,
+        };
+        {
+            let state = Arc::clone(&node.state);
+            std::thread::spawn(move || {
+                for stream in rpc_listener.incoming().flatten() {
+                    let state = Arc::clone(&state);
+                    std::thread::spawn(move || {
+                        if let Err(e) = handle_rpc_connection(&state, stream) {
+                            log::debug!("Mock node failed to serve RPC request: {:?}", e);
+                        }
+                    });
+                }
+            });
+        }
+        {
+            let state = Arc::clone(&node.state);
+            std::thread::spawn(move || {
+                for stream in tcp_listener.incoming().flatten() {
+                    let state = Arc::clone(&state);
+                    std::thread::spawn(move || {
+                        if let Err(e) = handle_pubsub_connection(&state, stream) {
+                            log::debug!("Mock node closed pubsub connection: {:?}", e);
+                        }
+                    });
+                }
+            });
+        }
+        node
+    }
+
+    /// URL of the JSON-RPC endpoint, like `rpc.listen_address` of a node
+    pub fn rpc_url(&self) -> String {
+        format!("http://{}", self.rpc_addr)
+    }
+
+    /// Address of the pubsub endpoint, like `rpc.tcp_listen_address` of a node
+    pub fn tcp_url(&self) -> String {
+        self.tcp_addr.to_string()
+    }
+
+    /// Responds `result` to the following calls of `method`
+    pub fn set_result(&self, method: &str, result: impl Serialize) {
+        self.state.lock().unwrap().fixtures.insert(
+            method.to_string(),
+            Ok(serde_json::to_value(result).unwrap()),
+        );
+    }
+
+    /// Responds an error with `message` to the following calls of `method`
+    pub fn set_error(&self, method: &str, message: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .fixtures
+            .insert(method.to_string(), Err(message.to_string()));
+    }
+
+    /// Makes `method` unsupported, e.g. `get_overview` of a node without the Terminal module
+    pub fn remove(&self, method: &str) {
+        self.state.lock().unwrap().fixtures.remove(method);
+    }
+
+    /// Number of calls of `method` received so far, including the ones in batches
+    pub fn calls(&self, method: &str) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .get(method)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Waits until a client subscribed `topic`
+    pub fn wait_for_subscriber(&self, topic: &str) {
+        wait_until(&format!("a subscriber of {}", topic), || {
+            self.state
+                .lock()
+                .unwrap()
+                .subscribers
+                .iter()
+                .any(|x| x.topic == topic)
+        });
+    }
+
+    /// Publishes `message` to the subscribers of `topic`, returning the number of subscribers
+    /// it was sent to
+    pub fn publish(&self, topic: &str, message: impl Serialize) -> usize {
+        let notification = |subscription: &str| {
+            // Like the node, the result is the message encoded as a JSON string
+            json!({
+                "jsonrpc": "2.0",
+                "method": "subscribe",
+                "params": {
+                    "result": serde_json::to_string(&message).unwrap(),
+                    "subscription": subscription,
+                },
+            })
+        };
+        let mut guard = self.state.lock().unwrap();
+        let mut sent = 0;
+        guard.subscribers.retain_mut(|subscriber| {
+            if subscriber.topic != topic {
+                return true;
+            }
+            let line = format!("{}\n", notification(&subscriber.subscription));
+            let ok = subscriber.stream.write_all(line.as_bytes()).is_ok();
+            if ok {
+                sent += 1;
+            }
+            ok
+        });
+        sent
+    }
+
+    /// Closes all pubsub connections, like a restarting node
+    pub fn disconnect_subscribers(&self) {
+        for subscriber in self.state.lock().unwrap().subscribers.drain(..) {
+            subscriber.stream.shutdown(std::net::Shutdown::Both).ok();
+        }
+    }
+}
+
+/// Polls `condition` until it holds, panics if it doesn't within 5 seconds
+pub fn wait_until(what: &str, mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+impl MockNodeState {
+    fn respond(&mut self, call: &Value) -> Value {
+        let id = call["id"].clone();
+        let Some(method) = call["method"].as_str() else {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": -32600, "message": "Invalid request"},
+            });
+        };
+        *self.calls.entry(method.to_string()).or_default() += 1;
+        match self.fixtures.get(method) {
+            Some(Ok(result)) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Some(Err(message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": -32000, "message": message},
+            }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": METHOD_NOT_FOUND, "message": "Method not found"},
+            }),
+        }
+    }
+}
+
+fn handle_rpc_connection(state: &Mutex<MockNodeState>, stream: TcpStream) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse()?;
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let request = serde_json::from_slice::<Value>(&body)
+        .with_context(|| anyhow!("Bad request: {}", String::from_utf8_lossy(&body)))?;
+    let response = {
+        let mut guard = state.lock().unwrap();
+        match &request {
+            Value::Array(calls) => Value::Array(calls.iter().map(|x| guard.respond(x)).collect()),
+            call => guard.respond(call),
+        }
+    }
+    .to_string();
+    let mut writer = &stream;
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.len(),
+        response
+    )?;
+    Ok(())
+}
+
+/// Serves newline delimited JSON-RPC requests of one pubsub connection
+fn handle_pubsub_connection(state: &Mutex<MockNodeState>, stream: TcpStream) -> anyhow::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    for line in reader.lines() {
+        let request = serde_json::from_str::<Value>(&line?)?;
+        // Responded under the lock, so that no message is published before the subscription
+        let mut guard = state.lock().unwrap();
+        let result = match request["method"].as_str() {
+            Some("subscribe") => {
+                let topic = request["params"][0]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Subscribe without topic: {}", request))?;
+                let subscription = format!("{:#x}", guard.next_subscription);
+                guard.next_subscription += 1;
+                guard.subscribers.push(PubsubSubscriber {
+                    topic: topic.to_string(),
+                    subscription: subscription.clone(),
+                    stream: stream.try_clone()?,
+                });
+                json!(subscription)
+            }
+            Some("unsubscribe") => {
+                let subscription = request["params"][0].as_str().unwrap_or_default();
+                guard.subscribers.retain(|x| x.subscription != subscription);
+                json!(true)
+            }
+            _ => json!(null),
+        };
+        let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": result});
+        let mut writer = &stream;
+        writeln!(writer, "{}", response)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::MockNode;
+    use crate::utils::rpc_cache::CachedRpcClient;
+
+    #[test]
+    fn serves_fixtures_batches_and_unsupported_methods() {
+        let node = MockNode::start();
+        let client = CachedRpcClient::new(&node.rpc_url());
+        assert_eq!(client.inner().local_node_info().unwrap().version, "0.203.0");
+        assert_eq!(client.get_tip_header().unwrap().inner.number.value(), 10000);
+
+        node.remove("get_overview");
+        node.set_error("sync_state", "node is shutting down");
+        let results = client
+            .batch(&[
+                ("get_overview", json!(null)),
+                ("sync_state", json!(null)),
+                ("get_peers", json!(null)),
+            ])
+            .unwrap();
+        assert!(
+            results[0]
+                .as_ref()
+                .unwrap_err()
+                .contains("Method not found")
+        );
+        assert!(results[1].as_ref().unwrap_err().contains("shutting down"));
+        assert_eq!(results[2].as_ref().unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(node.calls("get_peers"), 1);
+    }
+}
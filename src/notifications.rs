@@ -0,0 +1,118 @@
+use chrono::{DateTime, Local};
+
+/// Number of distinct notifications kept, resolved ones are dropped first
+const MAX_NOTIFICATIONS: usize = 200;
+
+/// An error reported by a source, repeated occurrences of the same error are merged
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub source: String,
+    pub message: String,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+    pub count: usize,
+    /// Whether the source is still failing, i.e. it hasn't succeeded since the last occurrence
+    pub active: bool,
+}
+
+/// Collects errors of the update loops instead of showing a dialog for every failure
+#[derive(Default)]
+pub struct NotificationCenter {
+    notifications: Vec<Notification>,
+}
+
+impl NotificationCenter {
+    /// Records an error of `source`, merging it with the same error reported before
+    pub fn report(&mut self, source: &str, error: &anyhow::Error, now: DateTime<Local>) {
+        let message = format!("{:#}", error);
+        if let Some(notification) = self
+            .notifications
+            .iter_mut()
+            .find(|x| x.source == source && x.message == message)
+        {
+            notification.count += 1;
+            notification.last_seen = now;
+            notification.active = true;
+            return;
+        }
+        log::warn!("Error of {}: {}", source, message);
+        self.notifications.push(Notification {
+            source: source.to_string(),
+            message,
+            first_seen: now,
+            last_seen: now,
+            count: 1,
+            active: true,
+        });
+        if self.notifications.len() > MAX_NOTIFICATIONS {
+            let index = self
+                .notifications
+                .iter()
+                .position(|x| !x.active)
+                .unwrap_or(0);
+            self.notifications.remove(index);
+        }
+    }
+
+    /// Marks the errors of `source` as resolved after it succeeded
+    pub fn resolve(&mut self, source: &str) {
+        for notification in self.notifications.iter_mut() {
+            if notification.source == source {
+                notification.active = false;
+            }
+        }
+    }
+
+    /// Records the result of one update of `source`
+    pub fn track<T>(&mut self, source: &str, result: &anyhow::Result<T>) {
+        match result {
+            Ok(_) => self.resolve(source),
+            Err(e) => self.report(source, e, Local::now()),
+        }
+    }
+
+    /// Errors of sources that are still failing
+    pub fn active(&self) -> Vec<&Notification> {
+        self.notifications.iter().filter(|x| x.active).collect()
+    }
+
+    /// All kept notifications, oldest first
+    pub fn history(&self) -> &[Notification] {
+        &self.notifications
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use chrono::{Duration, Local};
+
+    use super::NotificationCenter;
+
+    #[test]
+    fn merges_repeated_errors_and_resolves_per_source() {
+        let mut center = NotificationCenter::default();
+        let start = Local::now();
+        let error = anyhow!("connection refused").context("Unable to get tip header");
+        center.report("OverviewDashboardState", &error, start);
+        center.report(
+            "OverviewDashboardState",
+            &error,
+            start + Duration::seconds(5),
+        );
+        center.report("MempoolDashboardState", &anyhow!("timeout"), start);
+        assert_eq!(center.history().len(), 2);
+        let overview = &center.history()[0];
+        assert_eq!(overview.count, 2);
+        assert_eq!(
+            overview.message,
+            "Unable to get tip header: connection refused"
+        );
+        assert_eq!(overview.last_seen, start + Duration::seconds(5));
+
+        center.resolve("OverviewDashboardState");
+        let active = center.active();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].source, "MempoolDashboardState");
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::bail;
+use serde::Serialize;
+
+use crate::{
+    NodeProfile,
+    components::{
+        DashboardData,
+        dashboard::{
+            GeneralDashboardData, blockchain::BlockchainDashboardData,
+            mempool::MempoolDashboardData, overview::OverviewDashboardData,
+            peers::PeersDashboardData,
+        },
+        is_overview_supported,
+    },
+    utils::rpc_cache::CachedRpcClient,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SnapshotFormat {
+    Json,
+    Yaml,
+}
+
+/// Data of all dashboards of one node, fetched through the same codepath as the TUI
+#[derive(Clone, Serialize)]
+pub struct DashboardSnapshot {
+    pub general: GeneralDashboardData,
+    pub overview: OverviewDashboardData,
+    pub blockchain: BlockchainDashboardData,
+    pub mempool: MempoolDashboardData,
+    pub peers: PeersDashboardData,
+}
+
+fn fetch_dashboard_data<T: DashboardData + Default>(
+    client: &CachedRpcClient,
+    enable_fetch_overview: bool,
+) -> anyhow::Result<T> {
+    let mut data = T::default();
+    data.set_enable_overview_data(enable_fetch_overview);
+    data.fetch_data_through_client(client)?;
+    Ok(data)
+}
+
+impl DashboardSnapshot {
+    pub fn fetch(client: &CachedRpcClient, node_name: &str) -> anyhow::Result<Self> {
+        let enable_fetch_overview = is_overview_supported(client);
+        let mut general: GeneralDashboardData =
+            fetch_dashboard_data(client, enable_fetch_overview)?;
+        general.node_name = node_name.to_string();
+        Ok(Self {
+            general,
+            overview: fetch_dashboard_data(client, enable_fetch_overview)?,
+            blockchain: fetch_dashboard_data(client, enable_fetch_overview)?,
+            mempool: fetch_dashboard_data(client, enable_fetch_overview)?,
+            peers: fetch_dashboard_data(client, enable_fetch_overview)?,
+        })
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct NodeSnapshot {
+    pub name: String,
+    pub rpc_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<DashboardSnapshot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Snapshot {
+    pub taken_at: String,
+    pub nodes: Vec<NodeSnapshot>,
+}
+
+impl Snapshot {
+    pub fn take(nodes: &[NodeProfile]) -> Self {
+        Self {
+            taken_at: chrono::Local::now().to_rfc3339(),
+            nodes: nodes
+                .iter()
+                .map(|profile| {
+                    let client = CachedRpcClient::new(&profile.rpc_url);
+                    let (data, error) = match DashboardSnapshot::fetch(&client, &profile.name) {
+                        Ok(data) => (Some(data), None),
+                        Err(e) => (None, Some(format!("{:#}", e))),
+                    };
+                    NodeSnapshot {
+                        name: profile.name.clone(),
+                        rpc_url: profile.rpc_url.clone(),
+                        data,
+                        error,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_string(&self, format: SnapshotFormat) -> anyhow::Result<String> {
+        Ok(match format {
+            SnapshotFormat::Json => serde_json::to_string_pretty(self)?,
+            SnapshotFormat::Yaml => serde_yaml::to_string(self)?,
+        })
+    }
+}
+
+/// Fetch dashboard data of all nodes once and print it to stdout, without starting the TUI.
+/// Fails after printing if any of the nodes couldn't be snapshotted.
+pub fn print_snapshot(nodes: &[NodeProfile], format: SnapshotFormat) -> anyhow::Result<()> {
+    let snapshot = Snapshot::take(nodes);
+    println!("{}", snapshot.to_string(format)?);
+    let failed_nodes = snapshot
+        .nodes
+        .iter()
+        .filter(|x| x.error.is_some())
+        .map(|x| x.name.as_str())
+        .collect::<Vec<_>>();
+    if !failed_nodes.is_empty() {
+        bail!("Unable to take snapshot of: {}", failed_nodes.join(", "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Snapshot, SnapshotFormat, print_snapshot};
+    use crate::{NodeProfile, mock_node::MockNode};
+
+    fn profile(name: &str, node: &MockNode) -> NodeProfile {
+        NodeProfile {
+            name: name.to_string(),
+            rpc_url: node.rpc_url(),
+            tcp_url: None,
+        }
+    }
+
+    #[test]
+    fn reports_failed_nodes_without_aborting_others() {
+        let node = MockNode::start();
+        // Answers every request with an error
+        let down = MockNode::empty();
+        let nodes = [profile("a", &down), profile("b", &node)];
+        let snapshot = Snapshot::take(&nodes);
+        let value = serde_json::to_value(&snapshot).unwrap();
+        assert!(value["taken_at"].is_string());
+        let [failed, ok] = value["nodes"].as_array().unwrap().as_slice() else {
+            panic!("expected a snapshot of each node");
+        };
+
+        assert_eq!(failed["name"], "a");
+        assert!(failed["error"].is_string());
+        assert!(failed.get("data").is_none());
+
+        assert_eq!(ok["name"], "b");
+        assert_eq!(ok["rpc_url"], node.rpc_url());
+        assert!(ok.get("error").is_none());
+        let data = ok["data"].as_object().unwrap();
+        assert_eq!(
+            data.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["blockchain", "general", "mempool", "overview", "peers"]
+        );
+        assert_eq!(data["general"]["node_name"], "b");
+
+        let yaml = snapshot.to_string(SnapshotFormat::Yaml).unwrap();
+        assert!(yaml.contains("name: b"));
+
+        let error = print_snapshot(&nodes, SnapshotFormat::Json).unwrap_err();
+        assert_eq!(error.to_string(), "Unable to take snapshot of: a");
+    }
+}
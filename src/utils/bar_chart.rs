@@ -37,10 +37,16 @@ impl SimpleBarChart {
 impl View for SimpleBarChart {
     fn draw(&self, printer: &cursive::Printer) {
         let mut str = String::default();
+        let range = self.max_value - self.diff_value.unwrap_or_default();
         for item in self.data.iter() {
-            let item = (*item - self.diff_value.unwrap_or_default())
-                / (self.max_value - self.diff_value.unwrap_or_default());
-            let char = if item == STEP * 0.0 {
+            // A degenerate range would divide by zero, such charts are left blank
+            let item = if range > 0.0 {
+                (*item - self.diff_value.unwrap_or_default()) / range
+            } else {
+                0.0
+            };
+            // Values below the diff value (e.g. gaps in history) are left blank
+            let char = if item <= STEP * 0.0 {
                 ' '
             } else if item > STEP * 0.0 && item <= STEP * 1.0 {
                 '▁'
@@ -67,3 +73,39 @@ impl View for SimpleBarChart {
         (self.data.len(), 1).into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SimpleBarChart;
+    use crate::utils::puppet::PuppetScreen;
+
+    fn draw(chart: SimpleBarChart) -> String {
+        let mut screen = PuppetScreen::new(20, 1);
+        screen.siv().add_fullscreen_layer(chart);
+        screen.render().remove(0)
+    }
+
+    #[test]
+    fn maps_values_to_eighth_blocks() {
+        let chart =
+            SimpleBarChart::new(&[0.0, 0.1, 0.25, 0.3, 0.5, 0.6, 0.75, 0.8, 1.0, 2.0]).unwrap();
+        assert_eq!(draw(chart), " ▁▂▃▄▅▆▇██");
+
+        // Values are scaled between the diff value and the max value
+        let mut chart = SimpleBarChart::new(&[5.0, 10.0, 15.0, 20.0]).unwrap();
+        chart.set_max_value(20.0);
+        chart.set_diff_value(Some(10.0));
+        assert_eq!(draw(chart), "  ▄█");
+
+        // Flat or empty series don't draw NaN as full bars
+        let mut chart = SimpleBarChart::new(&[0.0, 0.0]).unwrap();
+        chart.set_max_value(0.0);
+        assert_eq!(draw(chart), "");
+        let mut chart = SimpleBarChart::new(&[5.0, 5.0]).unwrap();
+        chart.set_max_value(5.0);
+        chart.set_diff_value(Some(5.0));
+        assert_eq!(draw(chart), "");
+
+        assert!(SimpleBarChart::new(&[1.0, -1.0]).is_err());
+    }
+}
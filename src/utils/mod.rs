@@ -2,6 +2,10 @@ use number_prefix::NumberPrefix;
 use tokio::net::TcpStream;
 
 pub mod bar_chart;
+#[cfg(test)]
+pub mod puppet;
+pub mod rpc_cache;
+pub mod subscription;
 
 #[macro_export]
 macro_rules! update_text {
@@ -37,6 +41,10 @@ pub fn shorten_hex(hex: impl AsRef<str>, keep_prefix: usize, keep_suffix: usize)
     )
 }
 
+pub fn shannons_to_ckb_string(shannons: u64) -> String {
+    format!("{} CKB", shannons as f64 / 1e8)
+}
+
 pub fn hash_rate_to_string(hash_rate: f64) -> String {
     match NumberPrefix::decimal(hash_rate) {
         NumberPrefix::Standalone(s) => format!("{} H/s", s),
@@ -0,0 +1,71 @@
+use cursive::{
+    Cursive, CursiveRunner, Vec2,
+    backends::puppet::{Backend, observed::ObservedScreen},
+    event::Event,
+    reexports::crossbeam_channel::{Receiver, Sender},
+};
+
+/// A cursive session drawing to an in-memory screen, for testing views without a terminal
+pub struct PuppetScreen {
+    runner: CursiveRunner<Cursive>,
+    input: Sender<Option<Event>>,
+    output: Receiver<ObservedScreen>,
+}
+
+impl PuppetScreen {
+    pub fn new(width: usize, height: usize) -> Self {
+        let backend = Backend::init(Some(Vec2::new(width, height)));
+        let input = backend.input();
+        let output = backend.stream();
+        Self {
+            runner: Cursive::new().into_runner(backend),
+            input,
+            output,
+        }
+    }
+
+    pub fn siv(&mut self) -> &mut Cursive {
+        &mut self.runner
+    }
+
+    /// Sends an event, e.g. a key press, and processes it along with pending callbacks
+    pub fn press(&mut self, event: impl Into<Event>) {
+        self.input.send(Some(event.into())).unwrap();
+        self.runner.process_events();
+    }
+
+    /// Types `text` into the focused view
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.press(c);
+        }
+    }
+
+    /// Draws the screen and returns its lines, without trailing spaces
+    pub fn render(&mut self) -> Vec<String> {
+        self.runner.process_events();
+        self.runner.refresh();
+        let screen = self
+            .output
+            .try_iter()
+            .last()
+            .expect("refresh should draw the screen");
+        let size = screen.size();
+        (0..size.y)
+            .map(|y| {
+                let line = (0..size.x)
+                    .filter_map(|x| match &screen[Vec2::new(x, y)] {
+                        Some(cell) => cell.letter.as_option().map(String::as_str),
+                        None => Some(" "),
+                    })
+                    .collect::<String>();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// Whether any line of the drawn screen contains `text`
+    pub fn contains(&mut self, text: &str) -> bool {
+        self.render().iter().any(|x| x.contains(text))
+    }
+}
@@ -0,0 +1,379 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail};
+use ckb_jsonrpc_types::{
+    BlockNumber, ChainInfo, Consensus, FeeRateStatistics, HeaderView, Overview, RemoteNode,
+    SyncState, Uint64,
+};
+use ckb_sdk::CkbRpcClient;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+/// Responses of volatile methods are only shared by the requests of one refresh cycle
+const VOLATILE_TTL: Duration = Duration::from_millis(200);
+/// Expired responses are dropped once the cache holds more entries than this
+const MAX_ENTRIES: usize = 256;
+
+/// How long a response of `method` is reused, `None` if it's never cached
+fn ttl_of(method: &str) -> Option<Duration> {
+    match method {
+        "get_overview"
+        | "get_tip_header"
+        | "sync_state"
+        | "get_peers"
+        | "get_fee_rate_statistics" => Some(VOLATILE_TTL),
+        "get_blockchain_info" => Some(Duration::from_secs(60)),
+        // Fixed for a running node
+        "get_consensus" => Some(Duration::from_secs(600)),
+        // Only requested for the first block of the epoch, which only changes in deep reorgs
+        "get_header_by_number" => Some(Duration::from_secs(600)),
+        _ => None,
+    }
+}
+
+/// Outcome of a request shared with the requests waiting for it
+struct PendingCall {
+    result: Mutex<Option<Result<Value, String>>>,
+    ready: Condvar,
+}
+
+impl PendingCall {
+    fn wait(&self) -> Result<Value, String> {
+        let mut guard = self.result.lock().unwrap();
+        loop {
+            if let Some(result) = guard.as_ref() {
+                return result.clone();
+            }
+            guard = self.ready.wait(guard).unwrap();
+        }
+    }
+
+    fn finish(&self, result: Result<Value, String>) {
+        *self.result.lock().unwrap() = Some(result);
+        self.ready.notify_all();
+    }
+}
+
+enum CacheEntry {
+    Ready { value: Value, expires_at: Instant },
+    Pending(Arc<PendingCall>),
+}
+
+type Cache = Arc<Mutex<HashMap<String, CacheEntry>>>;
+
+/// A request started by `lookup`. If it's dropped unfinished, e.g. when the caller fetching it
+/// panicked, the requests waiting for it get an error instead of waiting forever
+struct Fetch {
+    cache: Cache,
+    key: String,
+    pending: Arc<PendingCall>,
+    finished: bool,
+}
+
+impl Fetch {
+    /// Stores the response and hands it to the waiting requests, failed requests aren't cached
+    fn finish(mut self, ttl: Duration, result: Result<Value, String>) {
+        {
+            let mut guard = self.cache.lock().unwrap();
+            match &result {
+                Ok(value) => {
+                    guard.insert(
+                        self.key.clone(),
+                        CacheEntry::Ready {
+                            value: value.clone(),
+                            expires_at: Instant::now() + ttl,
+                        },
+                    );
+                }
+                Err(_) => {
+                    guard.remove(&self.key);
+                }
+            }
+        }
+        self.pending.finish(result);
+        self.finished = true;
+    }
+}
+
+impl Drop for Fetch {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Ok(mut guard) = self.cache.lock()
+            && let Some(CacheEntry::Pending(pending)) = guard.get(&self.key)
+            && Arc::ptr_eq(pending, &self.pending)
+        {
+            guard.remove(&self.key);
+        }
+        self.pending
+            .finish(Err(format!("Request of {} was abandoned", self.key)));
+    }
+}
+
+enum Lookup {
+    Hit(Value),
+    Wait(Arc<PendingCall>),
+    Fetch(Fetch),
+}
+
+/// A client of one node whose responses are shared by all panels. Requests of the same method
+/// and params are answered from the cache within the TTL of the method, and concurrent requests
+/// are coalesced into one
+#[derive(Clone)]
+pub struct CachedRpcClient {
+    client: CkbRpcClient,
+    url: String,
+    http: reqwest::blocking::Client,
+    cache: Cache,
+}
+
+impl CachedRpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: CkbRpcClient::new(url),
+            url: url.to_string(),
+            http: reqwest::blocking::Client::new(),
+            cache: Default::default(),
+        }
+    }
+
+    /// The underlying client, for requests that are never cached
+    pub fn inner(&self) -> &CkbRpcClient {
+        &self.client
+    }
+
+    fn cache_key(method: &str, params: &Value) -> String {
+        format!("{}:{}", method, params)
+    }
+
+    fn lookup(&self, key: &str) -> Lookup {
+        let mut guard = self.cache.lock().unwrap();
+        match guard.get(key) {
+            Some(CacheEntry::Ready { value, expires_at }) if *expires_at > Instant::now() => {
+                return Lookup::Hit(value.clone());
+            }
+            Some(CacheEntry::Pending(pending)) => return Lookup::Wait(Arc::clone(pending)),
+            _ => {}
+        }
+        if guard.len() > MAX_ENTRIES {
+            let now = Instant::now();
+            guard.retain(|_, entry| match entry {
+                CacheEntry::Ready { expires_at, .. } => *expires_at > now,
+                CacheEntry::Pending(_) => true,
+            });
+        }
+        let pending = Arc::new(PendingCall {
+            result: Mutex::new(None),
+            ready: Condvar::new(),
+        });
+        guard.insert(key.to_string(), CacheEntry::Pending(Arc::clone(&pending)));
+        Lookup::Fetch(Fetch {
+            cache: Arc::clone(&self.cache),
+            key: key.to_string(),
+            pending,
+            finished: false,
+        })
+    }
+
+    /// Calls `method`, answering from the cache if the method is cacheable
+    pub fn call<P: Serialize, R: DeserializeOwned + Send + 'static>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> anyhow::Result<R> {
+        let params = serde_json::to_value(params)?;
+        let Some(ttl) = ttl_of(method) else {
+            return Ok(self.client.post::<Value, R>(method, params)?);
+        };
+        let key = Self::cache_key(method, &params);
+        let value = match self.lookup(&key) {
+            Lookup::Hit(value) => value,
+            Lookup::Wait(pending) => pending.wait().map_err(|e| anyhow!(e))?,
+            Lookup::Fetch(fetch) => {
+                let result = self
+                    .client
+                    .post::<Value, Value>(method, params)
+                    .map_err(|e| e.to_string());
+                fetch.finish(ttl, result.clone());
+                result.map_err(|e| anyhow!(e))?
+            }
+        };
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Sends the calls as one JSON-RPC batch request, returning the result of each call
+    pub fn batch(&self, calls: &[(&str, Value)]) -> anyhow::Result<Vec<Result<Value, String>>> {
+        let request = calls
+            .iter()
+            .enumerate()
+            .map(|(id, (method, params))| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": method,
+                    "params": if params.is_null() { json!([]) } else { params.clone() },
+                })
+            })
+            .collect::<Vec<_>>();
+        let responses = self
+            .http
+            .post(&self.url)
+            .json(&request)
+            .send()?
+            .error_for_status()?
+            .json::<Vec<Value>>()?;
+        let mut results = vec![Err(String::from("Missing in the batch response")); calls.len()];
+        // Responses of a batch may come in any order
+        for mut response in responses.into_iter() {
+            let Some(id) = response["id"].as_u64().map(|x| x as usize) else {
+                bail!("Batch response without id: {}", response);
+            };
+            let Some(slot) = results.get_mut(id) else {
+                bail!("Unknown id in batch response: {}", id);
+            };
+            *slot = match response["error"].take() {
+                Value::Null => Ok(response["result"].take()),
+                error => Err(error.to_string()),
+            };
+        }
+        Ok(results)
+    }
+
+    /// Fetches the cacheable calls that aren't cached yet in one batch request, so that the
+    /// panels requesting them during this refresh are answered from the cache
+    pub fn prefetch(&self, calls: &[(&str, Value)]) {
+        let mut fetches = vec![];
+        let mut batch = vec![];
+        for (method, params) in calls.iter() {
+            let Some(ttl) = ttl_of(method) else {
+                continue;
+            };
+            let key = Self::cache_key(method, params);
+            if let Lookup::Fetch(fetch) = self.lookup(&key) {
+                fetches.push((ttl, fetch));
+                batch.push((*method, params.clone()));
+            }
+        }
+        if batch.is_empty() {
+            return;
+        }
+        let results = self.batch(&batch).unwrap_or_else(|e| {
+            log::debug!("Batch request failed: {:?}", e);
+            vec![Err(e.to_string()); batch.len()]
+        });
+        for ((ttl, fetch), result) in fetches.into_iter().zip(results) {
+            fetch.finish(ttl, result);
+        }
+    }
+
+    /// Batches the requests shared by the panels of one refresh cycle
+    pub fn prefetch_refresh_cycle(&self, fetch_overview: bool) {
+        let mut calls = vec![
+            ("get_tip_header", Value::Null),
+            ("get_peers", Value::Null),
+            ("get_fee_rate_statistics", json!([null])),
+            ("sync_state", Value::Null),
+        ];
+        if fetch_overview {
+            calls.push(("get_overview", Value::Null));
+        }
+        self.prefetch(&calls);
+    }
+
+    pub fn get_overview(&self) -> anyhow::Result<Overview> {
+        self.call("get_overview", ())
+    }
+
+    pub fn get_tip_header(&self) -> anyhow::Result<HeaderView> {
+        self.call("get_tip_header", ())
+    }
+
+    pub fn get_header_by_number(&self, number: BlockNumber) -> anyhow::Result<Option<HeaderView>> {
+        self.call("get_header_by_number", (number,))
+    }
+
+    pub fn get_peers(&self) -> anyhow::Result<Vec<RemoteNode>> {
+        self.call("get_peers", ())
+    }
+
+    pub fn get_fee_rate_statistics(
+        &self,
+        target: Option<Uint64>,
+    ) -> anyhow::Result<Option<FeeRateStatistics>> {
+        self.call("get_fee_rate_statistics", (target,))
+    }
+
+    pub fn sync_state(&self) -> anyhow::Result<SyncState> {
+        self.call("sync_state", ())
+    }
+
+    pub fn get_blockchain_info(&self) -> anyhow::Result<ChainInfo> {
+        self.call("get_blockchain_info", ())
+    }
+
+    pub fn get_consensus(&self) -> anyhow::Result<Consensus> {
+        self.call("get_consensus", ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+
+    use super::{CacheEntry, CachedRpcClient, Lookup};
+
+    #[test]
+    fn coalesces_requests_and_expires_entries() {
+        // Nothing is sent to the node as long as requests are answered from the cache
+        let client = CachedRpcClient::new("http://127.0.0.1:1");
+        let key = CachedRpcClient::cache_key("get_tip_header", &json!(null));
+        let Lookup::Fetch(fetch) = client.lookup(&key) else {
+            panic!("the first lookup should fetch");
+        };
+        let Lookup::Wait(waiting) = client.lookup(&key) else {
+            panic!("concurrent lookups should wait for the pending request");
+        };
+        let waiter = std::thread::spawn(move || waiting.wait());
+        fetch.finish(Duration::from_secs(60), Ok(json!({"number": "0x1"})));
+        assert_eq!(waiter.join().unwrap(), Ok(json!({"number": "0x1"})));
+        assert!(matches!(client.lookup(&key), Lookup::Hit(_)));
+
+        client.cache.lock().unwrap().insert(
+            key.clone(),
+            CacheEntry::Ready {
+                value: json!(null),
+                expires_at: Instant::now() - Duration::from_secs(1),
+            },
+        );
+        assert!(matches!(client.lookup(&key), Lookup::Fetch(_)));
+    }
+
+    #[test]
+    fn fails_waiting_requests_of_abandoned_fetches() {
+        let client = CachedRpcClient::new("http://127.0.0.1:1");
+        let key = CachedRpcClient::cache_key("get_tip_header", &json!(null));
+        let Lookup::Fetch(fetch) = client.lookup(&key) else {
+            panic!("the first lookup should fetch");
+        };
+        let Lookup::Wait(waiting) = client.lookup(&key) else {
+            panic!("concurrent lookups should wait for the pending request");
+        };
+        let waiter = std::thread::spawn(move || waiting.wait());
+        // A fetching caller that panicked before finishing
+        let fetcher = std::thread::spawn(move || {
+            let _fetch = fetch;
+            panic!("fetch failed");
+        });
+        assert!(fetcher.join().is_err());
+        assert!(waiter.join().unwrap().unwrap_err().contains("abandoned"));
+        // Fetched again by the next request
+        assert!(matches!(client.lookup(&key), Lookup::Fetch(_)));
+    }
+}
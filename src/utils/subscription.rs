@@ -0,0 +1,408 @@
+use std::{
+    collections::BTreeSet,
+    sync::{
+        Arc, Mutex, OnceLock, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Local};
+use cursive::{
+    theme::{BaseColor, ColorStyle},
+    utils::markup::StyledString,
+};
+use serde::de::DeserializeOwned;
+use tokio_stream::StreamExt;
+
+use crate::utils::create_subscription_client;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Subscribers registered within this delay share one subscription request
+const SUBSCRIBE_DELAY: Duration = Duration::from_millis(100);
+
+/// The runtime running all subscriptions, started on first use
+pub fn async_runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("ckb-tui-async")
+            .enable_all()
+            .build()
+            .expect("Unable to start tokio runtime")
+    })
+}
+
+#[derive(Clone, Debug)]
+pub enum SubscriptionStatus {
+    Connecting {
+        attempt: u32,
+    },
+    Connected {
+        since: DateTime<Local>,
+    },
+    /// The last attempt failed or the connection was lost, will reconnect at `retry_at`
+    Failed {
+        error: String,
+        retry_at: DateTime<Local>,
+    },
+    Stopped,
+}
+
+// Shared, so that handlers are called without holding the list of subscribers
+type Handler = Arc<Mutex<dyn FnMut(serde_json::Value) + Send>>;
+
+struct Subscriber {
+    id: u64,
+    topic: &'static str,
+    handler: Handler,
+}
+
+struct Subscribers {
+    list: Vec<Subscriber>,
+    // Whether a task is maintaining the connection
+    running: bool,
+}
+
+struct MultiplexerInner {
+    addr: String,
+    subscribers: Mutex<Subscribers>,
+    status: RwLock<SubscriptionStatus>,
+    changed: tokio::sync::watch::Sender<()>,
+    next_id: AtomicU64,
+}
+
+/// One pubsub connection to a node shared by all subscribers, each topic received is fanned out
+/// to the subscribers of it. The connection is kept alive while there are subscribers, lost
+/// connections are re-established with exponential backoff, e.g. after a node restart
+#[derive(Clone)]
+pub struct SubscriptionMultiplexer {
+    inner: Arc<MultiplexerInner>,
+}
+
+impl SubscriptionMultiplexer {
+    pub fn new(addr: String) -> Self {
+        Self {
+            inner: Arc::new(MultiplexerInner {
+                addr,
+                subscribers: Mutex::new(Subscribers {
+                    list: vec![],
+                    running: false,
+                }),
+                status: RwLock::new(SubscriptionStatus::Stopped),
+                changed: tokio::sync::watch::Sender::new(()),
+                next_id: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Calls `handler` with every item of `topic` until the returned handle is stopped
+    pub fn subscribe<T, F>(&self, topic: &'static str, mut handler: F) -> SubscriptionHandle
+    where
+        T: DeserializeOwned,
+        F: FnMut(T) + Send + 'static,
+    {
+        let id = self.inner.next_id.fetch_add(1, Ordering::SeqCst);
+        let handler: Handler = Arc::new(Mutex::new(move |value| {
+            match serde_json::from_value(value) {
+                Ok(item) => handler(item),
+                Err(e) => log::warn!("Bad message of {}: {:?}", topic, e),
+            }
+        }));
+        let mut guard = self.inner.subscribers.lock().unwrap();
+        guard.list.push(Subscriber { id, topic, handler });
+        if !guard.running {
+            guard.running = true;
+            *self.inner.status.write().unwrap() = SubscriptionStatus::Connecting { attempt: 1 };
+            async_runtime().spawn(supervise(Arc::clone(&self.inner)));
+        }
+        drop(guard);
+        self.inner.changed.send_replace(());
+        SubscriptionHandle {
+            id,
+            topic,
+            multiplexer: self.clone(),
+        }
+    }
+}
+
+/// A subscriber of one topic of a [`SubscriptionMultiplexer`]
+#[derive(Clone)]
+pub struct SubscriptionHandle {
+    id: u64,
+    topic: &'static str,
+    multiplexer: SubscriptionMultiplexer,
+}
+
+impl SubscriptionHandle {
+    /// Unsubscribes the topic, the connection is closed after the last subscriber stopped
+    pub fn stop(&self) {
+        let inner = &self.multiplexer.inner;
+        inner
+            .subscribers
+            .lock()
+            .unwrap()
+            .list
+            .retain(|x| x.id != self.id);
+        inner.changed.send_replace(());
+    }
+
+    pub fn status(&self) -> SubscriptionStatus {
+        let inner = &self.multiplexer.inner;
+        let subscribed = inner
+            .subscribers
+            .lock()
+            .unwrap()
+            .list
+            .iter()
+            .any(|x| x.id == self.id);
+        if subscribed {
+            inner.status.read().unwrap().clone()
+        } else {
+            SubscriptionStatus::Stopped
+        }
+    }
+
+    /// One-line status for the dashboards, e.g. `new_tip_block: connected since 12:00:00`
+    pub fn describe(&self) -> StyledString {
+        match self.status() {
+            SubscriptionStatus::Connecting { attempt } => StyledString::styled(
+                format!("● {}: connecting (attempt {})", self.topic, attempt),
+                ColorStyle::front(BaseColor::Yellow),
+            ),
+            SubscriptionStatus::Connected { since } => StyledString::styled(
+                format!(
+                    "● {}: connected since {}",
+                    self.topic,
+                    since.format("%H:%M:%S")
+                ),
+                ColorStyle::front(BaseColor::Green),
+            ),
+            SubscriptionStatus::Failed { error, retry_at } => StyledString::styled(
+                format!(
+                    "● {}: {}, retrying in {}s",
+                    self.topic,
+                    error,
+                    (retry_at - Local::now()).num_seconds().max(0)
+                ),
+                ColorStyle::front(BaseColor::Red),
+            ),
+            SubscriptionStatus::Stopped => {
+                StyledString::plain(format!("● {}: stopped", self.topic))
+            }
+        }
+    }
+}
+
+impl MultiplexerInner {
+    fn set_status(&self, status: SubscriptionStatus) {
+        *self.status.write().unwrap() = status;
+    }
+
+    /// Topics to subscribe, or `None` if the last subscriber has stopped
+    fn topics_or_finish(&self) -> Option<BTreeSet<&'static str>> {
+        let mut guard = self.subscribers.lock().unwrap();
+        if guard.list.is_empty() {
+            guard.running = false;
+            self.set_status(SubscriptionStatus::Stopped);
+            return None;
+        }
+        Some(guard.list.iter().map(|x| x.topic).collect())
+    }
+
+    /// Calls the handlers of `topic` after releasing the list, as they may subscribe or stop
+    /// subscriptions themselves
+    fn dispatch(&self, topic: &str, value: serde_json::Value) {
+        let handlers = self
+            .subscribers
+            .lock()
+            .unwrap()
+            .list
+            .iter()
+            .filter(|x| x.topic == topic)
+            .map(|x| Arc::clone(&x.handler))
+            .collect::<Vec<_>>();
+        for handler in handlers {
+            (handler.lock().unwrap())(value.clone());
+        }
+    }
+}
+
+async fn supervise(inner: Arc<MultiplexerInner>) {
+    let addr = inner.addr.clone();
+    log::info!("Subscription task of {} started", addr);
+    let mut changed = inner.changed.subscribe();
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    'connect: loop {
+        tokio::time::sleep(SUBSCRIBE_DELAY).await;
+        changed.mark_unchanged();
+        let Some(topics) = inner.topics_or_finish() else {
+            break;
+        };
+        attempt += 1;
+        inner.set_status(SubscriptionStatus::Connecting { attempt });
+        let connect = async {
+            create_subscription_client(&addr)
+                .await
+                .with_context(|| anyhow!("Unable to connect to: {}", addr))?
+                .subscribe_list::<serde_json::Value, _, _>(topics.iter())
+                .await
+                .with_context(|| anyhow!("Unable to subscribe {:?}", topics))
+        };
+        let result = tokio::select! {
+            _ = changed.changed() => {
+                attempt = 0;
+                continue 'connect;
+            }
+            result = connect => result,
+        };
+        let error = match result {
+            Ok(mut subscription) => {
+                log::info!("Subscribed {:?} of {}", topics, addr);
+                inner.set_status(SubscriptionStatus::Connected {
+                    since: Local::now(),
+                });
+                attempt = 0;
+                backoff = INITIAL_BACKOFF;
+                loop {
+                    tokio::select! {
+                        // Subscribe again with the new topics
+                        _ = changed.changed() => continue 'connect,
+                        item = subscription.next() => match item {
+                            Some(Ok((topic, value))) => inner.dispatch(&topic, value),
+                            Some(Err(e)) => log::warn!("Bad message of {}: {:?}", addr, e),
+                            None => break,
+                        }
+                    }
+                }
+                String::from("connection closed by the node")
+            }
+            Err(e) => format!("{:#}", e),
+        };
+        log::warn!(
+            "Subscription of {} failed: {}, retrying in {:?}",
+            addr,
+            error,
+            backoff
+        );
+        inner.set_status(SubscriptionStatus::Failed {
+            error,
+            retry_at: Local::now() + chrono::Duration::from_std(backoff).unwrap(),
+        });
+        tokio::select! {
+            _ = changed.changed() => {}
+            _ = tokio::time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+    log::info!("Subscription task of {} exited", addr);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::{Duration, Instant},
+    };
+
+    use super::{SubscriptionHandle, SubscriptionMultiplexer, SubscriptionStatus};
+    use crate::mock_node::{MockNode, fixtures, wait_until};
+
+    #[test]
+    fn reports_failure_and_stops() {
+        // Nothing listens on the port of a dropped listener
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let multiplexer = SubscriptionMultiplexer::new(addr.to_string());
+        let logs = multiplexer.subscribe("log", |_: serde_json::Value| {});
+        let blocks = multiplexer.subscribe("new_tip_block", |_: serde_json::Value| {});
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !matches!(blocks.status(), SubscriptionStatus::Failed { .. }) {
+            assert!(Instant::now() < deadline, "subscription never failed");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        logs.stop();
+        assert!(matches!(logs.status(), SubscriptionStatus::Stopped));
+        assert!(!matches!(blocks.status(), SubscriptionStatus::Stopped));
+        blocks.stop();
+        while multiplexer.inner.subscribers.lock().unwrap().running {
+            assert!(Instant::now() < deadline, "subscription task never exited");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn resubscribes_after_the_node_restarts() {
+        let node = MockNode::start();
+        let multiplexer = SubscriptionMultiplexer::new(node.tcp_url());
+        let received = Arc::new(AtomicUsize::new(0));
+        let blocks = {
+            let received = Arc::clone(&received);
+            multiplexer.subscribe("new_tip_block", move |_: serde_json::Value| {
+                received.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+        node.wait_for_subscriber("new_tip_block");
+        node.disconnect_subscribers();
+        // Subscribed again after the initial backoff
+        node.wait_for_subscriber("new_tip_block");
+        node.publish(
+            "new_tip_block",
+            fixtures::new_tip_block(fixtures::TIP_NUMBER + 1),
+        );
+        wait_until("a block after reconnecting", || {
+            received.load(Ordering::SeqCst) == 1
+        });
+        assert!(matches!(
+            blocks.status(),
+            SubscriptionStatus::Connected { .. }
+        ));
+        blocks.stop();
+    }
+
+    #[test]
+    fn handlers_may_subscribe_and_stop() {
+        let node = MockNode::start();
+        let multiplexer = SubscriptionMultiplexer::new(node.tcp_url());
+        let logs = Arc::new(Mutex::new(None));
+        let blocks = Arc::new(Mutex::new(None::<SubscriptionHandle>));
+        let handle = {
+            let resubscriber = multiplexer.clone();
+            let logs = Arc::clone(&logs);
+            let blocks = Arc::clone(&blocks);
+            multiplexer.subscribe("new_tip_block", move |_: serde_json::Value| {
+                // Like a node switch replacing the subscriptions
+                if let Some(blocks) = blocks.lock().unwrap().take() {
+                    blocks.stop();
+                }
+                logs.lock().unwrap().get_or_insert_with(|| {
+                    resubscriber.subscribe("log", |_: serde_json::Value| {})
+                });
+            })
+        };
+        *blocks.lock().unwrap() = Some(handle.clone());
+        node.wait_for_subscriber("new_tip_block");
+        node.publish(
+            "new_tip_block",
+            fixtures::new_tip_block(fixtures::TIP_NUMBER + 1),
+        );
+        node.wait_for_subscriber("log");
+        wait_until("the handler to stop its subscription", || {
+            matches!(handle.status(), SubscriptionStatus::Stopped)
+        });
+        let logs = logs.lock().unwrap().take().unwrap();
+        wait_until("the new subscription", || {
+            matches!(logs.status(), SubscriptionStatus::Connected { .. })
+        });
+        logs.stop();
+    }
+}